@@ -0,0 +1,137 @@
+import * as anchor from "@coral-xyz/anchor";
+import { PublicKey, Connection, SystemProgram, Keypair, Transaction } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID, getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+// Compute units consumed by claim_tokens as the batch grows, on a fresh local validator.
+// This file targets the current program: zero-copy batches, the 4-argument claimTokens and
+// the referral, profile, tier and breaker accounts. The Borsh QRBatch build (5921264) takes
+// neither, so compare layouts with this benchmark as of 8427a2e
+// (attached_assets/claim_compute_bench_1763200000001.ts), run once against a build of 5921264
+// and once against 2b7527a (point USV_TOKEN_IDL at each build's IDL). It lives outside tests/
+// so `anchor test` does not run it.
+const BATCH_SIZES = [200, 1_000, 2_000, 5_000, 10_000];
+const MAX_QR_CODES_PER_APPEND = 200;
+const QR_CODE_LEN = 16;
+const QR_BATCH_HEADER_LEN = 8 + 248;
+
+describe("Claim Compute Benchmark", () => {
+  const connection = new Connection("http://localhost:8899", "confirmed");
+  const idl = require(process.env.USV_TOKEN_IDL ?? "../target/idl/usv_token.json");
+  const programId = new PublicKey(idl.metadata.address);
+
+  const authority = Keypair.generate();
+  const wallet = new anchor.Wallet(authority);
+  const provider = new anchor.AnchorProvider(connection, wallet, {});
+  const program = new anchor.Program(idl, programId, provider);
+
+  const [usvStatePDA] = PublicKey.findProgramAddressSync([Buffer.from("usv_state")], programId);
+  const [loyaltyTiersPDA] = PublicKey.findProgramAddressSync([Buffer.from("loyalty_tiers")], programId);
+  const [mintPDA] = PublicKey.findProgramAddressSync([Buffer.from("mint")], programId);
+  const [mintAuthorityPDA] = PublicKey.findProgramAddressSync([Buffer.from("mint_authority")], programId);
+  const authorityTokenAccount = getAssociatedTokenAddressSync(mintPDA, authority.publicKey);
+  const budget = anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 });
+
+  const batchPDA = (batchNumber: number) => {
+    const seed = Buffer.alloc(4);
+    seed.writeUInt32LE(batchNumber);
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("qr_batch"), authority.publicKey.toBuffer(), seed],
+      programId
+    )[0];
+  };
+
+  // Zero-copy batches store raw codes after the header
+  const readQrCode = async (qrBatch: PublicKey, index: number): Promise<number[]> => {
+    const info = await connection.getAccountInfo(qrBatch);
+    const start = QR_BATCH_HEADER_LEN + index * QR_CODE_LEN;
+    return Array.from(info!.data.subarray(start, start + QR_CODE_LEN));
+  };
+
+  before(async () => {
+    const signature = await connection.requestAirdrop(authority.publicKey, 100 * anchor.web3.LAMPORTS_PER_SOL);
+    await connection.confirmTransaction(signature);
+
+    await program.methods
+      .initialize()
+      .accounts({
+        usvState: usvStatePDA,
+        loyaltyTiers: loyaltyTiersPDA,
+        mint: mintPDA,
+        mintAuthority: mintAuthorityPDA,
+        authorityTokenAccount,
+        authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+  });
+
+  it("Measures compute units per claim", async () => {
+    const results: { batchSize: number; unitsConsumed: number | string }[] = [];
+
+    for (const [batchNumber, batchSize] of BATCH_SIZES.entries()) {
+      const qrBatch = batchPDA(batchNumber);
+
+      try {
+        await program.methods
+          .createBatch(null, `bench ${batchSize}`)
+          .accounts({ usvState: usvStatePDA, qrBatch, authority: authority.publicKey, systemProgram: SystemProgram.programId })
+          .rpc();
+
+        for (let filled = 0; filled < batchSize; filled += MAX_QR_CODES_PER_APPEND) {
+          await program.methods
+            .appendQrCodes(Math.min(MAX_QR_CODES_PER_APPEND, batchSize - filled))
+            .accounts({ usvState: usvStatePDA, qrBatch, authority: authority.publicKey, systemProgram: SystemProgram.programId })
+            .preInstructions([budget])
+            .rpc();
+        }
+
+        // Claim the last code, the furthest into the batch
+        const codeIndex = batchSize - 1;
+        const qrCode = await readQrCode(qrBatch, codeIndex);
+        const claimer = Keypair.generate();
+        const [qrClaim] = PublicKey.findProgramAddressSync([Buffer.from("qr_claim"), Buffer.from(qrCode)], programId);
+
+        const tx: Transaction = await program.methods
+          .claimTokens(qrCode, codeIndex, null, null)
+          .accounts({
+            usvState: usvStatePDA,
+            qrBatch,
+            batchBreaker: null, // The per-batch circuit breaker is off
+            qrClaim,
+            authorityTokenAccount,
+            claimerTokenAccount: getAssociatedTokenAddressSync(mintPDA, claimer.publicKey),
+            referral: PublicKey.findProgramAddressSync([Buffer.from("referral"), claimer.publicKey.toBuffer()], programId)[0],
+            userProfile: PublicKey.findProgramAddressSync([Buffer.from("user_profile"), claimer.publicKey.toBuffer()], programId)[0],
+            loyaltyTiers: loyaltyTiersPDA,
+            referrerReferral: null,
+            referrerTokenAccount: null,
+            mint: mintPDA,
+            authority: authority.publicKey,
+            claimer: claimer.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([budget])
+          .transaction();
+        tx.feePayer = authority.publicKey;
+        tx.recentBlockhash = (await connection.getLatestBlockhash()).blockhash;
+        tx.sign(authority);
+
+        const simulation = await connection.simulateTransaction(tx);
+        results.push({
+          batchSize,
+          unitsConsumed: simulation.value.err ? `failed: ${JSON.stringify(simulation.value.err)}` : simulation.value.unitsConsumed!,
+        });
+      } catch (err: any) {
+        results.push({ batchSize, unitsConsumed: `failed: ${err.message}` });
+      }
+    }
+
+    console.log("📊 claim_tokens compute units by batch size");
+    console.table(results);
+  });
+});
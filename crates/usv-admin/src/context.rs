@@ -0,0 +1,104 @@
+// crates/usv-admin/src/context.rs - Cluster, signer and output handling shared by every command
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use serde_json::{json, Map, Value};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use usv_client::cluster_url;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+pub struct Context {
+    pub rpc: RpcClient,
+    payer: Keypair,
+    dry_run: bool,
+    compute_units: Option<u32>,
+    output: OutputFormat,
+}
+
+impl Context {
+    pub fn new(
+        url: &str,
+        keypair: &str,
+        dry_run: bool,
+        compute_units: Option<u32>,
+        output: OutputFormat,
+    ) -> Result<Self> {
+        let keypair_path = shellexpand::tilde(keypair);
+        let payer = read_keypair_file(keypair_path.as_ref())
+            .map_err(|err| anyhow!("failed to read keypair {}: {}", keypair_path, err))?;
+        Ok(Context {
+            rpc: RpcClient::new_with_commitment(cluster_url(url).to_string(), CommitmentConfig::confirmed()),
+            payer,
+            dry_run,
+            compute_units,
+            output,
+        })
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    // Sign and send the instructions, or simulate them with --dry-run
+    pub fn execute(&self, instructions: Vec<Instruction>) -> Result<Map<String, Value>> {
+        let mut all = Vec::with_capacity(instructions.len() + 1);
+        if let Some(units) = self.compute_units {
+            all.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+        }
+        all.extend(instructions);
+
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(&all, Some(&self.payer()), &[&self.payer], blockhash);
+
+        let mut out = Map::new();
+        if self.dry_run {
+            let simulation = self.rpc.simulate_transaction(&tx)?.value;
+            out.insert("simulated".into(), json!(true));
+            out.insert("error".into(), json!(simulation.err.map(|err| err.to_string())));
+            out.insert("units_consumed".into(), json!(simulation.units_consumed));
+            out.insert("logs".into(), json!(simulation.logs.unwrap_or_default()));
+        } else {
+            let signature = self.rpc.send_and_confirm_transaction(&tx)?;
+            out.insert("signature".into(), json!(signature.to_string()));
+        }
+        Ok(out)
+    }
+
+    pub fn report(&self, out: Map<String, Value>) {
+        match self.output {
+            OutputFormat::Json => println!("{}", Value::Object(out)),
+            OutputFormat::Human => {
+                for (key, value) in out {
+                    match value {
+                        Value::Array(items) => {
+                            println!("{}:", key);
+                            for item in items {
+                                println!("  {}", display(&item));
+                            }
+                        }
+                        value => println!("{}: {}", key, display(&value)),
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => "-".to_string(),
+        value => value.to_string(),
+    }
+}
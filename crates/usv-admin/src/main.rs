@@ -0,0 +1,1372 @@
+// crates/usv-admin/src/main.rs - Command-line administration for the USV programs
+
+mod context;
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token;
+use clap::{Parser, Subcommand};
+use serde_json::{json, Map, Value};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_instruction;
+use usv_client::usv_qr::QrPayload;
+use usv_client::usv_trading::allowlist;
+use usv_client::usv_trading::curve::{BondingCurve, CurveKind};
+use usv_client::{accounts, nft_auth, pda, token, trading};
+use usv_sheets::{Format, Layout};
+
+use crate::context::{Context, OutputFormat};
+
+#[derive(Parser)]
+#[command(name = "usv-admin", version, about = "Administer the USV token, trading and NFT programs")]
+struct Cli {
+    /// RPC URL or moniker (localnet, devnet, testnet, mainnet-beta)
+    #[arg(short, long, global = true, env = "USV_URL", default_value = "localnet")]
+    url: String,
+
+    /// Keypair that signs and pays for transactions
+    #[arg(short, long, global = true, env = "USV_KEYPAIR", default_value = "~/.config/solana/id.json")]
+    keypair: String,
+
+    /// Simulate transactions instead of sending them
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Compute unit limit to request for each transaction
+    #[arg(long, global = true)]
+    compute_units: Option<u32>,
+
+    /// Output format
+    #[arg(short, long, global = true, value_enum, default_value_t = OutputFormat::Human)]
+    output: OutputFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// usv_token: QR batches, claims and partner transfers
+    #[command(subcommand)]
+    Token(TokenCommand),
+    /// usv_trading: fixed-price sales
+    #[command(subcommand)]
+    Trading(TradingCommand),
+    /// nft_auth_program: registered pieces and NFT mints
+    #[command(subcommand)]
+    Nft(NftCommand),
+}
+
+#[derive(Subcommand)]
+enum TokenCommand {
+    /// Create the program state and mint the initial supply
+    Initialize,
+    /// Create and seal a batch of QR codes in one transaction
+    GenerateQrCodes {
+        #[arg(long)]
+        count: u32,
+        #[arg(long)]
+        partner_id: Option<String>,
+        #[arg(long, default_value = "")]
+        batch_info: String,
+    },
+    /// Create an empty batch to fill with append-qr-codes
+    CreateBatch {
+        #[arg(long)]
+        partner_id: Option<String>,
+        #[arg(long, default_value = "")]
+        batch_info: String,
+    },
+    /// Add codes to an unsealed batch
+    AppendQrCodes {
+        #[arg(long)]
+        batch: Pubkey,
+        #[arg(long)]
+        count: u32,
+    },
+    /// Freeze a batch against further appends
+    SealBatch {
+        #[arg(long)]
+        batch: Pubkey,
+    },
+    /// Claim tokens for a QR code; the keypair pays for the claim
+    Claim {
+        /// Scanned payload, e.g. usv:1/<batch>/<index>/<code>/<check>
+        payload: QrPayload,
+        #[arg(long)]
+        email: Option<String>,
+        /// Wallet that referred the claimer; only counts on a first claim
+        #[arg(long)]
+        referrer: Option<Pubkey>,
+    },
+    /// Send tokens from the authority account to a partner wallet
+    TransferToPartner {
+        #[arg(long)]
+        partner: Pubkey,
+        /// Amount in base units
+        #[arg(long)]
+        amount: u64,
+        #[arg(long, default_value = "")]
+        partner_info: String,
+    },
+    /// Stop claims
+    Pause,
+    /// Resume claims
+    Unpause,
+    /// Upgrade a state account created before referrals to the current layout
+    MigrateState,
+    /// Replace the loyalty tiers; an empty list pays every claim the base amount
+    SetLoyaltyTiers {
+        /// Tiers as NAME:MIN_CLAIMS:MULTIPLIER_BPS, e.g. Bronze:0:10000 Silver:10:12500
+        #[arg(value_parser = parse_tier)]
+        tiers: Vec<accounts::LoyaltyTier>,
+    },
+    /// Show the loyalty tiers
+    Tiers,
+    /// Show a wallet's claim profile
+    Profile { wallet: Pubkey },
+    /// Limit what one wallet can claim over a rolling day
+    SetClaimLimits {
+        /// Claims per wallet per day; 0 for no limit
+        #[arg(long, default_value_t = 0)]
+        claims: u32,
+        /// Tokens per wallet per day in base units; 0 for no limit
+        #[arg(long, default_value_t = 0)]
+        tokens: u64,
+    },
+    /// Replace the guardians allowed to vote for an emergency pause
+    SetGuardians {
+        /// Guardian wallets, comma-separated; empty disables guardian pauses
+        #[arg(long, value_delimiter = ',')]
+        guardians: Vec<Pubkey>,
+        /// Votes needed to pause
+        #[arg(long)]
+        threshold: u8,
+    },
+    /// Vote to pause claims as a guardian; the keypair must be a guardian
+    GuardianPause,
+    /// Pause claims automatically when too many land in one time bucket
+    SetCircuitBreaker {
+        /// Bucket length in seconds; 0 disables the breaker
+        #[arg(long)]
+        bucket_secs: u32,
+        /// Program-wide claims per bucket that trip the breaker; 0 for no limit
+        #[arg(long, default_value_t = 0)]
+        max_claims: u32,
+        /// Claims from a single batch per bucket that trip the breaker; 0 for no limit
+        #[arg(long, default_value_t = 0)]
+        max_batch_claims: u32,
+    },
+    /// Exempt a wallet from claim limits
+    Trust { wallet: Pubkey },
+    /// Subject a trusted wallet to claim limits again
+    Untrust { wallet: Pubkey },
+    /// Set the bonus paid to referrers on a referred wallet's first claim
+    SetReferralBonus {
+        /// Amount in base units; 0 disables referral bonuses
+        amount: u64,
+    },
+    /// Emit the ProgramStats event
+    GetStats,
+    /// Show the program state
+    State,
+    /// Show a QR batch
+    Batch {
+        address: Pubkey,
+        /// Also list the printable payload of every code in the batch
+        #[arg(long)]
+        codes: bool,
+    },
+    /// Render printable label sheets and a CSV manifest for a batch
+    ExportSheets {
+        address: Pubkey,
+        /// Directory for sheet-NNNN.<format> files and manifest.csv
+        #[arg(long)]
+        out: PathBuf,
+        /// Preset (a4-3x8, letter-3x10) or path to a JSON layout file
+        #[arg(long, default_value = "a4-3x8")]
+        layout: String,
+        #[arg(long, value_delimiter = ',', default_value = "png,svg")]
+        formats: Vec<Format>,
+    },
+}
+
+#[derive(Subcommand)]
+enum TradingCommand {
+    /// Create the trading state
+    Initialize {
+        /// Defaults to the usv_token mint
+        #[arg(long)]
+        usv_mint: Option<Pubkey>,
+        /// Pyth SOL/USD feed id as hex; defaults to the mainnet SOL/USD feed
+        #[arg(long, value_parser = parse_feed_id)]
+        price_feed_id: Option<[u8; 32]>,
+        /// Wallet receiving SOL proceeds; defaults to the keypair
+        #[arg(long)]
+        treasury: Option<Pubkey>,
+    },
+    /// Send SOL proceeds to another wallet
+    SetTreasury {
+        treasury: Pubkey,
+    },
+    /// Move tokens from the keypair's token account into the sale inventory
+    DepositInventory {
+        /// Amount in token base units
+        #[arg(long)]
+        amount: u64,
+    },
+    /// Move unsold tokens out of the sale inventory
+    WithdrawInventory {
+        /// Amount in token base units
+        #[arg(long)]
+        amount: u64,
+        /// Token account to receive them; defaults to the keypair's associated token account
+        #[arg(long)]
+        destination: Option<Pubkey>,
+    },
+    /// Buy tokens at the current price with the keypair as buyer
+    Buy {
+        /// Amount in lamports
+        #[arg(long)]
+        sol_amount: u64,
+        /// Pyth price update account; defaults to the push oracle account for the configured feed
+        #[arg(long)]
+        price_update: Option<Pubkey>,
+        /// Fail unless at least this many token base units are delivered
+        #[arg(long, default_value_t = 0)]
+        min_tokens_out: u64,
+        /// Fail if the purchase has not landed this many seconds from now
+        #[arg(long, default_value_t = 60)]
+        deadline_secs: u64,
+    },
+    /// Buy tokens at the current price with a whitelisted SPL token, paying from the keypair's token account
+    BuySpl {
+        /// Payment token mint
+        #[arg(long)]
+        mint: Pubkey,
+        /// Amount in payment token base units
+        #[arg(long)]
+        amount: u64,
+        /// Fail unless at least this many token base units are delivered
+        #[arg(long, default_value_t = 0)]
+        min_tokens_out: u64,
+        /// Fail if the purchase has not landed this many seconds from now
+        #[arg(long, default_value_t = 60)]
+        deadline_secs: u64,
+    },
+    /// Add a presale round after the existing ones
+    AddRound {
+        /// Unix timestamp the round opens; defaults to now
+        #[arg(long)]
+        start_time: Option<i64>,
+        /// Unix timestamp the round closes
+        #[arg(long)]
+        end_time: i64,
+        #[arg(long)]
+        price_cents: u64,
+        /// Token base units for sale in the round
+        #[arg(long)]
+        cap: u64,
+        /// Token base units each wallet may buy; 0 for no limit
+        #[arg(long, default_value_t = 0)]
+        per_wallet_cap: u64,
+        /// File with one wallet address per line; only those wallets may buy
+        #[arg(long)]
+        allowlist: Option<PathBuf>,
+        /// Lock every purchase in the round in the buyer's vesting escrow
+        #[arg(long)]
+        vests: bool,
+    },
+    /// Change the terms of the current or a later round, keeping any not given
+    UpdateRound {
+        index: u32,
+        #[arg(long)]
+        start_time: Option<i64>,
+        #[arg(long)]
+        end_time: Option<i64>,
+        #[arg(long)]
+        price_cents: Option<u64>,
+        #[arg(long)]
+        cap: Option<u64>,
+        #[arg(long)]
+        per_wallet_cap: Option<u64>,
+        /// File with one wallet address per line; only those wallets may buy
+        #[arg(long, conflicts_with = "no_allowlist")]
+        allowlist: Option<PathBuf>,
+        /// Let any wallet buy
+        #[arg(long)]
+        no_allowlist: bool,
+        /// Whether purchases in the round vest
+        #[arg(long)]
+        vests: Option<bool>,
+    },
+    /// Buy in the current sale round with the keypair as buyer
+    BuyRound {
+        /// Amount in lamports
+        #[arg(long)]
+        sol_amount: u64,
+        /// Pyth price update account; defaults to the push oracle account for the configured feed
+        #[arg(long)]
+        price_update: Option<Pubkey>,
+        /// Fail unless at least this many token base units are delivered
+        #[arg(long, default_value_t = 0)]
+        min_tokens_out: u64,
+        /// Fail if the purchase has not landed this many seconds from now
+        #[arg(long, default_value_t = 60)]
+        deadline_secs: u64,
+        /// The round's allowlist file, to prove the keypair is on it
+        #[arg(long)]
+        allowlist: Option<PathBuf>,
+    },
+    /// Close the current sale round once it has ended or sold out
+    AdvanceRound,
+    /// Lock large purchases and purchases in vesting rounds in per-buyer escrows
+    SetVesting {
+        /// Purchases of more token base units vest; 0 vests only in vesting rounds
+        #[arg(long, default_value_t = 0)]
+        threshold: u64,
+        /// Seconds before anything unlocks
+        #[arg(long, default_value_t = 0)]
+        cliff_secs: i64,
+        /// Seconds until everything has unlocked
+        #[arg(long)]
+        duration_secs: i64,
+    },
+    /// Release the keypair's unlocked vesting tokens to its token account
+    ReleaseVested,
+    /// Show a wallet's vesting escrow
+    Vesting {
+        /// Defaults to the keypair
+        wallet: Option<Pubkey>,
+    },
+    /// Show a sale round
+    Round {
+        /// Defaults to the current round
+        index: Option<u32>,
+    },
+    /// Redeem tokens for SOL from the reserve at the bid price
+    Sell {
+        /// Amount in token base units
+        #[arg(long)]
+        token_amount: u64,
+        /// Pyth price update account; defaults to the push oracle account for the configured feed
+        #[arg(long)]
+        price_update: Option<Pubkey>,
+        /// Fail unless at least this many lamports are paid out
+        #[arg(long, default_value_t = 0)]
+        min_lamports_out: u64,
+        /// Fail if the redemption has not landed this many seconds from now
+        #[arg(long, default_value_t = 60)]
+        deadline_secs: u64,
+    },
+    /// Redeem tokens for a whitelisted SPL token from its reserve, paid to the keypair's token account
+    SellSpl {
+        /// Payout token mint
+        #[arg(long)]
+        mint: Pubkey,
+        /// Amount in token base units
+        #[arg(long)]
+        token_amount: u64,
+        /// Fail unless at least this many payout token base units are paid out
+        #[arg(long, default_value_t = 0)]
+        min_payout: u64,
+        /// Fail if the redemption has not landed this many seconds from now
+        #[arg(long, default_value_t = 60)]
+        deadline_secs: u64,
+    },
+    /// Set the redemption bid price, daily cap and reserve ratio
+    SetRedemption {
+        /// Bid price in USD cents; 0 disables redemption
+        #[arg(long)]
+        bid_cents: u64,
+        /// Token base units redeemable per day; 0 removes the cap
+        #[arg(long, default_value_t = 0)]
+        max_per_day: u64,
+        /// Share of the bid value of circulating tokens each reserve must keep
+        #[arg(long, default_value_t = 0)]
+        min_reserve_ratio_bps: u64,
+        /// Token account funding QR claims; defaults to the token program authority's
+        #[arg(long)]
+        claim_vault: Option<Pubkey>,
+    },
+    /// Move SOL from the keypair into the redemption reserve
+    FundReserve {
+        #[arg(long)]
+        lamports: u64,
+    },
+    /// Price purchases on a linear (--slope-micros) or exponential (--growth-bps, --step) bonding curve
+    SetCurve {
+        /// Price of the first token, in USD millionths
+        #[arg(long)]
+        start_price_micros: u64,
+        /// Price rise per whole token sold, in USD millionths
+        #[arg(long, conflicts_with_all = ["growth_bps", "step"], required_unless_present = "growth_bps")]
+        slope_micros: Option<u64>,
+        /// Price rise per step
+        #[arg(long, requires = "step")]
+        growth_bps: Option<u64>,
+        /// Token base units sold per step
+        #[arg(long, requires = "growth_bps")]
+        step: Option<u64>,
+        /// Most token base units to sell on the curve; 0 for no cap
+        #[arg(long, default_value_t = 0)]
+        supply_cap: u64,
+    },
+    /// Return purchases to the fixed price
+    ClearCurve,
+    /// Create the USV/SOL pool
+    InitPool {
+        /// Swap fee paid to liquidity providers
+        #[arg(long, default_value_t = 30)]
+        fee_bps: u64,
+        /// Reject swaps that move the price more than this
+        #[arg(long, default_value_t = 500)]
+        max_price_impact_bps: u64,
+    },
+    /// Deposit SOL and the matching USV into the pool from the keypair
+    AddLiquidity {
+        #[arg(long)]
+        sol_amount: u64,
+        /// Most USV base units to deposit; the first deposit uses exactly this and sets the price
+        #[arg(long)]
+        max_usv_amount: u64,
+        #[arg(long, default_value_t = 0)]
+        min_lp_out: u64,
+    },
+    /// Burn LP tokens for their share of the pool
+    RemoveLiquidity {
+        #[arg(long)]
+        lp_amount: u64,
+        #[arg(long, default_value_t = 0)]
+        min_sol_out: u64,
+        #[arg(long, default_value_t = 0)]
+        min_usv_out: u64,
+    },
+    /// Swap SOL for USV against the pool, or USV for SOL with --sell
+    Swap {
+        /// Lamports, or USV base units with --sell
+        #[arg(long)]
+        amount_in: u64,
+        #[arg(long)]
+        sell: bool,
+        #[arg(long, default_value_t = 0)]
+        min_amount_out: u64,
+        /// Fail if the swap has not landed this many seconds from now
+        #[arg(long, default_value_t = 60)]
+        deadline_secs: u64,
+    },
+    /// Set the pool's swap fee and price impact limit
+    SetPoolConfig {
+        #[arg(long)]
+        fee_bps: u64,
+        #[arg(long)]
+        max_price_impact_bps: u64,
+    },
+    /// Show the pool
+    Pool,
+    /// Accept an SPL token as payment
+    AddPaymentMint {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Token account receiving payments; defaults to the keypair's associated token account
+        #[arg(long)]
+        treasury: Option<Pubkey>,
+        /// USD value of one whole token in millionths
+        #[arg(long, default_value_t = 1_000_000)]
+        usd_price_micros: u64,
+    },
+    /// Reprice, redirect or disable an accepted payment token
+    UpdatePaymentMint {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Defaults to the current treasury
+        #[arg(long)]
+        treasury: Option<Pubkey>,
+        /// Defaults to the current price
+        #[arg(long)]
+        usd_price_micros: Option<u64>,
+        #[arg(long)]
+        disabled: bool,
+    },
+    /// Show an accepted payment token
+    PaymentMint {
+        mint: Pubkey,
+    },
+    /// Configure the Pyth feed used to price purchases
+    UpdateOracle {
+        /// Pyth SOL/USD feed id as hex
+        #[arg(long, value_parser = parse_feed_id)]
+        price_feed_id: [u8; 32],
+        /// Reject prices published longer ago than this
+        #[arg(long, default_value_t = usv_client::usv_trading::DEFAULT_MAX_PRICE_AGE_SECS)]
+        max_age_secs: u64,
+        /// Reject prices whose confidence interval exceeds this share of the price
+        #[arg(long, default_value_t = usv_client::usv_trading::DEFAULT_MAX_CONFIDENCE_BPS)]
+        max_confidence_bps: u64,
+    },
+    /// Set the fixed price in USD cents
+    UpdatePrice {
+        #[arg(long)]
+        cents: u64,
+    },
+    /// Enable purchases
+    Resume,
+    /// Disable purchases
+    Pause,
+    /// Replace the guardians allowed to vote for an emergency pause
+    SetGuardians {
+        /// Guardian wallets, comma-separated; empty disables guardian pauses
+        #[arg(long, value_delimiter = ',')]
+        guardians: Vec<Pubkey>,
+        /// Votes needed to pause
+        #[arg(long)]
+        threshold: u8,
+    },
+    /// Vote to pause trading as a guardian; the keypair must be a guardian
+    GuardianPause,
+    /// Emit the TradingStats event
+    GetStats,
+    /// Show the trading state
+    State,
+}
+
+#[derive(Subcommand)]
+enum NftCommand {
+    /// Create the program state and master collection
+    Initialize,
+    /// Register a QR code as a numbered piece
+    RegisterQr {
+        code: String,
+    },
+    /// Mint the NFT piece for a registered QR code to the keypair
+    MintNft {
+        code: String,
+    },
+    /// Stop minting; also creates the mint guard for collections that predate it
+    Pause,
+    /// Resume minting
+    Unpause,
+    /// Replace the guardians allowed to vote for an emergency pause
+    SetGuardians {
+        /// Guardian wallets, comma-separated; empty disables guardian pauses
+        #[arg(long, value_delimiter = ',')]
+        guardians: Vec<Pubkey>,
+        /// Votes needed to pause
+        #[arg(long)]
+        threshold: u8,
+    },
+    /// Vote to pause minting as a guardian; the keypair must be a guardian
+    GuardianPause,
+    /// Show the program state
+    State,
+    /// Show a registered QR code
+    Qr {
+        code: String,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let ctx = Context::new(&cli.url, &cli.keypair, cli.dry_run, cli.compute_units, cli.output)?;
+
+    match cli.command {
+        Command::Token(command) => run_token(&ctx, command),
+        Command::Trading(command) => run_trading(&ctx, command),
+        Command::Nft(command) => run_nft(&ctx, command),
+    }
+}
+
+// New batches are addressed by the running batch counter
+fn next_batch(ctx: &Context) -> Result<(u32, Pubkey)> {
+    let state: accounts::USVState = accounts::fetch(&ctx.rpc, &pda::usv_state().0)?;
+    Ok((state.total_batches, pda::qr_batch(&ctx.payer(), state.total_batches).0))
+}
+
+fn run_token(ctx: &Context, command: TokenCommand) -> Result<()> {
+    let authority = ctx.payer();
+
+    let out = match command {
+        TokenCommand::Initialize => ctx.execute(vec![token::initialize(&authority)])?,
+        TokenCommand::GenerateQrCodes {
+            count,
+            partner_id,
+            batch_info,
+        } => {
+            let (batch_number, batch) = next_batch(ctx)?;
+            let mut out = ctx.execute(vec![token::generate_qr_codes(
+                &authority,
+                batch_number,
+                count,
+                partner_id,
+                batch_info,
+            )])?;
+            out.insert("batch".into(), json!(batch.to_string()));
+            out
+        }
+        TokenCommand::CreateBatch {
+            partner_id,
+            batch_info,
+        } => {
+            let (batch_number, batch) = next_batch(ctx)?;
+            let mut out = ctx.execute(vec![token::create_batch(
+                &authority,
+                batch_number,
+                partner_id,
+                batch_info,
+            )])?;
+            out.insert("batch".into(), json!(batch.to_string()));
+            out
+        }
+        TokenCommand::AppendQrCodes { batch, count } => {
+            ctx.execute(vec![token::append_qr_codes(&authority, &batch, count)])?
+        }
+        TokenCommand::SealBatch { batch } => ctx.execute(vec![token::seal_batch(&authority, &batch)])?,
+        TokenCommand::Claim {
+            payload,
+            email,
+            referrer,
+        } => {
+            let batch = Pubkey::new_from_array(payload.batch);
+            // Claims are co-signed by the batch authority, which is the keypair here too
+            let header = accounts::fetch_qr_batch(&ctx.rpc, &batch)?.header;
+            if header.authority != authority {
+                return Err(anyhow!("batch {} is not owned by {}", batch, authority));
+            }
+            let state: accounts::USVState = accounts::fetch(&ctx.rpc, &pda::usv_state().0)?;
+            ctx.execute(vec![token::claim_tokens(
+                &authority,
+                &authority,
+                &batch,
+                payload.code,
+                payload.index,
+                email,
+                referrer.as_ref(),
+                token::batch_breaker_required(&state),
+            )])?
+        }
+        TokenCommand::TransferToPartner {
+            partner,
+            amount,
+            partner_info,
+        } => ctx.execute(vec![token::transfer_to_partner(
+            &authority,
+            &partner,
+            amount,
+            partner_info,
+        )])?,
+        TokenCommand::Pause => ctx.execute(vec![token::set_pause_state(&authority, true)])?,
+        TokenCommand::Unpause => ctx.execute(vec![token::set_pause_state(&authority, false)])?,
+        TokenCommand::MigrateState => ctx.execute(vec![token::migrate_state(&authority)])?,
+        TokenCommand::SetLoyaltyTiers { tiers } => ctx.execute(vec![token::set_loyalty_tiers(&authority, tiers)])?,
+        TokenCommand::Tiers => {
+            let address = pda::loyalty_tiers().0;
+            let loyalty: accounts::LoyaltyTiers = accounts::fetch(&ctx.rpc, &address)?;
+            let tiers: Vec<Value> = loyalty
+                .tiers
+                .iter()
+                .map(|tier| {
+                    json!({
+                        "name": tier.name,
+                        "min_claims": tier.min_claims,
+                        "multiplier_bps": tier.multiplier_bps,
+                    })
+                })
+                .collect();
+            fields([("address", json!(address.to_string())), ("tiers", json!(tiers))])
+        }
+        TokenCommand::Profile { wallet } => {
+            let address = pda::user_profile(&wallet).0;
+            let profile: accounts::UserProfile = accounts::fetch(&ctx.rpc, &address)?;
+            let loyalty: accounts::LoyaltyTiers = accounts::fetch(&ctx.rpc, &pda::loyalty_tiers().0)?;
+            fields([
+                ("address", json!(address.to_string())),
+                ("owner", json!(profile.owner.to_string())),
+                ("claim_count", json!(profile.claim_count)),
+                ("lifetime_rewards", json!(profile.lifetime_rewards)),
+                ("tier", json!(profile.tier)),
+                ("tier_name", json!(loyalty.name(profile.tier))),
+                ("created_at", json!(profile.created_at)),
+                ("last_claim_at", json!(profile.last_claim_at)),
+                ("is_trusted", json!(profile.is_trusted)),
+            ])
+        }
+        TokenCommand::SetClaimLimits { claims, tokens } => {
+            ctx.execute(vec![token::set_claim_limits(&authority, claims, tokens)])?
+        }
+        TokenCommand::SetGuardians { guardians, threshold } => {
+            ctx.execute(vec![token::set_guardians(&authority, guardians, threshold)])?
+        }
+        TokenCommand::GuardianPause => ctx.execute(vec![token::guardian_pause(&authority)])?,
+        TokenCommand::SetCircuitBreaker {
+            bucket_secs,
+            max_claims,
+            max_batch_claims,
+        } => ctx.execute(vec![token::set_circuit_breaker(
+            &authority,
+            bucket_secs,
+            max_claims,
+            max_batch_claims,
+        )])?,
+        TokenCommand::Trust { wallet } => ctx.execute(vec![token::set_trusted(&authority, &wallet, true)])?,
+        TokenCommand::Untrust { wallet } => ctx.execute(vec![token::set_trusted(&authority, &wallet, false)])?,
+        TokenCommand::SetReferralBonus { amount } => {
+            ctx.execute(vec![token::set_referral_bonus(&authority, amount)])?
+        }
+        TokenCommand::GetStats => ctx.execute(vec![token::get_stats()])?,
+        TokenCommand::State => {
+            let address = pda::usv_state().0;
+            let state: accounts::USVState = accounts::fetch(&ctx.rpc, &address)?;
+            fields([
+                ("address", json!(address.to_string())),
+                ("authority", json!(state.authority.to_string())),
+                ("mint", json!(state.mint.to_string())),
+                ("total_supply", json!(state.total_supply)),
+                ("tokens_claimed", json!(state.tokens_claimed)),
+                ("total_qr_codes", json!(state.total_qr_codes)),
+                ("total_batches", json!(state.total_batches)),
+                ("referral_bonus", json!(state.referral_bonus)),
+                ("referral_rewards_paid", json!(state.referral_rewards_paid)),
+                ("max_claims_per_day", json!(state.max_claims_per_day)),
+                ("max_tokens_per_day", json!(state.max_tokens_per_day)),
+                ("breaker_bucket_secs", json!(state.breaker_bucket_secs)),
+                ("breaker_max_claims", json!(state.breaker_max_claims)),
+                ("breaker_max_batch_claims", json!(state.breaker_max_batch_claims)),
+                ("guardians", json!(state.guardians.members.iter().map(Pubkey::to_string).collect::<Vec<_>>())),
+                ("guardian_threshold", json!(state.guardians.threshold)),
+                ("pause_votes", json!(state.guardians.pause_votes.count_ones())),
+                ("is_paused", json!(state.is_paused)),
+            ])
+        }
+        TokenCommand::Batch { address, codes } => {
+            let batch = accounts::fetch_qr_batch(&ctx.rpc, &address)?;
+            let header = &batch.header;
+            let mut out = fields([
+                ("address", json!(address.to_string())),
+                ("authority", json!(header.authority.to_string())),
+                ("batch_id", json!(header.batch_id())),
+                ("partner_id", json!(header.partner_id())),
+                ("batch_info", json!(header.batch_info())),
+                ("count", json!(header.count)),
+                ("created_at", json!(header.created_at)),
+                ("is_sealed", json!(header.is_sealed())),
+            ]);
+            if codes {
+                let payloads: Vec<String> = batch.payloads(&address).iter().map(QrPayload::encode).collect();
+                out.insert("payloads".into(), json!(payloads));
+            }
+            out
+        }
+        TokenCommand::ExportSheets {
+            address,
+            out,
+            layout,
+            formats,
+        } => {
+            let layout = Layout::load(&layout)?;
+            let batch = accounts::fetch_qr_batch(&ctx.rpc, &address)?;
+            let header = &batch.header;
+            let labels = usv_sheets::labels(
+                address.to_bytes(),
+                &header.batch_id(),
+                header.partner_id().as_deref(),
+                &batch.codes,
+            );
+            let written = usv_sheets::export(&out, &layout, &labels, &formats)?;
+            let files: Vec<String> = written.iter().map(|path| path.display().to_string()).collect();
+            fields([
+                ("batch", json!(address.to_string())),
+                ("labels", json!(labels.len())),
+                ("pages", json!(labels.len().div_ceil(layout.labels_per_page()))),
+                ("files", json!(files)),
+            ])
+        }
+    };
+
+    ctx.report(out);
+    Ok(())
+}
+
+fn run_trading(ctx: &Context, command: TradingCommand) -> Result<()> {
+    let authority = ctx.payer();
+
+    let out = match command {
+        TradingCommand::Initialize {
+            usv_mint,
+            price_feed_id,
+            treasury,
+        } => {
+            let usv_mint = usv_mint.unwrap_or(pda::mint().0);
+            let price_feed_id = price_feed_id.unwrap_or(usv_client::usv_trading::oracle::SOL_USD_FEED_ID);
+            let treasury = treasury.unwrap_or(authority);
+            ctx.execute(vec![trading::initialize_trading(&authority, &usv_mint, &treasury, price_feed_id)])?
+        }
+        TradingCommand::SetTreasury { treasury } => {
+            ctx.execute(vec![trading::set_treasury(&authority, &treasury)])?
+        }
+        TradingCommand::DepositInventory { amount } => {
+            let state: accounts::TradingState = accounts::fetch(&ctx.rpc, &pda::trading_state().0)?;
+            ctx.execute(vec![spl_token::instruction::transfer(
+                &spl_token::ID,
+                &get_associated_token_address(&authority, &state.usv_mint),
+                &pda::inventory().0,
+                &authority,
+                &[],
+                amount,
+            )?])?
+        }
+        TradingCommand::WithdrawInventory { amount, destination } => {
+            let state: accounts::TradingState = accounts::fetch(&ctx.rpc, &pda::trading_state().0)?;
+            let destination = destination.unwrap_or(get_associated_token_address(&authority, &state.usv_mint));
+            ctx.execute(vec![trading::withdraw_inventory(&authority, &destination, amount)])?
+        }
+        TradingCommand::Buy {
+            sol_amount,
+            price_update,
+            min_tokens_out,
+            deadline_secs,
+        } => {
+            let state: accounts::TradingState = accounts::fetch(&ctx.rpc, &pda::trading_state().0)?;
+            let price_update = price_update.unwrap_or(pda::price_feed(0, &state.price_feed_id).0);
+            ctx.execute(vec![trading::buy_tokens(
+                &authority,
+                &get_associated_token_address(&authority, &state.usv_mint),
+                &state.treasury,
+                &state.usv_mint,
+                &price_update,
+                sol_amount,
+                min_tokens_out,
+                unix_time()? + deadline_secs as i64,
+                trading::current_round(&state),
+                state.vesting_threshold > 0,
+            )])?
+        }
+        TradingCommand::BuySpl {
+            mint,
+            amount,
+            min_tokens_out,
+            deadline_secs,
+        } => {
+            let state: accounts::TradingState = accounts::fetch(&ctx.rpc, &pda::trading_state().0)?;
+            let config: accounts::PaymentMintConfig = accounts::fetch(&ctx.rpc, &pda::payment_mint(&mint).0)?;
+            ctx.execute(vec![trading::buy_tokens_with_spl(
+                &authority,
+                &get_associated_token_address(&authority, &mint),
+                &get_associated_token_address(&authority, &state.usv_mint),
+                &mint,
+                &config.treasury,
+                &state.usv_mint,
+                amount,
+                min_tokens_out,
+                unix_time()? + deadline_secs as i64,
+                trading::current_round(&state),
+                state.vesting_threshold > 0,
+            )])?
+        }
+        TradingCommand::Sell {
+            token_amount,
+            price_update,
+            min_lamports_out,
+            deadline_secs,
+        } => {
+            let state: accounts::TradingState = accounts::fetch(&ctx.rpc, &pda::trading_state().0)?;
+            let price_update = price_update.unwrap_or(pda::price_feed(0, &state.price_feed_id).0);
+            ctx.execute(vec![trading::sell_tokens(
+                &authority,
+                &get_associated_token_address(&authority, &state.usv_mint),
+                &state.usv_mint,
+                &state.claim_vault,
+                &price_update,
+                token_amount,
+                min_lamports_out,
+                unix_time()? + deadline_secs as i64,
+            )])?
+        }
+        TradingCommand::SellSpl {
+            mint,
+            token_amount,
+            min_payout,
+            deadline_secs,
+        } => {
+            let state: accounts::TradingState = accounts::fetch(&ctx.rpc, &pda::trading_state().0)?;
+            ctx.execute(vec![trading::sell_tokens_for_spl(
+                &authority,
+                &get_associated_token_address(&authority, &state.usv_mint),
+                &get_associated_token_address(&authority, &mint),
+                &mint,
+                &state.usv_mint,
+                &state.claim_vault,
+                token_amount,
+                min_payout,
+                unix_time()? + deadline_secs as i64,
+            )])?
+        }
+        TradingCommand::SetRedemption {
+            bid_cents,
+            max_per_day,
+            min_reserve_ratio_bps,
+            claim_vault,
+        } => {
+            let claim_vault = match claim_vault {
+                Some(claim_vault) => claim_vault,
+                None => {
+                    let usv_state: accounts::USVState = accounts::fetch(&ctx.rpc, &pda::usv_state().0)?;
+                    token::authority_token_account(&usv_state.authority)
+                }
+            };
+            ctx.execute(vec![trading::set_redemption_config(
+                &authority,
+                &claim_vault,
+                bid_cents,
+                max_per_day,
+                min_reserve_ratio_bps,
+            )])?
+        }
+        TradingCommand::FundReserve { lamports } => {
+            ctx.execute(vec![system_instruction::transfer(&authority, &pda::sol_reserve().0, lamports)])?
+        }
+        TradingCommand::AddRound {
+            start_time,
+            end_time,
+            price_cents,
+            cap,
+            per_wallet_cap,
+            allowlist,
+            vests,
+        } => {
+            let state: accounts::TradingState = accounts::fetch(&ctx.rpc, &pda::trading_state().0)?;
+            let allowlist_root = allowlist.map(|path| load_allowlist(&path)).transpose()?;
+            let mut out = ctx.execute(vec![trading::add_sale_round(
+                &authority,
+                state.round_count,
+                start_time.map_or_else(unix_time, Ok)?,
+                end_time,
+                price_cents,
+                cap,
+                per_wallet_cap,
+                allowlist_root.as_deref().map(allowlist::root),
+                vests,
+            )])?;
+            out.insert("index".into(), json!(state.round_count));
+            out
+        }
+        TradingCommand::UpdateRound {
+            index,
+            start_time,
+            end_time,
+            price_cents,
+            cap,
+            per_wallet_cap,
+            allowlist,
+            no_allowlist,
+            vests,
+        } => {
+            let round: accounts::SaleRound = accounts::fetch(&ctx.rpc, &pda::sale_round(index).0)?;
+            let allowlist_root = match allowlist {
+                Some(path) => Some(allowlist::root(&load_allowlist(&path)?)),
+                None if no_allowlist => None,
+                None => round.allowlist_root,
+            };
+            ctx.execute(vec![trading::update_sale_round(
+                &authority,
+                index,
+                start_time.unwrap_or(round.start_time),
+                end_time.unwrap_or(round.end_time),
+                price_cents.unwrap_or(round.price_cents),
+                cap.unwrap_or(round.cap),
+                per_wallet_cap.unwrap_or(round.per_wallet_cap),
+                allowlist_root,
+                vests.unwrap_or(round.vests),
+            )])?
+        }
+        TradingCommand::BuyRound {
+            sol_amount,
+            price_update,
+            min_tokens_out,
+            deadline_secs,
+            allowlist,
+        } => {
+            let state: accounts::TradingState = accounts::fetch(&ctx.rpc, &pda::trading_state().0)?;
+            let round: accounts::SaleRound = accounts::fetch(&ctx.rpc, &pda::sale_round(state.current_round).0)?;
+            let price_update = price_update.unwrap_or(pda::price_feed(0, &state.price_feed_id).0);
+            let proof = match allowlist {
+                Some(path) => {
+                    let leaves = load_allowlist(&path)?;
+                    let index = leaves
+                        .iter()
+                        .position(|leaf| *leaf == allowlist::leaf(&authority))
+                        .ok_or_else(|| anyhow!("{} is not on the allowlist", authority))?;
+                    allowlist::proof(&leaves, index)
+                }
+                None => Vec::new(),
+            };
+            ctx.execute(vec![trading::buy_in_round(
+                &authority,
+                &get_associated_token_address(&authority, &state.usv_mint),
+                &state.treasury,
+                &state.usv_mint,
+                &price_update,
+                state.current_round,
+                sol_amount,
+                min_tokens_out,
+                unix_time()? + deadline_secs as i64,
+                proof,
+                round.vests || state.vesting_threshold > 0,
+            )])?
+        }
+        TradingCommand::AdvanceRound => {
+            let state: accounts::TradingState = accounts::fetch(&ctx.rpc, &pda::trading_state().0)?;
+            ctx.execute(vec![trading::advance_sale_round(state.current_round)])?
+        }
+        TradingCommand::SetVesting {
+            threshold,
+            cliff_secs,
+            duration_secs,
+        } => ctx.execute(vec![trading::set_vesting_config(
+            &authority,
+            threshold,
+            cliff_secs,
+            duration_secs,
+        )])?,
+        TradingCommand::ReleaseVested => {
+            let state: accounts::TradingState = accounts::fetch(&ctx.rpc, &pda::trading_state().0)?;
+            ctx.execute(vec![trading::release_vested(
+                &authority,
+                &get_associated_token_address(&authority, &state.usv_mint),
+                &state.usv_mint,
+            )])?
+        }
+        TradingCommand::Vesting { wallet } => {
+            let wallet = wallet.unwrap_or(authority);
+            let address = pda::vesting_escrow(&wallet).0;
+            let escrow: accounts::VestingEscrow = accounts::fetch(&ctx.rpc, &address)?;
+            let schedule = escrow.schedule;
+            let now = unix_time()?;
+            let tranches: Vec<_> = schedule
+                .tranches
+                .iter()
+                .map(|tranche| {
+                    json!({
+                        "amount": tranche.amount,
+                        "released": tranche.released,
+                        "cliff_time": tranche.cliff_time(),
+                        "end_time": tranche.end_time(),
+                    })
+                })
+                .collect();
+            fields([
+                ("address", json!(address.to_string())),
+                ("buyer", json!(escrow.buyer.to_string())),
+                ("locked", json!(schedule.locked(now))),
+                ("releasable", json!(schedule.releasable(now))),
+                ("end_time", json!(schedule.end_time())),
+                ("tranches", json!(tranches)),
+            ])
+        }
+        TradingCommand::Round { index } => {
+            let state: accounts::TradingState = accounts::fetch(&ctx.rpc, &pda::trading_state().0)?;
+            let index = index.unwrap_or(state.current_round);
+            let address = pda::sale_round(index).0;
+            let round: accounts::SaleRound = accounts::fetch(&ctx.rpc, &address)?;
+            fields([
+                ("address", json!(address.to_string())),
+                ("index", json!(round.index)),
+                ("is_current", json!(round.index == state.current_round)),
+                ("start_time", json!(round.start_time)),
+                ("end_time", json!(round.end_time)),
+                ("price_cents", json!(round.price_cents)),
+                ("cap", json!(round.cap)),
+                ("sold", json!(round.sold)),
+                ("per_wallet_cap", json!(round.per_wallet_cap)),
+                ("allowlist_root", json!(round.allowlist_root.map(hex::encode))),
+                ("vests", json!(round.vests)),
+            ])
+        }
+        TradingCommand::SetCurve {
+            start_price_micros,
+            slope_micros,
+            growth_bps,
+            step,
+            supply_cap,
+        } => {
+            let kind = match (slope_micros, growth_bps, step) {
+                (Some(slope_micros), _, _) => CurveKind::Linear { slope_micros },
+                (None, Some(growth_bps), Some(step)) => CurveKind::Exponential { growth_bps, step },
+                _ => return Err(anyhow!("pass --slope-micros, or --growth-bps with --step")),
+            };
+            let curve = BondingCurve {
+                kind,
+                start_price_micros,
+                supply_cap,
+            };
+            ctx.execute(vec![trading::set_bonding_curve(&authority, Some(curve))])?
+        }
+        TradingCommand::ClearCurve => ctx.execute(vec![trading::set_bonding_curve(&authority, None)])?,
+        TradingCommand::InitPool {
+            fee_bps,
+            max_price_impact_bps,
+        } => {
+            let state: accounts::TradingState = accounts::fetch(&ctx.rpc, &pda::trading_state().0)?;
+            ctx.execute(vec![trading::initialize_pool(&authority, &state.usv_mint, fee_bps, max_price_impact_bps)])?
+        }
+        TradingCommand::AddLiquidity {
+            sol_amount,
+            max_usv_amount,
+            min_lp_out,
+        } => {
+            let pool: accounts::Pool = accounts::fetch(&ctx.rpc, &pda::pool().0)?;
+            ctx.execute(vec![trading::add_liquidity(
+                &authority,
+                &get_associated_token_address(&authority, &pool.usv_mint),
+                sol_amount,
+                max_usv_amount,
+                min_lp_out,
+            )])?
+        }
+        TradingCommand::RemoveLiquidity {
+            lp_amount,
+            min_sol_out,
+            min_usv_out,
+        } => {
+            let pool: accounts::Pool = accounts::fetch(&ctx.rpc, &pda::pool().0)?;
+            ctx.execute(vec![trading::remove_liquidity(
+                &authority,
+                &get_associated_token_address(&authority, &pool.usv_mint),
+                lp_amount,
+                min_sol_out,
+                min_usv_out,
+            )])?
+        }
+        TradingCommand::Swap {
+            amount_in,
+            sell,
+            min_amount_out,
+            deadline_secs,
+        } => {
+            let pool: accounts::Pool = accounts::fetch(&ctx.rpc, &pda::pool().0)?;
+            ctx.execute(vec![trading::swap(
+                &authority,
+                &get_associated_token_address(&authority, &pool.usv_mint),
+                amount_in,
+                min_amount_out,
+                !sell,
+                unix_time()? + deadline_secs as i64,
+            )])?
+        }
+        TradingCommand::SetPoolConfig {
+            fee_bps,
+            max_price_impact_bps,
+        } => ctx.execute(vec![trading::set_pool_config(&authority, fee_bps, max_price_impact_bps)])?,
+        TradingCommand::Pool => {
+            let address = pda::pool().0;
+            let pool: accounts::Pool = accounts::fetch(&ctx.rpc, &address)?;
+            fields([
+                ("address", json!(address.to_string())),
+                ("usv_mint", json!(pool.usv_mint.to_string())),
+                ("lp_mint", json!(pool.lp_mint.to_string())),
+                ("reserve_sol", json!(pool.reserve_sol)),
+                ("reserve_usv", json!(pool.reserve_usv)),
+                ("lp_supply", json!(pool.lp_supply)),
+                ("fee_bps", json!(pool.fee_bps)),
+                ("max_price_impact_bps", json!(pool.max_price_impact_bps)),
+            ])
+        }
+        TradingCommand::AddPaymentMint {
+            mint,
+            treasury,
+            usd_price_micros,
+        } => {
+            let treasury = treasury.unwrap_or(get_associated_token_address(&authority, &mint));
+            ctx.execute(vec![trading::add_payment_mint(&authority, &mint, &treasury, usd_price_micros)])?
+        }
+        TradingCommand::UpdatePaymentMint {
+            mint,
+            treasury,
+            usd_price_micros,
+            disabled,
+        } => {
+            let config: accounts::PaymentMintConfig = accounts::fetch(&ctx.rpc, &pda::payment_mint(&mint).0)?;
+            ctx.execute(vec![trading::update_payment_mint(
+                &authority,
+                &mint,
+                &treasury.unwrap_or(config.treasury),
+                usd_price_micros.unwrap_or(config.usd_price_micros),
+                !disabled,
+            )])?
+        }
+        TradingCommand::PaymentMint { mint } => {
+            let address = pda::payment_mint(&mint).0;
+            let config: accounts::PaymentMintConfig = accounts::fetch(&ctx.rpc, &address)?;
+            fields([
+                ("address", json!(address.to_string())),
+                ("mint", json!(config.mint.to_string())),
+                ("decimals", json!(config.decimals)),
+                ("usd_price_micros", json!(config.usd_price_micros)),
+                ("treasury", json!(config.treasury.to_string())),
+                ("reserve", json!(pda::reserve(&mint).0.to_string())),
+                ("is_enabled", json!(config.is_enabled)),
+                ("total_received", json!(config.total_received)),
+            ])
+        }
+        TradingCommand::UpdateOracle {
+            price_feed_id,
+            max_age_secs,
+            max_confidence_bps,
+        } => ctx.execute(vec![trading::update_oracle_config(
+            &authority,
+            price_feed_id,
+            max_age_secs,
+            max_confidence_bps,
+        )])?,
+        TradingCommand::UpdatePrice { cents } => {
+            ctx.execute(vec![trading::update_fixed_price(&authority, cents)])?
+        }
+        TradingCommand::Resume => ctx.execute(vec![trading::toggle_trading(&authority, true)])?,
+        TradingCommand::Pause => ctx.execute(vec![trading::toggle_trading(&authority, false)])?,
+        TradingCommand::SetGuardians { guardians, threshold } => {
+            ctx.execute(vec![trading::set_guardians(&authority, guardians, threshold)])?
+        }
+        TradingCommand::GuardianPause => ctx.execute(vec![trading::guardian_pause(&authority)])?,
+        TradingCommand::GetStats => ctx.execute(vec![trading::get_trading_stats()])?,
+        TradingCommand::State => {
+            let address = pda::trading_state().0;
+            let state: accounts::TradingState = accounts::fetch(&ctx.rpc, &address)?;
+            fields([
+                ("address", json!(address.to_string())),
+                ("authority", json!(state.authority.to_string())),
+                ("usv_mint", json!(state.usv_mint.to_string())),
+                ("treasury", json!(state.treasury.to_string())),
+                ("inventory", json!(pda::inventory().0.to_string())),
+                ("fixed_price_cents", json!(state.fixed_price_cents)),
+                ("curve", json!(state.curve.map(|curve| format!("{curve:?}")))),
+                ("curve_sold", json!(state.curve_sold)),
+                (
+                    "curve_price_micros",
+                    json!(state.curve.map(|curve| curve.price_micros(state.curve_sold)).transpose()?),
+                ),
+                ("is_active", json!(state.is_active)),
+                ("total_sales_volume", json!(state.total_sales_volume)),
+                ("total_purchases", json!(state.total_purchases)),
+                ("price_feed_id", json!(hex::encode(state.price_feed_id))),
+                ("max_price_age_secs", json!(state.max_price_age_secs)),
+                ("max_confidence_bps", json!(state.max_confidence_bps)),
+                ("bid_price_cents", json!(state.bid_price_cents)),
+                ("max_redeemed_per_day", json!(state.max_redeemed_per_day)),
+                ("min_reserve_ratio_bps", json!(state.min_reserve_ratio_bps)),
+                ("redeemed_in_window", json!(state.redeemed_in_window)),
+                ("total_redeemed", json!(state.total_redeemed)),
+                ("claim_vault", json!(state.claim_vault.to_string())),
+                ("current_round", json!(state.current_round)),
+                ("round_count", json!(state.round_count)),
+                ("vesting_threshold", json!(state.vesting_threshold)),
+                ("vesting_cliff_secs", json!(state.vesting_cliff_secs)),
+                ("vesting_duration_secs", json!(state.vesting_duration_secs)),
+                ("vesting_vault", json!(pda::vesting_vault().0.to_string())),
+                ("guardians", json!(state.guardians.members.iter().map(Pubkey::to_string).collect::<Vec<_>>())),
+                ("guardian_threshold", json!(state.guardians.threshold)),
+                ("sol_reserve", json!(pda::sol_reserve().0.to_string())),
+            ])
+        }
+    };
+
+    ctx.report(out);
+    Ok(())
+}
+
+fn run_nft(ctx: &Context, command: NftCommand) -> Result<()> {
+    let authority = ctx.payer();
+
+    let out = match command {
+        NftCommand::Initialize => ctx.execute(vec![nft_auth::initialize(&authority)])?,
+        NftCommand::RegisterQr { code } => {
+            ctx.execute(vec![nft_auth::register_qr_code(&authority, &code)])?
+        }
+        NftCommand::MintNft { code } => {
+            let mut out = ctx.execute(vec![nft_auth::mint_nft_piece(&authority, &code)])?;
+            out.insert("nft_mint".into(), json!(pda::nft_mint(&code).0.to_string()));
+            out
+        }
+        NftCommand::Pause => ctx.execute(vec![nft_auth::set_pause_state(&authority, true)])?,
+        NftCommand::Unpause => ctx.execute(vec![nft_auth::set_pause_state(&authority, false)])?,
+        NftCommand::SetGuardians { guardians, threshold } => {
+            ctx.execute(vec![nft_auth::set_guardians(&authority, guardians, threshold)])?
+        }
+        NftCommand::GuardianPause => ctx.execute(vec![nft_auth::guardian_pause(&authority)])?,
+        NftCommand::State => {
+            let address = pda::program_state().0;
+            let state: accounts::ProgramState = accounts::fetch(&ctx.rpc, &address)?;
+            // Collections initialized before the mint guard existed have none until paused or given guardians
+            let guard: Option<accounts::MintGuard> = accounts::fetch(&ctx.rpc, &pda::mint_guard().0).ok();
+            fields([
+                ("address", json!(address.to_string())),
+                ("authority", json!(state.authority.to_string())),
+                ("total_pieces", json!(state.total_pieces)),
+                ("minted_pieces", json!(state.minted_pieces)),
+                ("is_paused", json!(guard.as_ref().map(|guard| guard.is_paused))),
+                (
+                    "guardians",
+                    json!(guard.as_ref().map(|guard| guard.guardians.members.iter().map(Pubkey::to_string).collect::<Vec<_>>())),
+                ),
+                ("guardian_threshold", json!(guard.as_ref().map(|guard| guard.guardians.threshold))),
+            ])
+        }
+        NftCommand::Qr { code } => {
+            let address = pda::qr_data(&code).0;
+            let qr: accounts::QRData = accounts::fetch(&ctx.rpc, &address)?;
+            fields([
+                ("address", json!(address.to_string())),
+                ("piece_number", json!(qr.piece_number)),
+                ("is_claimed", json!(qr.is_claimed)),
+                ("claimed_by", json!(qr.is_claimed.then(|| qr.claimed_by.to_string()))),
+            ])
+        }
+    };
+
+    ctx.report(out);
+    Ok(())
+}
+
+fn unix_time() -> Result<i64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64)
+}
+
+// Allowlist leaves from a file with one wallet address per line
+fn load_allowlist(path: &Path) -> Result<Vec<[u8; 32]>> {
+    std::fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| Ok(allowlist::leaf(&Pubkey::from_str(line)?)))
+        .collect()
+}
+
+// 32-byte Pyth feed id, with or without a 0x prefix
+fn parse_feed_id(value: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(value.trim_start_matches("0x"))?;
+    bytes
+        .try_into()
+        .map_err(|_| anyhow!("feed id must be 32 bytes"))
+}
+
+// NAME:MIN_CLAIMS:MULTIPLIER_BPS
+fn parse_tier(value: &str) -> Result<accounts::LoyaltyTier> {
+    let parts: Vec<&str> = value.split(':').collect();
+    let [name, min_claims, multiplier_bps] = parts[..] else {
+        return Err(anyhow!("expected NAME:MIN_CLAIMS:MULTIPLIER_BPS, got {}", value));
+    };
+    Ok(accounts::LoyaltyTier {
+        name: name.to_string(),
+        min_claims: min_claims.parse()?,
+        multiplier_bps: multiplier_bps.parse()?,
+    })
+}
+
+fn fields<const N: usize>(entries: [(&str, Value); N]) -> Map<String, Value> {
+    entries
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect()
+}
@@ -0,0 +1,71 @@
+// crates/usv-client/src/accounts.rs - Account fetching and decoding
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use solana_client::rpc_client::RpcClient;
+use usv_qr::QrPayload;
+use usv_token::{QRBatch, QR_CODE_LEN};
+
+use crate::{ClientError, Result};
+
+pub use nft_auth_program::{MintGuard, ProgramState, QRData};
+pub use usv_token::{BatchBreaker, LoyaltyTier, LoyaltyTiers, QRClaim, Referral, USVState, UserProfile};
+pub use usv_trading::{PaymentMintConfig, Pool, RoundBuyer, SaleRound, TradingState, VestingEscrow};
+
+// A zero-copy QR batch: the fixed header plus the codes stored after it
+pub struct QRBatchAccount {
+    pub header: QRBatch,
+    pub codes: Vec<[u8; QR_CODE_LEN]>,
+}
+
+impl QRBatchAccount {
+    // The printable payload for every code, given the batch's own address
+    pub fn payloads(&self, address: &Pubkey) -> Vec<QrPayload> {
+        self.codes
+            .iter()
+            .zip(0u32..)
+            .map(|(code, index)| QrPayload::new(address.to_bytes(), index, *code))
+            .collect()
+    }
+}
+
+// Decode any Borsh account, checking its discriminator
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    let mut data = data;
+    Ok(T::try_deserialize(&mut data)?)
+}
+
+pub fn decode_qr_batch(data: &[u8]) -> Result<QRBatchAccount> {
+    let header_end = QRBatch::space(0);
+    if data.len() < header_end || data[..8] != QRBatch::discriminator() {
+        return Err(ClientError::InvalidAccountData("QRBatch"));
+    }
+    let header: QRBatch = bytemuck::pod_read_unaligned(&data[8..header_end]);
+
+    let codes_end = QRBatch::space(header.count);
+    if data.len() < codes_end {
+        return Err(ClientError::InvalidAccountData("QRBatch"));
+    }
+    let codes = data[header_end..codes_end]
+        .chunks_exact(QR_CODE_LEN)
+        .map(|code| code.try_into().unwrap())
+        .collect();
+
+    Ok(QRBatchAccount { header, codes })
+}
+
+pub fn fetch<T: AccountDeserialize>(rpc: &RpcClient, address: &Pubkey) -> Result<T> {
+    decode(&fetch_data(rpc, address)?)
+}
+
+pub fn fetch_qr_batch(rpc: &RpcClient, address: &Pubkey) -> Result<QRBatchAccount> {
+    decode_qr_batch(&fetch_data(rpc, address)?)
+}
+
+fn fetch_data(rpc: &RpcClient, address: &Pubkey) -> Result<Vec<u8>> {
+    let account = rpc
+        .get_account_with_commitment(address, rpc.commitment())?
+        .value
+        .ok_or(ClientError::AccountNotFound(*address))?;
+    Ok(account.data)
+}
@@ -0,0 +1,11 @@
+// crates/usv-client/src/cluster.rs - Cluster monikers accepted by the Solana CLI
+
+pub fn cluster_url(url: &str) -> &str {
+    match url {
+        "localnet" | "l" => "http://localhost:8899",
+        "devnet" | "d" => "https://api.devnet.solana.com",
+        "testnet" | "t" => "https://api.testnet.solana.com",
+        "mainnet-beta" | "m" => "https://api.mainnet-beta.solana.com",
+        url => url,
+    }
+}
@@ -0,0 +1,27 @@
+// crates/usv-client/src/error.rs
+
+use anchor_lang::prelude::Pubkey;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("account {0} not found")]
+    AccountNotFound(Pubkey),
+    #[error("account data is not a valid {0}")]
+    InvalidAccountData(&'static str),
+    #[error("invalid event data")]
+    InvalidEventData,
+    #[error("transaction must be fetched with JSON encoding")]
+    UnsupportedEncoding,
+    #[error(transparent)]
+    Anchor(#[from] anchor_lang::error::Error),
+    #[error(transparent)]
+    Rpc(#[from] Box<solana_client::client_error::ClientError>),
+}
+
+impl From<solana_client::client_error::ClientError> for ClientError {
+    fn from(err: solana_client::client_error::ClientError) -> Self {
+        ClientError::Rpc(Box::new(err))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
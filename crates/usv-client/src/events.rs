@@ -0,0 +1,155 @@
+// crates/usv-client/src/events.rs - Decoding Anchor events emitted through self-CPI
+//
+// Every USV program emits events with `emit_cpi!`: the program invokes itself
+// with the event as instruction data, signed by its event authority PDA. Only
+// the program can produce that signature, so an inner instruction to a USV
+// program whose first account is that program's event authority is a genuine
+// event, unlike `Program data:` log lines which any program can print and RPC
+// nodes truncate.
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiInstruction, UiMessage,
+};
+
+use crate::{pda, ClientError, Result};
+
+pub use nft_auth_program::{MintingPauseVoted, NftPieceMinted, QRCodeRegistered};
+pub use usv_token::{
+    BatchSealed, CircuitBreakerTripped, GuardianPauseVoted, PartnerTransfer, ProgramStats, QRCodesGenerated,
+    ReferralRewarded, TierChanged, TokensClaimed,
+};
+pub use usv_trading::{
+    LiquidityAdded, LiquidityRemoved, PoolSwap, PriceUpdated, SaleRoundAdvanced, SplTokenPurchase, TokenPurchase,
+    TokenRedemption, TokensVested, TradingPauseVoted, TradingStats, VestedTokensReleased,
+};
+
+pub enum UsvEvent {
+    QRCodesGenerated(QRCodesGenerated),
+    BatchSealed(BatchSealed),
+    TokensClaimed(TokensClaimed),
+    TierChanged(TierChanged),
+    CircuitBreakerTripped(CircuitBreakerTripped),
+    GuardianPauseVoted(GuardianPauseVoted),
+    ReferralRewarded(ReferralRewarded),
+    PartnerTransfer(PartnerTransfer),
+    ProgramStats(ProgramStats),
+    TokenPurchase(TokenPurchase),
+    SplTokenPurchase(SplTokenPurchase),
+    TokenRedemption(TokenRedemption),
+    LiquidityAdded(LiquidityAdded),
+    LiquidityRemoved(LiquidityRemoved),
+    PoolSwap(PoolSwap),
+    SaleRoundAdvanced(SaleRoundAdvanced),
+    TokensVested(TokensVested),
+    VestedTokensReleased(VestedTokensReleased),
+    PriceUpdated(PriceUpdated),
+    TradingPauseVoted(TradingPauseVoted),
+    TradingStats(TradingStats),
+    QRCodeRegistered(QRCodeRegistered),
+    NftPieceMinted(NftPieceMinted),
+    MintingPauseVoted(MintingPauseVoted),
+}
+
+const PROGRAMS: [Pubkey; 3] = [usv_token::ID, usv_trading::ID, nft_auth_program::ID];
+
+fn parse<T: AnchorDeserialize>(mut data: &[u8]) -> Result<T> {
+    T::deserialize(&mut data).map_err(|_| ClientError::InvalidEventData)
+}
+
+// Decode discriminator-prefixed event data; Ok(None) if it is not a USV event
+pub fn decode_event(data: &[u8]) -> Result<Option<UsvEvent>> {
+    if data.len() < 8 {
+        return Err(ClientError::InvalidEventData);
+    }
+    let (discriminator, body) = data.split_at(8);
+
+    let event = match discriminator {
+        d if d == QRCodesGenerated::discriminator() => UsvEvent::QRCodesGenerated(parse(body)?),
+        d if d == BatchSealed::discriminator() => UsvEvent::BatchSealed(parse(body)?),
+        d if d == TokensClaimed::discriminator() => UsvEvent::TokensClaimed(parse(body)?),
+        d if d == TierChanged::discriminator() => UsvEvent::TierChanged(parse(body)?),
+        d if d == CircuitBreakerTripped::discriminator() => UsvEvent::CircuitBreakerTripped(parse(body)?),
+        d if d == GuardianPauseVoted::discriminator() => UsvEvent::GuardianPauseVoted(parse(body)?),
+        d if d == ReferralRewarded::discriminator() => UsvEvent::ReferralRewarded(parse(body)?),
+        d if d == PartnerTransfer::discriminator() => UsvEvent::PartnerTransfer(parse(body)?),
+        d if d == ProgramStats::discriminator() => UsvEvent::ProgramStats(parse(body)?),
+        d if d == TokenPurchase::discriminator() => UsvEvent::TokenPurchase(parse(body)?),
+        d if d == SplTokenPurchase::discriminator() => UsvEvent::SplTokenPurchase(parse(body)?),
+        d if d == TokenRedemption::discriminator() => UsvEvent::TokenRedemption(parse(body)?),
+        d if d == LiquidityAdded::discriminator() => UsvEvent::LiquidityAdded(parse(body)?),
+        d if d == LiquidityRemoved::discriminator() => UsvEvent::LiquidityRemoved(parse(body)?),
+        d if d == PoolSwap::discriminator() => UsvEvent::PoolSwap(parse(body)?),
+        d if d == SaleRoundAdvanced::discriminator() => UsvEvent::SaleRoundAdvanced(parse(body)?),
+        d if d == TokensVested::discriminator() => UsvEvent::TokensVested(parse(body)?),
+        d if d == VestedTokensReleased::discriminator() => UsvEvent::VestedTokensReleased(parse(body)?),
+        d if d == PriceUpdated::discriminator() => UsvEvent::PriceUpdated(parse(body)?),
+        d if d == TradingPauseVoted::discriminator() => UsvEvent::TradingPauseVoted(parse(body)?),
+        d if d == TradingStats::discriminator() => UsvEvent::TradingStats(parse(body)?),
+        d if d == QRCodeRegistered::discriminator() => UsvEvent::QRCodeRegistered(parse(body)?),
+        d if d == NftPieceMinted::discriminator() => UsvEvent::NftPieceMinted(parse(body)?),
+        d if d == MintingPauseVoted::discriminator() => UsvEvent::MintingPauseVoted(parse(body)?),
+        _ => return Ok(None),
+    };
+    Ok(Some(event))
+}
+
+// Decode one inner instruction; Ok(None) unless it is an event self-CPI of a USV program
+pub fn decode_cpi_event(program_id: &Pubkey, accounts: &[Pubkey], data: &[u8]) -> Result<Option<UsvEvent>> {
+    if !PROGRAMS.contains(program_id) || accounts.first() != Some(&pda::event_authority(program_id).0) {
+        return Ok(None);
+    }
+    match data.strip_prefix(&EVENT_IX_TAG_LE) {
+        Some(event) => decode_event(event),
+        None => Ok(None),
+    }
+}
+
+// Collect every USV event in a transaction fetched with JSON encoding. Failed
+// transactions emit nothing
+pub fn parse_transaction(tx: &EncodedConfirmedTransactionWithStatusMeta) -> Result<Vec<UsvEvent>> {
+    let Some(meta) = &tx.transaction.meta else {
+        return Ok(Vec::new());
+    };
+    if meta.err.is_some() {
+        return Ok(Vec::new());
+    }
+    let EncodedTransaction::Json(ui) = &tx.transaction.transaction else {
+        return Err(ClientError::UnsupportedEncoding);
+    };
+    let UiMessage::Raw(message) = &ui.message else {
+        return Err(ClientError::UnsupportedEncoding);
+    };
+
+    // Static keys, then addresses loaded from lookup tables (writable before readonly)
+    let mut keys: Vec<String> = message.account_keys.clone();
+    if let Some(loaded) = Option::<&_>::from(meta.loaded_addresses.as_ref()) {
+        keys.extend(loaded.writable.iter().cloned());
+        keys.extend(loaded.readonly.iter().cloned());
+    }
+    let keys = keys
+        .iter()
+        .map(|key| key.parse())
+        .collect::<std::result::Result<Vec<Pubkey>, _>>()
+        .map_err(|_| ClientError::InvalidEventData)?;
+    let key = |index: u8| keys.get(index as usize).copied().ok_or(ClientError::InvalidEventData);
+
+    let mut events = Vec::new();
+    let inner = Option::<&Vec<_>>::from(meta.inner_instructions.as_ref()).into_iter().flatten();
+    for instruction in inner.flat_map(|inner| &inner.instructions) {
+        let UiInstruction::Compiled(instruction) = instruction else {
+            continue;
+        };
+        let program_id = key(instruction.program_id_index)?;
+        let accounts = instruction.accounts.iter().map(|&index| key(index)).collect::<Result<Vec<_>>>()?;
+        let data = bs58::decode(&instruction.data)
+            .into_vec()
+            .map_err(|_| ClientError::InvalidEventData)?;
+        if let Some(event) = decode_cpi_event(&program_id, &accounts, &data)? {
+            events.push(event);
+        }
+    }
+    Ok(events)
+}
@@ -0,0 +1,19 @@
+// crates/usv-client/src/lib.rs - Client SDK for usv_token, usv_trading and nft_auth_program
+
+pub mod accounts;
+pub mod cluster;
+pub mod error;
+pub mod events;
+pub mod nft_auth;
+pub mod pda;
+pub mod token;
+pub mod trading;
+
+pub use cluster::cluster_url;
+pub use error::{ClientError, Result};
+
+// Re-export the program crates so callers get matching account and event types
+pub use nft_auth_program;
+pub use usv_qr;
+pub use usv_token;
+pub use usv_trading;
@@ -0,0 +1,110 @@
+// crates/usv-client/src/nft_auth.rs - Instruction builders for nft_auth_program
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{system_program, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use nft_auth_program::{accounts, instruction};
+
+use crate::pda;
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: nft_auth_program::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+pub fn initialize(authority: &Pubkey) -> Instruction {
+    build(
+        accounts::Initialize {
+            program_state: pda::program_state().0,
+            master_collection: pda::master_collection().0,
+            mint_guard: pda::mint_guard().0,
+            authority: *authority,
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::Initialize {},
+    )
+}
+
+pub fn register_qr_code(authority: &Pubkey, qr_code: &str) -> Instruction {
+    build(
+        accounts::RegisterQR {
+            qr_data: pda::qr_data(qr_code).0,
+            authority: *authority,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(&nft_auth_program::ID).0,
+            program: nft_auth_program::ID,
+        },
+        instruction::RegisterQrCode {
+            qr_code: qr_code.to_string(),
+        },
+    )
+}
+
+pub fn mint_nft_piece(customer: &Pubkey, qr_code: &str) -> Instruction {
+    let nft_mint = pda::nft_mint(qr_code).0;
+    build(
+        accounts::MintNFT {
+            qr_data: pda::qr_data(qr_code).0,
+            program_state: pda::program_state().0,
+            mint_guard: pda::mint_guard().0,
+            master_collection: pda::master_collection().0,
+            nft_mint,
+            customer_token_account: get_associated_token_address(customer, &nft_mint),
+            customer: *customer,
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: associated_token::ID,
+            rent: sysvar::rent::ID,
+            event_authority: pda::event_authority(&nft_auth_program::ID).0,
+            program: nft_auth_program::ID,
+        },
+        instruction::MintNftPiece {
+            qr_code: qr_code.to_string(),
+        },
+    )
+}
+
+// Also creates the mint guard for collections initialized before it existed
+pub fn set_pause_state(authority: &Pubkey, is_paused: bool) -> Instruction {
+    build(
+        accounts::SetMintGuard {
+            program_state: pda::program_state().0,
+            mint_guard: pda::mint_guard().0,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::SetPauseState { is_paused },
+    )
+}
+
+// `threshold` of `guardians` must call `guardian_pause` to pause minting
+pub fn set_guardians(authority: &Pubkey, guardians: Vec<Pubkey>, threshold: u8) -> Instruction {
+    build(
+        accounts::SetMintGuard {
+            program_state: pda::program_state().0,
+            mint_guard: pda::mint_guard().0,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::SetGuardians { guardians, threshold },
+    )
+}
+
+pub fn guardian_pause(guardian: &Pubkey) -> Instruction {
+    build(
+        accounts::GuardianPause {
+            mint_guard: pda::mint_guard().0,
+            guardian: *guardian,
+            event_authority: pda::event_authority(&nft_auth_program::ID).0,
+            program: nft_auth_program::ID,
+        },
+        instruction::GuardianPause {},
+    )
+}
@@ -0,0 +1,145 @@
+// crates/usv-client/src/pda.rs - Program-derived addresses for every USV program
+
+use anchor_lang::prelude::Pubkey;
+use usv_token::QR_CODE_LEN;
+
+// Signs the self-CPI that carries each event (Anchor's event-cpi pattern); one per program
+pub fn event_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"__event_authority"], program_id)
+}
+
+// usv_token
+
+pub fn usv_state() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"usv_state"], &usv_token::ID)
+}
+
+pub fn mint() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"mint"], &usv_token::ID)
+}
+
+pub fn mint_authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"mint_authority"], &usv_token::ID)
+}
+
+// `batch_number` is `USVState.total_batches` at the time the batch was created
+pub fn qr_batch(authority: &Pubkey, batch_number: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"qr_batch", authority.as_ref(), &batch_number.to_le_bytes()],
+        &usv_token::ID,
+    )
+}
+
+// Circuit breaker bucket for claims from `qr_batch`, created on its first claim
+pub fn batch_breaker(qr_batch: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"batch_breaker", qr_batch.as_ref()], &usv_token::ID)
+}
+
+pub fn qr_claim(qr_code: &[u8; QR_CODE_LEN]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"qr_claim", qr_code.as_ref()], &usv_token::ID)
+}
+
+pub fn loyalty_tiers() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"loyalty_tiers"], &usv_token::ID)
+}
+
+// Created on a wallet's first claim
+pub fn user_profile(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"user_profile", wallet.as_ref()], &usv_token::ID)
+}
+
+// Created on a wallet's first claim
+pub fn referral(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"referral", wallet.as_ref()], &usv_token::ID)
+}
+
+// usv_trading
+
+pub fn trading_state() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"trading_state"], &usv_trading::ID)
+}
+
+// Token account holding the USV for sale, owned by the trading state
+pub fn inventory() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"inventory"], &usv_trading::ID)
+}
+
+// Token account holding sold tokens until they vest, owned by the trading state
+pub fn vesting_vault() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vesting_vault"], &usv_trading::ID)
+}
+
+// Created on a wallet's first purchase that vests
+pub fn vesting_escrow(buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vesting", buyer.as_ref()], &usv_trading::ID)
+}
+
+// Whitelist entry for an SPL payment token
+pub fn payment_mint(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"payment_mint", mint.as_ref()], &usv_trading::ID)
+}
+
+// Token account paying out redemptions in an SPL payment token
+pub fn reserve(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"reserve", mint.as_ref()], &usv_trading::ID)
+}
+
+// System account paying out SOL redemptions
+pub fn sol_reserve() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"sol_reserve"], &usv_trading::ID)
+}
+
+// `index` counts up from 0 in the order rounds were added
+pub fn sale_round(index: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"sale_round", &index.to_le_bytes()], &usv_trading::ID)
+}
+
+// What `buyer` has bought in the sale round at `sale_round`
+pub fn round_buyer(sale_round: &Pubkey, buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"round_buyer", sale_round.as_ref(), buyer.as_ref()], &usv_trading::ID)
+}
+
+// The USV/SOL pool and its vaults
+pub fn pool() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"pool"], &usv_trading::ID)
+}
+
+pub fn pool_sol() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"pool_sol"], &usv_trading::ID)
+}
+
+pub fn pool_usv() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"pool_usv"], &usv_trading::ID)
+}
+
+pub fn lp_mint() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"lp_mint"], &usv_trading::ID)
+}
+
+// Pyth push oracle account for `feed_id`; shard 0 holds the sponsored feeds
+pub fn price_feed(shard: u16, feed_id: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[&shard.to_le_bytes(), feed_id], &usv_trading::oracle::pyth_push_oracle::ID)
+}
+
+// nft_auth_program
+
+pub fn program_state() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"state"], &nft_auth_program::ID)
+}
+
+pub fn master_collection() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"master"], &nft_auth_program::ID)
+}
+
+pub fn qr_data(qr_code: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"qr", qr_code.as_bytes()], &nft_auth_program::ID)
+}
+
+// Minting pause and guardians
+pub fn mint_guard() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"mint_guard"], &nft_auth_program::ID)
+}
+
+pub fn nft_mint(qr_code: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"nft", qr_code.as_bytes()], &nft_auth_program::ID)
+}
@@ -0,0 +1,313 @@
+// crates/usv-client/src/token.rs - Instruction builders for usv_token
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{system_program, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use usv_token::{accounts, instruction, LoyaltyTier, USVState, QR_CODE_LEN};
+
+use crate::pda;
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: usv_token::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+// Token account holding the authority's USV supply
+pub fn authority_token_account(authority: &Pubkey) -> Pubkey {
+    get_associated_token_address(authority, &pda::mint().0)
+}
+
+pub fn initialize(authority: &Pubkey) -> Instruction {
+    build(
+        accounts::Initialize {
+            usv_state: pda::usv_state().0,
+            loyalty_tiers: pda::loyalty_tiers().0,
+            mint: pda::mint().0,
+            mint_authority: pda::mint_authority().0,
+            authority_token_account: authority_token_account(authority),
+            authority: *authority,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::Initialize {},
+    )
+}
+
+// `batch_number` must be the current `USVState.total_batches`
+pub fn generate_qr_codes(
+    authority: &Pubkey,
+    batch_number: u32,
+    count: u32,
+    partner_id: Option<String>,
+    batch_info: String,
+) -> Instruction {
+    build(
+        accounts::GenerateQRCodes {
+            usv_state: pda::usv_state().0,
+            qr_batch: pda::qr_batch(authority, batch_number).0,
+            authority: *authority,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(&usv_token::ID).0,
+            program: usv_token::ID,
+        },
+        instruction::GenerateQrCodes {
+            count,
+            partner_id,
+            batch_info,
+        },
+    )
+}
+
+// `batch_number` must be the current `USVState.total_batches`
+pub fn create_batch(
+    authority: &Pubkey,
+    batch_number: u32,
+    partner_id: Option<String>,
+    batch_info: String,
+) -> Instruction {
+    build(
+        accounts::CreateBatch {
+            usv_state: pda::usv_state().0,
+            qr_batch: pda::qr_batch(authority, batch_number).0,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::CreateBatch {
+            partner_id,
+            batch_info,
+        },
+    )
+}
+
+pub fn append_qr_codes(authority: &Pubkey, qr_batch: &Pubkey, count: u32) -> Instruction {
+    build(
+        accounts::AppendQRCodes {
+            usv_state: pda::usv_state().0,
+            qr_batch: *qr_batch,
+            authority: *authority,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(&usv_token::ID).0,
+            program: usv_token::ID,
+        },
+        instruction::AppendQrCodes { count },
+    )
+}
+
+pub fn seal_batch(authority: &Pubkey, qr_batch: &Pubkey) -> Instruction {
+    build(
+        accounts::SealBatch {
+            usv_state: pda::usv_state().0,
+            qr_batch: *qr_batch,
+            authority: *authority,
+            event_authority: pda::event_authority(&usv_token::ID).0,
+            program: usv_token::ID,
+        },
+        instruction::SealBatch {},
+    )
+}
+
+// Whether claims must pass their batch's breaker account
+pub fn batch_breaker_required(state: &USVState) -> bool {
+    state.breaker_bucket_secs > 0 && state.breaker_max_batch_claims > 0
+}
+
+// `referrer` only pays out on the claimer's first claim, and must have claimed before.
+// `batch_breaker` must be set while batch_breaker_required holds
+#[allow(clippy::too_many_arguments)]
+pub fn claim_tokens(
+    authority: &Pubkey,
+    claimer: &Pubkey,
+    qr_batch: &Pubkey,
+    qr_code: [u8; QR_CODE_LEN],
+    code_index: u32,
+    user_email: Option<String>,
+    referrer: Option<&Pubkey>,
+    batch_breaker: bool,
+) -> Instruction {
+    let mint = pda::mint().0;
+    build(
+        accounts::ClaimTokens {
+            usv_state: pda::usv_state().0,
+            qr_batch: *qr_batch,
+            batch_breaker: batch_breaker.then(|| pda::batch_breaker(qr_batch).0),
+            qr_claim: pda::qr_claim(&qr_code).0,
+            authority_token_account: authority_token_account(authority),
+            claimer_token_account: get_associated_token_address(claimer, &mint),
+            referral: pda::referral(claimer).0,
+            user_profile: pda::user_profile(claimer).0,
+            loyalty_tiers: pda::loyalty_tiers().0,
+            referrer_referral: referrer.map(|referrer| pda::referral(referrer).0),
+            referrer_token_account: referrer.map(|referrer| get_associated_token_address(referrer, &mint)),
+            mint,
+            authority: *authority,
+            claimer: *claimer,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(&usv_token::ID).0,
+            program: usv_token::ID,
+        },
+        instruction::ClaimTokens {
+            qr_code,
+            code_index,
+            user_email,
+            referrer: referrer.copied(),
+        },
+    )
+}
+
+pub fn transfer_to_partner(
+    authority: &Pubkey,
+    partner: &Pubkey,
+    amount: u64,
+    partner_info: String,
+) -> Instruction {
+    let mint = pda::mint().0;
+    build(
+        accounts::TransferToPartner {
+            usv_state: pda::usv_state().0,
+            authority_token_account: authority_token_account(authority),
+            partner_token_account: get_associated_token_address(partner, &mint),
+            mint,
+            partner: *partner,
+            authority: *authority,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(&usv_token::ID).0,
+            program: usv_token::ID,
+        },
+        instruction::TransferToPartner {
+            amount,
+            partner_info,
+        },
+    )
+}
+
+pub fn set_pause_state(authority: &Pubkey, is_paused: bool) -> Instruction {
+    build(
+        accounts::SetPauseState {
+            usv_state: pda::usv_state().0,
+            authority: *authority,
+        },
+        instruction::SetPauseState { is_paused },
+    )
+}
+
+// Rewrites a USVState created before the referral and later fields into the current layout
+pub fn migrate_state(authority: &Pubkey) -> Instruction {
+    build(
+        accounts::MigrateState {
+            usv_state: pda::usv_state().0,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateState {},
+    )
+}
+
+// `tiers` must be ordered by `min_claims`, starting at 0; creates the tiers account if needed
+pub fn set_loyalty_tiers(authority: &Pubkey, tiers: Vec<LoyaltyTier>) -> Instruction {
+    build(
+        accounts::SetLoyaltyTiers {
+            usv_state: pda::usv_state().0,
+            loyalty_tiers: pda::loyalty_tiers().0,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::SetLoyaltyTiers { tiers },
+    )
+}
+
+// 0 disables a limit
+pub fn set_claim_limits(authority: &Pubkey, max_claims_per_day: u32, max_tokens_per_day: u64) -> Instruction {
+    build(
+        accounts::SetClaimLimits {
+            usv_state: pda::usv_state().0,
+            authority: *authority,
+        },
+        instruction::SetClaimLimits {
+            max_claims_per_day,
+            max_tokens_per_day,
+        },
+    )
+}
+
+// `threshold` of `guardians` must call `guardian_pause` to pause claims
+pub fn set_guardians(authority: &Pubkey, guardians: Vec<Pubkey>, threshold: u8) -> Instruction {
+    build(
+        accounts::SetGuardians {
+            usv_state: pda::usv_state().0,
+            authority: *authority,
+        },
+        instruction::SetGuardians { guardians, threshold },
+    )
+}
+
+pub fn guardian_pause(guardian: &Pubkey) -> Instruction {
+    build(
+        accounts::GuardianPause {
+            usv_state: pda::usv_state().0,
+            guardian: *guardian,
+            event_authority: pda::event_authority(&usv_token::ID).0,
+            program: usv_token::ID,
+        },
+        instruction::GuardianPause {},
+    )
+}
+
+// `bucket_secs` 0 disables the breaker; a threshold of 0 disables that scope
+pub fn set_circuit_breaker(authority: &Pubkey, bucket_secs: u32, max_claims: u32, max_batch_claims: u32) -> Instruction {
+    build(
+        accounts::SetCircuitBreaker {
+            usv_state: pda::usv_state().0,
+            authority: *authority,
+        },
+        instruction::SetCircuitBreaker {
+            bucket_secs,
+            max_claims,
+            max_batch_claims,
+        },
+    )
+}
+
+pub fn set_trusted(authority: &Pubkey, wallet: &Pubkey, is_trusted: bool) -> Instruction {
+    build(
+        accounts::SetTrusted {
+            usv_state: pda::usv_state().0,
+            user_profile: pda::user_profile(wallet).0,
+            wallet: *wallet,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::SetTrusted { is_trusted },
+    )
+}
+
+pub fn set_referral_bonus(authority: &Pubkey, referral_bonus: u64) -> Instruction {
+    build(
+        accounts::SetReferralBonus {
+            usv_state: pda::usv_state().0,
+            authority: *authority,
+        },
+        instruction::SetReferralBonus { referral_bonus },
+    )
+}
+
+pub fn get_stats() -> Instruction {
+    build(
+        accounts::GetStats {
+            usv_state: pda::usv_state().0,
+            event_authority: pda::event_authority(&usv_token::ID).0,
+            program: usv_token::ID,
+        },
+        instruction::GetStats {},
+    )
+}
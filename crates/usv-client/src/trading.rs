@@ -0,0 +1,669 @@
+// crates/usv-client/src/trading.rs - Instruction builders for usv_trading
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use usv_trading::curve::BondingCurve;
+use usv_trading::{accounts, instruction, TradingState};
+
+use crate::pda;
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: usv_trading::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+// `price_feed_id` is the Pyth SOL/USD feed, e.g. `oracle::SOL_USD_FEED_ID`;
+// SOL proceeds go to `treasury`
+pub fn initialize_trading(
+    authority: &Pubkey,
+    usv_mint: &Pubkey,
+    treasury: &Pubkey,
+    price_feed_id: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::InitializeTrading {
+            trading_state: pda::trading_state().0,
+            usv_mint: *usv_mint,
+            inventory: pda::inventory().0,
+            vesting_vault: pda::vesting_vault().0,
+            treasury: *treasury,
+            authority: *authority,
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeTrading { price_feed_id },
+    )
+}
+
+// The buyer's vesting escrow, created by the first purchase that passes it
+fn vesting_escrow(buyer: &Pubkey, vest: bool) -> Option<Pubkey> {
+    vest.then(|| pda::vesting_escrow(buyer).0)
+}
+
+// The sale round `buy_tokens` and `buy_tokens_with_spl` must be passed, if any remain
+pub fn current_round(state: &TradingState) -> Option<u32> {
+    (state.current_round < state.round_count).then_some(state.current_round)
+}
+
+// Fails if fewer than `min_tokens_out` would be delivered, the cluster clock is past `deadline`
+// or `current_round` is live. `vest` must be set for purchases that may vest, which then go to
+// the buyer's escrow
+#[allow(clippy::too_many_arguments)]
+pub fn buy_tokens(
+    buyer: &Pubkey,
+    buyer_token_account: &Pubkey,
+    treasury: &Pubkey,
+    usv_mint: &Pubkey,
+    price_update: &Pubkey,
+    sol_amount: u64,
+    min_tokens_out: u64,
+    deadline: i64,
+    current_round: Option<u32>,
+    vest: bool,
+) -> Instruction {
+    build(
+        accounts::BuyTokens {
+            trading_state: pda::trading_state().0,
+            sale_round: current_round.map(|index| pda::sale_round(index).0),
+            usv_mint: *usv_mint,
+            price_update: *price_update,
+            inventory: pda::inventory().0,
+            buyer_token_account: *buyer_token_account,
+            vesting_vault: pda::vesting_vault().0,
+            vesting_escrow: vesting_escrow(buyer, vest),
+            treasury: *treasury,
+            buyer: *buyer,
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(&usv_trading::ID).0,
+            program: usv_trading::ID,
+        },
+        instruction::BuyTokens {
+            sol_amount,
+            min_tokens_out,
+            deadline,
+        },
+    )
+}
+
+// Pays `payment_amount` base units of `payment_mint` into its configured treasury;
+// `current_round` and `vest` as for `buy_tokens`
+#[allow(clippy::too_many_arguments)]
+pub fn buy_tokens_with_spl(
+    buyer: &Pubkey,
+    buyer_payment_account: &Pubkey,
+    buyer_token_account: &Pubkey,
+    payment_mint: &Pubkey,
+    treasury: &Pubkey,
+    usv_mint: &Pubkey,
+    payment_amount: u64,
+    min_tokens_out: u64,
+    deadline: i64,
+    current_round: Option<u32>,
+    vest: bool,
+) -> Instruction {
+    build(
+        accounts::BuyTokensWithSpl {
+            trading_state: pda::trading_state().0,
+            sale_round: current_round.map(|index| pda::sale_round(index).0),
+            usv_mint: *usv_mint,
+            payment_config: pda::payment_mint(payment_mint).0,
+            payment_mint: *payment_mint,
+            buyer_payment_account: *buyer_payment_account,
+            treasury: *treasury,
+            inventory: pda::inventory().0,
+            buyer_token_account: *buyer_token_account,
+            vesting_vault: pda::vesting_vault().0,
+            vesting_escrow: vesting_escrow(buyer, vest),
+            buyer: *buyer,
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(&usv_trading::ID).0,
+            program: usv_trading::ID,
+        },
+        instruction::BuyTokensWithSpl {
+            payment_amount,
+            min_tokens_out,
+            deadline,
+        },
+    )
+}
+
+// Redeems `token_amount` into the inventory for SOL from the reserve
+#[allow(clippy::too_many_arguments)]
+pub fn sell_tokens(
+    seller: &Pubkey,
+    seller_token_account: &Pubkey,
+    usv_mint: &Pubkey,
+    claim_vault: &Pubkey,
+    price_update: &Pubkey,
+    token_amount: u64,
+    min_lamports_out: u64,
+    deadline: i64,
+) -> Instruction {
+    build(
+        accounts::SellTokens {
+            trading_state: pda::trading_state().0,
+            usv_mint: *usv_mint,
+            price_update: *price_update,
+            seller_token_account: *seller_token_account,
+            inventory: pda::inventory().0,
+            claim_vault: *claim_vault,
+            vesting_vault: pda::vesting_vault().0,
+            pool_usv: pda::pool_usv().0,
+            sol_reserve: pda::sol_reserve().0,
+            seller: *seller,
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(&usv_trading::ID).0,
+            program: usv_trading::ID,
+        },
+        instruction::SellTokens {
+            token_amount,
+            min_lamports_out,
+            deadline,
+        },
+    )
+}
+
+// Redeems `token_amount` into the inventory for `payment_mint` from its reserve
+#[allow(clippy::too_many_arguments)]
+pub fn sell_tokens_for_spl(
+    seller: &Pubkey,
+    seller_token_account: &Pubkey,
+    seller_payment_account: &Pubkey,
+    payment_mint: &Pubkey,
+    usv_mint: &Pubkey,
+    claim_vault: &Pubkey,
+    token_amount: u64,
+    min_payout: u64,
+    deadline: i64,
+) -> Instruction {
+    build(
+        accounts::SellTokensForSpl {
+            trading_state: pda::trading_state().0,
+            usv_mint: *usv_mint,
+            payment_config: pda::payment_mint(payment_mint).0,
+            payment_mint: *payment_mint,
+            seller_token_account: *seller_token_account,
+            seller_payment_account: *seller_payment_account,
+            inventory: pda::inventory().0,
+            claim_vault: *claim_vault,
+            vesting_vault: pda::vesting_vault().0,
+            pool_usv: pda::pool_usv().0,
+            reserve: pda::reserve(payment_mint).0,
+            seller: *seller,
+            token_program: anchor_spl::token::ID,
+            event_authority: pda::event_authority(&usv_trading::ID).0,
+            program: usv_trading::ID,
+        },
+        instruction::SellTokensForSpl {
+            token_amount,
+            min_payout,
+            deadline,
+        },
+    )
+}
+
+// None returns purchases to the fixed price
+pub fn set_bonding_curve(authority: &Pubkey, curve: Option<BondingCurve>) -> Instruction {
+    build(
+        accounts::SetBondingCurve {
+            trading_state: pda::trading_state().0,
+            authority: *authority,
+        },
+        instruction::SetBondingCurve { curve },
+    )
+}
+
+// Purchases of more than `threshold` base units vest; 0 vests only in vesting rounds
+pub fn set_vesting_config(authority: &Pubkey, threshold: u64, cliff_secs: i64, duration_secs: i64) -> Instruction {
+    build(
+        accounts::SetVestingConfig {
+            trading_state: pda::trading_state().0,
+            authority: *authority,
+        },
+        instruction::SetVestingConfig {
+            threshold,
+            cliff_secs,
+            duration_secs,
+        },
+    )
+}
+
+// Sends whatever has unlocked in the buyer's vesting escrow to `buyer_token_account`
+pub fn release_vested(buyer: &Pubkey, buyer_token_account: &Pubkey, usv_mint: &Pubkey) -> Instruction {
+    build(
+        accounts::ReleaseVested {
+            trading_state: pda::trading_state().0,
+            vesting_escrow: pda::vesting_escrow(buyer).0,
+            usv_mint: *usv_mint,
+            vesting_vault: pda::vesting_vault().0,
+            buyer_token_account: *buyer_token_account,
+            buyer: *buyer,
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(&usv_trading::ID).0,
+            program: usv_trading::ID,
+        },
+        instruction::ReleaseVested {},
+    )
+}
+
+// A `bid_price_cents` of 0 disables redemption; a `max_redeemed_per_day` of 0 removes the cap.
+// `claim_vault` is the usv_token authority's token account that funds claims
+pub fn set_redemption_config(
+    authority: &Pubkey,
+    claim_vault: &Pubkey,
+    bid_price_cents: u64,
+    max_redeemed_per_day: u64,
+    min_reserve_ratio_bps: u64,
+) -> Instruction {
+    build(
+        accounts::SetRedemptionConfig {
+            trading_state: pda::trading_state().0,
+            claim_vault: *claim_vault,
+            authority: *authority,
+        },
+        instruction::SetRedemptionConfig {
+            bid_price_cents,
+            max_redeemed_per_day,
+            min_reserve_ratio_bps,
+        },
+    )
+}
+
+// `usd_price_micros` is the USD value of one whole token in millionths
+pub fn add_payment_mint(
+    authority: &Pubkey,
+    payment_mint: &Pubkey,
+    treasury: &Pubkey,
+    usd_price_micros: u64,
+) -> Instruction {
+    build(
+        accounts::AddPaymentMint {
+            trading_state: pda::trading_state().0,
+            payment_config: pda::payment_mint(payment_mint).0,
+            payment_mint: *payment_mint,
+            treasury: *treasury,
+            reserve: pda::reserve(payment_mint).0,
+            authority: *authority,
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::AddPaymentMint { usd_price_micros },
+    )
+}
+
+pub fn update_payment_mint(
+    authority: &Pubkey,
+    payment_mint: &Pubkey,
+    treasury: &Pubkey,
+    usd_price_micros: u64,
+    is_enabled: bool,
+) -> Instruction {
+    build(
+        accounts::UpdatePaymentMint {
+            trading_state: pda::trading_state().0,
+            payment_config: pda::payment_mint(payment_mint).0,
+            treasury: *treasury,
+            authority: *authority,
+        },
+        instruction::UpdatePaymentMint {
+            usd_price_micros,
+            is_enabled,
+        },
+    )
+}
+
+pub fn update_fixed_price(authority: &Pubkey, new_price_cents: u64) -> Instruction {
+    build(
+        accounts::UpdateFixedPrice {
+            trading_state: pda::trading_state().0,
+            authority: *authority,
+            event_authority: pda::event_authority(&usv_trading::ID).0,
+            program: usv_trading::ID,
+        },
+        instruction::UpdateFixedPrice { new_price_cents },
+    )
+}
+
+pub fn update_oracle_config(
+    authority: &Pubkey,
+    price_feed_id: [u8; 32],
+    max_price_age_secs: u64,
+    max_confidence_bps: u64,
+) -> Instruction {
+    build(
+        accounts::UpdateOracleConfig {
+            trading_state: pda::trading_state().0,
+            authority: *authority,
+        },
+        instruction::UpdateOracleConfig {
+            price_feed_id,
+            max_price_age_secs,
+            max_confidence_bps,
+        },
+    )
+}
+
+// Adds the round after the last one, at index `round_count` of the trading state
+#[allow(clippy::too_many_arguments)]
+pub fn add_sale_round(
+    authority: &Pubkey,
+    round_count: u32,
+    start_time: i64,
+    end_time: i64,
+    price_cents: u64,
+    cap: u64,
+    per_wallet_cap: u64,
+    allowlist_root: Option<[u8; 32]>,
+    vests: bool,
+) -> Instruction {
+    build(
+        accounts::AddSaleRound {
+            trading_state: pda::trading_state().0,
+            sale_round: pda::sale_round(round_count).0,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::AddSaleRound {
+            start_time,
+            end_time,
+            price_cents,
+            cap,
+            per_wallet_cap,
+            allowlist_root,
+            vests,
+        },
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_sale_round(
+    authority: &Pubkey,
+    index: u32,
+    start_time: i64,
+    end_time: i64,
+    price_cents: u64,
+    cap: u64,
+    per_wallet_cap: u64,
+    allowlist_root: Option<[u8; 32]>,
+    vests: bool,
+) -> Instruction {
+    build(
+        accounts::UpdateSaleRound {
+            trading_state: pda::trading_state().0,
+            sale_round: pda::sale_round(index).0,
+            authority: *authority,
+        },
+        instruction::UpdateSaleRound {
+            start_time,
+            end_time,
+            price_cents,
+            cap,
+            per_wallet_cap,
+            allowlist_root,
+            vests,
+        },
+    )
+}
+
+// `current_round` is the trading state's; `proof` comes from `allowlist::proof`
+// and is empty for rounds without an allowlist; `vest` as for `buy_tokens`
+#[allow(clippy::too_many_arguments)]
+pub fn buy_in_round(
+    buyer: &Pubkey,
+    buyer_token_account: &Pubkey,
+    treasury: &Pubkey,
+    usv_mint: &Pubkey,
+    price_update: &Pubkey,
+    current_round: u32,
+    sol_amount: u64,
+    min_tokens_out: u64,
+    deadline: i64,
+    proof: Vec<[u8; 32]>,
+    vest: bool,
+) -> Instruction {
+    let sale_round = pda::sale_round(current_round).0;
+    build(
+        accounts::BuyInRound {
+            trading_state: pda::trading_state().0,
+            sale_round,
+            round_buyer: pda::round_buyer(&sale_round, buyer).0,
+            usv_mint: *usv_mint,
+            price_update: *price_update,
+            inventory: pda::inventory().0,
+            buyer_token_account: *buyer_token_account,
+            vesting_vault: pda::vesting_vault().0,
+            vesting_escrow: vesting_escrow(buyer, vest),
+            treasury: *treasury,
+            buyer: *buyer,
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(&usv_trading::ID).0,
+            program: usv_trading::ID,
+        },
+        instruction::BuyInRound {
+            sol_amount,
+            min_tokens_out,
+            deadline,
+            proof,
+        },
+    )
+}
+
+pub fn advance_sale_round(current_round: u32) -> Instruction {
+    build(
+        accounts::AdvanceSaleRound {
+            trading_state: pda::trading_state().0,
+            sale_round: pda::sale_round(current_round).0,
+            event_authority: pda::event_authority(&usv_trading::ID).0,
+            program: usv_trading::ID,
+        },
+        instruction::AdvanceSaleRound {},
+    )
+}
+
+pub fn set_treasury(authority: &Pubkey, treasury: &Pubkey) -> Instruction {
+    build(
+        accounts::SetTreasury {
+            trading_state: pda::trading_state().0,
+            treasury: *treasury,
+            authority: *authority,
+        },
+        instruction::SetTreasury {},
+    )
+}
+
+// Moves unsold tokens from the inventory to `destination`
+pub fn withdraw_inventory(authority: &Pubkey, destination: &Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::WithdrawInventory {
+            trading_state: pda::trading_state().0,
+            inventory: pda::inventory().0,
+            destination: *destination,
+            authority: *authority,
+            token_program: anchor_spl::token::ID,
+        },
+        instruction::WithdrawInventory { amount },
+    )
+}
+
+// `fee_bps` of each swap's input goes to liquidity providers
+pub fn initialize_pool(authority: &Pubkey, usv_mint: &Pubkey, fee_bps: u64, max_price_impact_bps: u64) -> Instruction {
+    build(
+        accounts::InitializePool {
+            trading_state: pda::trading_state().0,
+            pool: pda::pool().0,
+            usv_mint: *usv_mint,
+            lp_mint: pda::lp_mint().0,
+            pool_usv: pda::pool_usv().0,
+            pool_sol: pda::pool_sol().0,
+            authority: *authority,
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::InitializePool {
+            fee_bps,
+            max_price_impact_bps,
+        },
+    )
+}
+
+// The first deposit into the pool sets its price with exactly `max_usv_amount`;
+// LP tokens go to the provider's associated token account
+pub fn add_liquidity(
+    provider: &Pubkey,
+    provider_token_account: &Pubkey,
+    sol_amount: u64,
+    max_usv_amount: u64,
+    min_lp_out: u64,
+) -> Instruction {
+    build(
+        accounts::AddLiquidity {
+            trading_state: pda::trading_state().0,
+            pool: pda::pool().0,
+            pool_usv: pda::pool_usv().0,
+            pool_sol: pda::pool_sol().0,
+            lp_mint: pda::lp_mint().0,
+            provider_token_account: *provider_token_account,
+            provider_lp_account: get_associated_token_address(provider, &pda::lp_mint().0),
+            provider: *provider,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(&usv_trading::ID).0,
+            program: usv_trading::ID,
+        },
+        instruction::AddLiquidity {
+            sol_amount,
+            max_usv_amount,
+            min_lp_out,
+        },
+    )
+}
+
+pub fn remove_liquidity(
+    provider: &Pubkey,
+    provider_token_account: &Pubkey,
+    lp_amount: u64,
+    min_sol_out: u64,
+    min_usv_out: u64,
+) -> Instruction {
+    build(
+        accounts::RemoveLiquidity {
+            pool: pda::pool().0,
+            pool_usv: pda::pool_usv().0,
+            pool_sol: pda::pool_sol().0,
+            lp_mint: pda::lp_mint().0,
+            provider_lp_account: get_associated_token_address(provider, &pda::lp_mint().0),
+            provider_token_account: *provider_token_account,
+            provider: *provider,
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(&usv_trading::ID).0,
+            program: usv_trading::ID,
+        },
+        instruction::RemoveLiquidity {
+            lp_amount,
+            min_sol_out,
+            min_usv_out,
+        },
+    )
+}
+
+// `amount_in` is lamports when `sol_to_usv`, USV base units otherwise
+pub fn swap(
+    trader: &Pubkey,
+    trader_token_account: &Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+    sol_to_usv: bool,
+    deadline: i64,
+) -> Instruction {
+    build(
+        accounts::Swap {
+            trading_state: pda::trading_state().0,
+            pool: pda::pool().0,
+            pool_usv: pda::pool_usv().0,
+            pool_sol: pda::pool_sol().0,
+            trader_token_account: *trader_token_account,
+            trader: *trader,
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(&usv_trading::ID).0,
+            program: usv_trading::ID,
+        },
+        instruction::Swap {
+            amount_in,
+            min_amount_out,
+            sol_to_usv,
+            deadline,
+        },
+    )
+}
+
+pub fn set_pool_config(authority: &Pubkey, fee_bps: u64, max_price_impact_bps: u64) -> Instruction {
+    build(
+        accounts::SetPoolConfig {
+            trading_state: pda::trading_state().0,
+            pool: pda::pool().0,
+            authority: *authority,
+        },
+        instruction::SetPoolConfig {
+            fee_bps,
+            max_price_impact_bps,
+        },
+    )
+}
+
+pub fn toggle_trading(authority: &Pubkey, is_active: bool) -> Instruction {
+    build(
+        accounts::ToggleTrading {
+            trading_state: pda::trading_state().0,
+            authority: *authority,
+        },
+        instruction::ToggleTrading { is_active },
+    )
+}
+
+// `threshold` of `guardians` must call `guardian_pause` to pause trading
+pub fn set_guardians(authority: &Pubkey, guardians: Vec<Pubkey>, threshold: u8) -> Instruction {
+    build(
+        accounts::SetGuardians {
+            trading_state: pda::trading_state().0,
+            authority: *authority,
+        },
+        instruction::SetGuardians { guardians, threshold },
+    )
+}
+
+pub fn guardian_pause(guardian: &Pubkey) -> Instruction {
+    build(
+        accounts::GuardianPause {
+            trading_state: pda::trading_state().0,
+            guardian: *guardian,
+            event_authority: pda::event_authority(&usv_trading::ID).0,
+            program: usv_trading::ID,
+        },
+        instruction::GuardianPause {},
+    )
+}
+
+pub fn get_trading_stats() -> Instruction {
+    build(
+        accounts::GetTradingStats {
+            trading_state: pda::trading_state().0,
+            event_authority: pda::event_authority(&usv_trading::ID).0,
+            program: usv_trading::ID,
+        },
+        instruction::GetTradingStats {},
+    )
+}
@@ -0,0 +1,1464 @@
+// crates/usv-client/tests/program_test.rs - Exercises the client against the programs in solana-program-test
+
+use anchor_lang::prelude::{AccountInfo, Pubkey};
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::Event;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::{AnchorSerialize, Discriminator, Space};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token;
+use solana_program_test::{processor, BanksClient, ProgramTest, ProgramTestContext};
+use solana_sdk::account::AccountSharedData;
+use solana_sdk::clock::Clock;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use usv_client::accounts::{
+    self, BatchBreaker, LoyaltyTier, MintGuard, Pool, ProgramState, QRClaim, QRData, Referral, SaleRound,
+    TradingState, USVState, UserProfile, VestingEscrow,
+};
+use usv_client::events::{self, UsvEvent};
+use usv_client::usv_trading::allowlist;
+use usv_client::usv_trading::curve::{BondingCurve, CurveKind};
+use usv_client::usv_trading::oracle::{self, PriceFeedMessage, PriceUpdateV2, VerificationLevel};
+use usv_client::{nft_auth, pda, token, trading};
+
+// Anchor's entrypoint ties the account slice and account lifetimes together
+fn usv_token_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    usv_token::entry(program_id, Box::leak(Box::new(accounts.to_vec())), data)
+}
+
+fn usv_trading_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    usv_trading::entry(program_id, Box::leak(Box::new(accounts.to_vec())), data)
+}
+
+fn nft_auth_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    nft_auth_program::entry(program_id, Box::leak(Box::new(accounts.to_vec())), data)
+}
+
+fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new("usv_token", usv_token::ID, processor!(usv_token_entry));
+    program_test.add_program("usv_trading", usv_trading::ID, processor!(usv_trading_entry));
+    program_test.add_program("nft_auth_program", nft_auth_program::ID, processor!(nft_auth_entry));
+    program_test
+}
+
+async fn start() -> (BanksClient, Keypair) {
+    let (banks_client, payer, _) = program_test().start().await;
+    (banks_client, payer)
+}
+
+// A verified SOL/USD update laid out as the Pyth receiver program writes it;
+// `price` and `conf` are in units of 10^-8 USD
+fn mock_price_update(price: i64, conf: u64, publish_time: i64) -> AccountSharedData {
+    let update = PriceUpdateV2 {
+        write_authority: Pubkey::new_unique(),
+        verification_level: VerificationLevel::Full,
+        price_message: PriceFeedMessage {
+            feed_id: oracle::SOL_USD_FEED_ID,
+            price,
+            conf,
+            exponent: -8,
+            publish_time,
+            prev_publish_time: publish_time - 1,
+            ema_price: price,
+            ema_conf: conf,
+        },
+        posted_slot: 1,
+    };
+    let data = [&oracle::PRICE_UPDATE_V2_DISCRIMINATOR[..], &update.try_to_vec().unwrap()].concat();
+    let mut account = AccountSharedData::new(1_000_000_000, data.len(), &oracle::pyth_receiver::ID);
+    account.set_data_from_slice(&data);
+    account
+}
+
+// Send instructions with `signers[0]` as fee payer
+async fn send(banks_client: &mut BanksClient, instructions: &[Instruction], signers: &[&Keypair]) {
+    let blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(instructions, Some(&signers[0].pubkey()), signers, blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+// Create the token and trading state, with the whole USV supply moved into the sale inventory
+async fn initialize_trading(banks_client: &mut BanksClient, authority: &Keypair, treasury: &Pubkey) {
+    let authority_tokens = token::authority_token_account(&authority.pubkey());
+    send(
+        banks_client,
+        &[
+            token::initialize(&authority.pubkey()),
+            trading::initialize_trading(&authority.pubkey(), &pda::mint().0, treasury, oracle::SOL_USD_FEED_ID),
+        ],
+        &[authority],
+    )
+    .await;
+    let supply = token_balance(banks_client, authority_tokens).await;
+    send(
+        banks_client,
+        &[spl_token::instruction::transfer(
+            &spl_token::ID,
+            &authority_tokens,
+            &pda::inventory().0,
+            &authority.pubkey(),
+            &[],
+            supply,
+        )
+        .unwrap()],
+        &[authority],
+    )
+    .await;
+}
+
+// Whether the instructions are rejected, with `signers[0]` as fee payer
+async fn fails(banks_client: &mut BanksClient, instructions: &[Instruction], signers: &[&Keypair]) -> bool {
+    let blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(instructions, Some(&signers[0].pubkey()), signers, blockhash);
+    banks_client.process_transaction(tx).await.is_err()
+}
+
+async fn account_data(banks_client: &mut BanksClient, address: &Pubkey) -> Vec<u8> {
+    banks_client.get_account(*address).await.unwrap().unwrap().data
+}
+
+#[tokio::test]
+async fn usv_token_generate_and_claim() {
+    let (mut banks_client, authority) = start().await;
+    send(&mut banks_client, &[token::initialize(&authority.pubkey())], &[&authority]).await;
+
+    send(
+        &mut banks_client,
+        &[token::generate_qr_codes(&authority.pubkey(), 0, 10, Some("PARTNER_1".into()), "test run".into())],
+        &[&authority],
+    )
+    .await;
+
+    let qr_batch = pda::qr_batch(&authority.pubkey(), 0).0;
+    let batch = accounts::decode_qr_batch(&account_data(&mut banks_client, &qr_batch).await).unwrap();
+    assert_eq!(batch.header.count, 10);
+    assert_eq!(batch.codes.len(), 10);
+    assert_eq!(batch.header.partner_id().as_deref(), Some("PARTNER_1"));
+    assert!(batch.header.is_sealed());
+
+    let claimer = Keypair::new();
+    let qr_code = batch.codes[3];
+    send(
+        &mut banks_client,
+        &[token::claim_tokens(&authority.pubkey(), &claimer.pubkey(), &qr_batch, qr_code, 3, None, None, false)],
+        &[&authority],
+    )
+    .await;
+
+    let claim: QRClaim = accounts::decode(&account_data(&mut banks_client, &pda::qr_claim(&qr_code).0).await).unwrap();
+    assert_eq!(claim.claimer, claimer.pubkey());
+
+    let state: USVState = accounts::decode(&account_data(&mut banks_client, &pda::usv_state().0).await).unwrap();
+    assert_eq!(state.total_qr_codes, 10);
+    assert_eq!(state.tokens_claimed, 1_000_000);
+    // With the breaker off, claims do not create the batch's breaker account
+    assert!(banks_client.get_account(pda::batch_breaker(&qr_batch).0).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn usv_token_referral_bonus() {
+    let (mut banks_client, authority) = start().await;
+    send(
+        &mut banks_client,
+        &[
+            token::initialize(&authority.pubkey()),
+            token::generate_qr_codes(&authority.pubkey(), 0, 4, None, "referrals".into()),
+            token::set_referral_bonus(&authority.pubkey(), 250_000),
+        ],
+        &[&authority],
+    )
+    .await;
+    let qr_batch = pda::qr_batch(&authority.pubkey(), 0).0;
+    let codes = accounts::decode_qr_batch(&account_data(&mut banks_client, &qr_batch).await).unwrap().codes;
+    let claim = |claimer: &Keypair, index: usize, referrer: Option<&Pubkey>| {
+        token::claim_tokens(
+            &authority.pubkey(),
+            &claimer.pubkey(),
+            &qr_batch,
+            codes[index],
+            index as u32,
+            None,
+            referrer,
+            false,
+        )
+    };
+
+    // A wallet that has never claimed cannot refer anyone, nor can a wallet refer itself
+    let referrer = Keypair::new();
+    let referee = Keypair::new();
+    for ix in [claim(&referee, 0, Some(&referrer.pubkey())), claim(&referee, 0, Some(&referee.pubkey()))] {
+        let blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        let tx = Transaction::new_signed_with_payer(&[ix], Some(&authority.pubkey()), &[&authority], blockhash);
+        assert!(banks_client.process_transaction(tx).await.is_err());
+    }
+
+    send(&mut banks_client, &[claim(&referrer, 1, None)], &[&authority]).await;
+
+    // The referrer's record must be the one derived from `referrer`, and cannot be left out
+    let bystander = Keypair::new();
+    for substitute in [pda::referral(&referrer.pubkey()).0, usv_token::ID] {
+        let mut ix = claim(&referee, 0, Some(&bystander.pubkey()));
+        let meta = ix.accounts.iter_mut().find(|meta| meta.pubkey == pda::referral(&bystander.pubkey()).0).unwrap();
+        meta.pubkey = substitute;
+        assert!(fails(&mut banks_client, &[ix], &[&authority]).await);
+    }
+
+    send(&mut banks_client, &[claim(&referee, 0, Some(&referrer.pubkey()))], &[&authority]).await;
+    // Only the first claim is rewarded, though later ones still have to name a valid referrer
+    let mut ix = claim(&referee, 2, Some(&referrer.pubkey()));
+    let meta = ix.accounts.iter_mut().find(|meta| meta.pubkey == pda::referral(&referrer.pubkey()).0).unwrap();
+    meta.pubkey = usv_token::ID;
+    assert!(fails(&mut banks_client, &[ix], &[&authority]).await);
+    send(&mut banks_client, &[claim(&referee, 2, Some(&referrer.pubkey()))], &[&authority]).await;
+
+    let referral: Referral = accounts::decode(&account_data(&mut banks_client, &pda::referral(&referee.pubkey()).0).await).unwrap();
+    assert_eq!(referral.referrer, Some(referrer.pubkey()));
+
+    let referrer_tokens = get_associated_token_address(&referrer.pubkey(), &pda::mint().0);
+    let balance = banks_client.get_packed_account_data::<spl_token::state::Account>(referrer_tokens).await.unwrap().amount;
+    assert_eq!(balance, 1_250_000);
+
+    let state: USVState = accounts::decode(&account_data(&mut banks_client, &pda::usv_state().0).await).unwrap();
+    assert_eq!(state.referral_rewards_paid, 250_000);
+    assert_eq!(state.tokens_claimed, 3_000_000);
+}
+
+#[tokio::test]
+async fn usv_token_referral_needs_first_claim() {
+    let mut context = program_test().start_with_context().await;
+    let authority = context.payer.insecure_clone();
+    send(
+        &mut context.banks_client,
+        &[
+            token::initialize(&authority.pubkey()),
+            token::generate_qr_codes(&authority.pubkey(), 0, 3, None, "late referrals".into()),
+            token::set_referral_bonus(&authority.pubkey(), 250_000),
+        ],
+        &[&authority],
+    )
+    .await;
+    let qr_batch = pda::qr_batch(&authority.pubkey(), 0).0;
+    let codes = accounts::decode_qr_batch(&account_data(&mut context.banks_client, &qr_batch).await).unwrap().codes;
+    let claim = |claimer: &Pubkey, index: usize, referrer: Option<&Pubkey>| {
+        token::claim_tokens(&authority.pubkey(), claimer, &qr_batch, codes[index], index as u32, None, referrer, false)
+    };
+
+    let referrer = Keypair::new();
+    let referee = Keypair::new();
+    send(
+        &mut context.banks_client,
+        &[claim(&referrer.pubkey(), 0, None), claim(&referee.pubkey(), 1, None)],
+        &[&authority],
+    )
+    .await;
+
+    // A wallet that claimed before referral records existed has a profile but no record
+    context.set_account(&pda::referral(&referee.pubkey()).0, &AccountSharedData::default());
+    let mut banks_client = context.banks_client;
+    send(&mut banks_client, &[claim(&referee.pubkey(), 2, Some(&referrer.pubkey()))], &[&authority]).await;
+
+    let referral: Referral = accounts::decode(&account_data(&mut banks_client, &pda::referral(&referee.pubkey()).0).await).unwrap();
+    assert_eq!((referral.referee, referral.referrer), (referee.pubkey(), None));
+    let state: USVState = accounts::decode(&account_data(&mut banks_client, &pda::usv_state().0).await).unwrap();
+    assert_eq!(state.referral_rewards_paid, 0);
+}
+
+#[tokio::test]
+async fn usv_token_loyalty_tiers() {
+    let (mut banks_client, authority) = start().await;
+    let tier = |name: &str, min_claims, multiplier_bps| LoyaltyTier {
+        name: name.into(),
+        min_claims,
+        multiplier_bps,
+    };
+    send(
+        &mut banks_client,
+        &[
+            token::initialize(&authority.pubkey()),
+            token::generate_qr_codes(&authority.pubkey(), 0, 4, None, "loyalty".into()),
+            token::set_loyalty_tiers(&authority.pubkey(), vec![tier("Bronze", 0, 10_000), tier("Silver", 2, 15_000)]),
+        ],
+        &[&authority],
+    )
+    .await;
+
+    // Tiers must start at zero claims
+    let blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let ix = token::set_loyalty_tiers(&authority.pubkey(), vec![tier("Silver", 2, 15_000)]);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&authority.pubkey()), &[&authority], blockhash);
+    assert!(banks_client.process_transaction(tx).await.is_err());
+
+    let qr_batch = pda::qr_batch(&authority.pubkey(), 0).0;
+    let codes = accounts::decode_qr_batch(&account_data(&mut banks_client, &qr_batch).await).unwrap().codes;
+    let claimer = Keypair::new();
+    for (index, qr_code) in codes.into_iter().enumerate().take(3) {
+        let ix = token::claim_tokens(
+            &authority.pubkey(),
+            &claimer.pubkey(),
+            &qr_batch,
+            qr_code,
+            index as u32,
+            None,
+            None,
+            false,
+        );
+        send(&mut banks_client, &[ix], &[&authority]).await;
+    }
+
+    // Two Bronze claims reach Silver, so the third pays 1.5x
+    let profile: UserProfile = accounts::decode(&account_data(&mut banks_client, &pda::user_profile(&claimer.pubkey()).0).await).unwrap();
+    assert_eq!(profile.claim_count, 3);
+    assert_eq!(profile.tier, 1);
+    assert_eq!(profile.lifetime_rewards, 3_500_000);
+}
+
+#[tokio::test]
+async fn usv_token_migrate_state() {
+    let mut context = program_test().start_with_context().await;
+    let authority = context.payer.insecure_clone();
+    send(&mut context.banks_client, &[token::initialize(&authority.pubkey())], &[&authority]).await;
+
+    // Roll the deployment back to the state layout that predates referrals, with no loyalty tiers
+    let usv_state = pda::usv_state().0;
+    let state: USVState = accounts::decode(&account_data(&mut context.banks_client, &usv_state).await).unwrap();
+    let legacy = [
+        &USVState::DISCRIMINATOR[..],
+        &state.authority.to_bytes(),
+        &state.mint.to_bytes(),
+        &state.total_supply.to_le_bytes(),
+        &5_000_000u64.to_le_bytes(),
+        &40u32.to_le_bytes(),
+        &0u32.to_le_bytes(),
+        &[0, state.bump, state.mint_bump],
+    ]
+    .concat();
+    let mut account = AccountSharedData::new(1_000_000_000, legacy.len(), &usv_token::ID);
+    account.set_data_from_slice(&legacy);
+    context.set_account(&usv_state, &account);
+    context.set_account(&pda::loyalty_tiers().0, &AccountSharedData::default());
+
+    let mut banks_client = context.banks_client;
+    assert!(fails(&mut banks_client, &[token::set_claim_limits(&authority.pubkey(), 2, 0)], &[&authority]).await);
+    send(&mut banks_client, &[token::migrate_state(&authority.pubkey())], &[&authority]).await;
+
+    // The old fields carry over and every added one starts disabled
+    let data = account_data(&mut banks_client, &usv_state).await;
+    assert_eq!(data.len(), 8 + USVState::INIT_SPACE);
+    let migrated: USVState = accounts::decode(&data).unwrap();
+    assert_eq!(migrated.authority, authority.pubkey());
+    assert_eq!(migrated.total_supply, state.total_supply);
+    assert_eq!(migrated.tokens_claimed, 5_000_000);
+    assert_eq!(migrated.total_qr_codes, 40);
+    assert_eq!((migrated.referral_bonus, migrated.breaker_bucket_secs), (0, 0));
+    assert!(migrated.guardians.members.is_empty() && !migrated.is_paused);
+    assert_eq!((migrated.bump, migrated.mint_bump), (state.bump, state.mint_bump));
+
+    // A second migration would misread the current layout
+    let ix = token::set_claim_limits(&authority.pubkey(), 2, 0);
+    assert!(fails(&mut banks_client, &[ix.clone(), token::migrate_state(&authority.pubkey())], &[&authority]).await);
+
+    // Setting tiers creates the missing account, after which claims go through again
+    send(
+        &mut banks_client,
+        &[
+            ix,
+            token::set_loyalty_tiers(&authority.pubkey(), Vec::new()),
+            token::generate_qr_codes(&authority.pubkey(), 0, 1, None, "migrated".into()),
+        ],
+        &[&authority],
+    )
+    .await;
+    let qr_batch = pda::qr_batch(&authority.pubkey(), 0).0;
+    let codes = accounts::decode_qr_batch(&account_data(&mut banks_client, &qr_batch).await).unwrap().codes;
+    let claimer = Keypair::new();
+    let ix = token::claim_tokens(&authority.pubkey(), &claimer.pubkey(), &qr_batch, codes[0], 0, None, None, false);
+    send(&mut banks_client, &[ix], &[&authority]).await;
+    let state: USVState = accounts::decode(&account_data(&mut banks_client, &usv_state).await).unwrap();
+    assert_eq!(state.tokens_claimed, 6_000_000);
+    assert_eq!(state.max_claims_per_day, 2);
+}
+
+#[tokio::test]
+async fn usv_token_migrate_inserted_fields() {
+    let mut context = program_test().start_with_context().await;
+    let authority = context.payer.insecure_clone();
+    send(&mut context.banks_client, &[token::initialize(&authority.pubkey())], &[&authority]).await;
+
+    // The last layout that inserted its fields ahead of `is_paused`, with every feature configured
+    let usv_state = pda::usv_state().0;
+    let state: USVState = accounts::decode(&account_data(&mut context.banks_client, &usv_state).await).unwrap();
+    let guardian = Pubkey::new_unique();
+    let mut legacy = [
+        &USVState::DISCRIMINATOR[..],
+        &state.authority.to_bytes(),
+        &state.mint.to_bytes(),
+        &state.total_supply.to_le_bytes(),
+        &7_000_000u64.to_le_bytes(),
+        &40u32.to_le_bytes(),
+        &1u32.to_le_bytes(),
+        &250_000u64.to_le_bytes(),
+        &500_000u64.to_le_bytes(),
+        &3u32.to_le_bytes(),
+        &0u64.to_le_bytes(),
+        &3600u32.to_le_bytes(),
+        &0u32.to_le_bytes(),
+        &10u32.to_le_bytes(),
+        &0i64.to_le_bytes(),
+        &0u32.to_le_bytes(),
+        &2u32.to_le_bytes(),
+        &1u32.to_le_bytes(),
+        &guardian.to_bytes(),
+        &[1, 0],
+        &0i64.to_le_bytes(),
+        &[1, state.bump, state.mint_bump],
+    ]
+    .concat();
+    legacy.resize(8 + 417, 0);
+    let mut account = AccountSharedData::new(1_000_000_000, legacy.len(), &usv_token::ID);
+    account.set_data_from_slice(&legacy);
+    context.set_account(&usv_state, &account);
+
+    let mut banks_client = context.banks_client;
+    send(&mut banks_client, &[token::migrate_state(&authority.pubkey())], &[&authority]).await;
+    let data = account_data(&mut banks_client, &usv_state).await;
+    assert_eq!(data.len(), 8 + USVState::INIT_SPACE);
+    let migrated: USVState = accounts::decode(&data).unwrap();
+    assert_eq!((migrated.tokens_claimed, migrated.total_qr_codes, migrated.total_batches), (7_000_000, 40, 1));
+    assert_eq!((migrated.referral_bonus, migrated.referral_rewards_paid), (250_000, 500_000));
+    assert_eq!((migrated.max_claims_per_day, migrated.max_tokens_per_day), (3, 0));
+    assert_eq!((migrated.breaker_bucket_secs, migrated.breaker_max_batch_claims, migrated.breaker_epoch), (3600, 10, 2));
+    assert_eq!((migrated.guardians.members, migrated.guardians.threshold), (vec![guardian], 1));
+    assert!(migrated.is_paused);
+    assert_eq!((migrated.bump, migrated.mint_bump), (state.bump, state.mint_bump));
+    assert_eq!(migrated.layout_version, usv_token::USV_STATE_LAYOUT_VERSION);
+
+    // Accounts already in the current layout are left alone
+    assert!(fails(&mut banks_client, &[token::migrate_state(&authority.pubkey())], &[&authority]).await);
+}
+
+#[tokio::test]
+async fn usv_token_claim_limits() {
+    let (mut banks_client, authority) = start().await;
+    send(
+        &mut banks_client,
+        &[
+            token::initialize(&authority.pubkey()),
+            token::generate_qr_codes(&authority.pubkey(), 0, 4, None, "limits".into()),
+            token::set_claim_limits(&authority.pubkey(), 2, 0),
+        ],
+        &[&authority],
+    )
+    .await;
+    let qr_batch = pda::qr_batch(&authority.pubkey(), 0).0;
+    let codes = accounts::decode_qr_batch(&account_data(&mut banks_client, &qr_batch).await).unwrap().codes;
+    let claimer = Keypair::new();
+    let claim = |index: usize| {
+        token::claim_tokens(
+            &authority.pubkey(),
+            &claimer.pubkey(),
+            &qr_batch,
+            codes[index],
+            index as u32,
+            None,
+            None,
+            false,
+        )
+    };
+
+    send(&mut banks_client, &[claim(0), claim(1)], &[&authority]).await;
+    let blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[claim(2)], Some(&authority.pubkey()), &[&authority], blockhash);
+    assert!(banks_client.process_transaction(tx).await.is_err());
+
+    // Trusted wallets bypass the limit
+    send(
+        &mut banks_client,
+        &[token::set_trusted(&authority.pubkey(), &claimer.pubkey(), true), claim(2)],
+        &[&authority],
+    )
+    .await;
+    let profile: UserProfile = accounts::decode(&account_data(&mut banks_client, &pda::user_profile(&claimer.pubkey()).0).await).unwrap();
+    assert!(profile.is_trusted);
+    assert_eq!(profile.claim_count, 3);
+    assert_eq!(profile.window_claims, 3);
+}
+
+#[tokio::test]
+async fn usv_token_circuit_breaker() {
+    let (mut banks_client, authority) = start().await;
+    send(
+        &mut banks_client,
+        &[
+            token::initialize(&authority.pubkey()),
+            token::generate_qr_codes(&authority.pubkey(), 0, 5, None, "breaker".into()),
+            token::set_circuit_breaker(&authority.pubkey(), 3600, 0, 2),
+        ],
+        &[&authority],
+    )
+    .await;
+    let qr_batch = pda::qr_batch(&authority.pubkey(), 0).0;
+    let codes = accounts::decode_qr_batch(&account_data(&mut banks_client, &qr_batch).await).unwrap().codes;
+    let claim_with = |index: usize, batch_breaker: bool| {
+        let claimer = Pubkey::new_unique();
+        token::claim_tokens(
+            &authority.pubkey(),
+            &claimer,
+            &qr_batch,
+            codes[index],
+            index as u32,
+            None,
+            None,
+            batch_breaker,
+        )
+    };
+    let claim = |index: usize| claim_with(index, true);
+
+    // While the per-batch breaker is on, claims must pass the batch's breaker account
+    assert!(fails(&mut banks_client, &[claim_with(0, false)], &[&authority]).await);
+
+    // Reaching the threshold is allowed; the claim that goes past it pauses claims
+    send(&mut banks_client, &[claim(0), claim(1)], &[&authority]).await;
+    let state: USVState = accounts::decode(&account_data(&mut banks_client, &pda::usv_state().0).await).unwrap();
+    assert!(!state.is_paused);
+    send(&mut banks_client, &[claim(2)], &[&authority]).await;
+    let state: USVState = accounts::decode(&account_data(&mut banks_client, &pda::usv_state().0).await).unwrap();
+    assert!(state.is_paused);
+    let breaker: BatchBreaker =
+        accounts::decode(&account_data(&mut banks_client, &pda::batch_breaker(&qr_batch).0).await).unwrap();
+    assert_eq!(breaker.bucket_claims, 3);
+
+    let blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[claim(3)], Some(&authority.pubkey()), &[&authority], blockhash);
+    assert!(banks_client.process_transaction(tx).await.is_err());
+
+    // Unpausing starts fresh buckets, so the next claim does not trip it again
+    send(&mut banks_client, &[token::set_pause_state(&authority.pubkey(), false), claim(3)], &[&authority]).await;
+    let state: USVState = accounts::decode(&account_data(&mut banks_client, &pda::usv_state().0).await).unwrap();
+    assert!(!state.is_paused);
+
+    // Reconfiguring the breaker discards the batch's bucket as well as the global one
+    let set_breaker = token::set_circuit_breaker(&authority.pubkey(), 3600, 0, 2);
+    send(&mut banks_client, &[set_breaker, claim(4)], &[&authority]).await;
+    let state: USVState = accounts::decode(&account_data(&mut banks_client, &pda::usv_state().0).await).unwrap();
+    assert!(!state.is_paused);
+    let breaker: BatchBreaker =
+        accounts::decode(&account_data(&mut banks_client, &pda::batch_breaker(&qr_batch).0).await).unwrap();
+    assert_eq!((breaker.bucket_claims, breaker.epoch), (1, state.breaker_epoch));
+}
+
+#[tokio::test]
+async fn usv_token_guardian_pause() {
+    let (mut banks_client, authority) = start().await;
+    let guardians = [Keypair::new(), Keypair::new(), Keypair::new()];
+    send(
+        &mut banks_client,
+        &[
+            token::initialize(&authority.pubkey()),
+            token::set_guardians(&authority.pubkey(), guardians.iter().map(|g| g.pubkey()).collect(), 2),
+        ],
+        &[&authority],
+    )
+    .await;
+
+    // Outsiders cannot vote, and one vote is not enough
+    let outsider = Keypair::new();
+    let blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[token::guardian_pause(&outsider.pubkey())],
+        Some(&authority.pubkey()),
+        &[&authority, &outsider],
+        blockhash,
+    );
+    assert!(banks_client.process_transaction(tx).await.is_err());
+
+    send(&mut banks_client, &[token::guardian_pause(&guardians[0].pubkey())], &[&authority, &guardians[0]]).await;
+    let state: USVState = accounts::decode(&account_data(&mut banks_client, &pda::usv_state().0).await).unwrap();
+    assert!(!state.is_paused);
+    assert_eq!(state.guardians.pause_votes, 0b001);
+
+    send(&mut banks_client, &[token::guardian_pause(&guardians[2].pubkey())], &[&authority, &guardians[2]]).await;
+    let state: USVState = accounts::decode(&account_data(&mut banks_client, &pda::usv_state().0).await).unwrap();
+    assert!(state.is_paused);
+    assert_eq!(state.guardians.pause_votes, 0);
+}
+
+#[tokio::test]
+async fn usv_trading_guardian_pause() {
+    let (mut banks_client, authority) = start().await;
+    initialize_trading(&mut banks_client, &authority, &Pubkey::new_unique()).await;
+    let guardians = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let guardian_keys = guardians.iter().map(|g| g.pubkey()).collect();
+    send(&mut banks_client, &[trading::set_guardians(&authority.pubkey(), guardian_keys, 2)], &[&authority]).await;
+
+    let outsider = Keypair::new();
+    assert!(fails(&mut banks_client, &[trading::guardian_pause(&outsider.pubkey())], &[&authority, &outsider]).await);
+    send(&mut banks_client, &[trading::guardian_pause(&guardians[1].pubkey())], &[&authority, &guardians[1]]).await;
+    let state: TradingState =
+        accounts::decode(&account_data(&mut banks_client, &pda::trading_state().0).await).unwrap();
+    assert!(state.is_active);
+    assert_eq!(state.guardians.pause_votes, 0b010);
+
+    send(&mut banks_client, &[trading::guardian_pause(&guardians[0].pubkey())], &[&authority, &guardians[0]]).await;
+    let state: TradingState =
+        accounts::decode(&account_data(&mut banks_client, &pda::trading_state().0).await).unwrap();
+    assert!(!state.is_active);
+    assert_eq!(state.guardians.pause_votes, 0);
+
+    // Only the authority can resume trading
+    send(&mut banks_client, &[trading::toggle_trading(&authority.pubkey(), true)], &[&authority]).await;
+    let state: TradingState =
+        accounts::decode(&account_data(&mut banks_client, &pda::trading_state().0).await).unwrap();
+    assert!(state.is_active);
+}
+
+#[tokio::test]
+async fn usv_token_append_and_seal() {
+    let (mut banks_client, authority) = start().await;
+    send(&mut banks_client, &[token::initialize(&authority.pubkey())], &[&authority]).await;
+
+    let qr_batch = pda::qr_batch(&authority.pubkey(), 0).0;
+    send(
+        &mut banks_client,
+        &[
+            token::create_batch(&authority.pubkey(), 0, None, "large run".into()),
+            token::append_qr_codes(&authority.pubkey(), &qr_batch, 50),
+            token::append_qr_codes(&authority.pubkey(), &qr_batch, 50),
+        ],
+        &[&authority],
+    )
+    .await;
+    send(&mut banks_client, &[token::seal_batch(&authority.pubkey(), &qr_batch)], &[&authority]).await;
+
+    let batch = accounts::decode_qr_batch(&account_data(&mut banks_client, &qr_batch).await).unwrap();
+    assert!(batch.header.is_sealed());
+    assert_eq!(batch.codes.len(), 100);
+    assert!(batch.header.partner_id().is_none());
+    assert_ne!(batch.codes[0], batch.codes[50]);
+}
+
+#[tokio::test]
+async fn usv_trading_price_update() {
+    let (mut banks_client, authority) = start().await;
+    let usv_mint = pda::mint().0;
+    send(
+        &mut banks_client,
+        &[
+            token::initialize(&authority.pubkey()),
+            trading::initialize_trading(&authority.pubkey(), &usv_mint, &authority.pubkey(), oracle::SOL_USD_FEED_ID),
+        ],
+        &[&authority],
+    )
+    .await;
+
+    send(&mut banks_client, &[trading::update_fixed_price(&authority.pubkey(), 25)], &[&authority]).await;
+
+    let state: TradingState = accounts::decode(&account_data(&mut banks_client, &pda::trading_state().0).await).unwrap();
+    assert_eq!(state.usv_mint, usv_mint);
+    assert_eq!(state.fixed_price_cents, 25);
+}
+
+#[tokio::test]
+async fn usv_trading_buy_at_oracle_price() {
+    let mut context = program_test().start_with_context().await;
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let (fresh, stale, uncertain) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    context.set_account(&fresh, &mock_price_update(150_00000000, 10_000000, now));
+    context.set_account(&stale, &mock_price_update(150_00000000, 10_000000, now - 120));
+    context.set_account(&uncertain, &mock_price_update(150_00000000, 10_00000000, now));
+
+    let mut banks_client = context.banks_client;
+    let authority = context.payer;
+    let usv_mint = pda::mint().0;
+    let (buyer, treasury) = (Keypair::new(), Pubkey::new_unique());
+    initialize_trading(&mut banks_client, &authority, &treasury).await;
+    send(
+        &mut banks_client,
+        &[system_instruction::transfer(&authority.pubkey(), &buyer.pubkey(), 5_000_000_000)],
+        &[&authority],
+    )
+    .await;
+
+    // Tokens come out of the program-owned inventory, so the buyer signs alone
+    let buyer_tokens = Keypair::new();
+    let buy = |price_update: &Pubkey, min_tokens_out: u64, deadline: i64, treasury: &Pubkey| {
+        trading::buy_tokens(
+            &buyer.pubkey(),
+            &buyer_tokens.pubkey(),
+            treasury,
+            &usv_mint,
+            price_update,
+            1_000_000_000,
+            min_tokens_out,
+            deadline,
+            None,
+            false,
+        )
+    };
+
+    // Stale or uncertain prices, an expired deadline, too little output and
+    // proceeds sent anywhere but the treasury all fail
+    for ix in [
+        buy(&stale, 0, now + 60, &treasury),
+        buy(&uncertain, 0, now + 60, &treasury),
+        buy(&fresh, 0, now - 1, &treasury),
+        buy(&fresh, 750_000_001, now + 60, &treasury),
+        buy(&fresh, 0, now + 60, &buyer.pubkey()),
+    ] {
+        assert!(fails(&mut banks_client, &[ix], &[&buyer, &buyer_tokens]).await);
+    }
+    send(&mut banks_client, &[buy(&fresh, 750_000_000, now + 60, &treasury)], &[&buyer, &buyer_tokens]).await;
+    assert_eq!(banks_client.get_balance(treasury).await.unwrap(), 1_000_000_000);
+
+    // 1 SOL at $150 buys $150 of tokens at 20 cents each
+    let balance = banks_client
+        .get_packed_account_data::<spl_token::state::Account>(buyer_tokens.pubkey())
+        .await
+        .unwrap()
+        .amount;
+    assert_eq!(balance, 750_000_000);
+}
+
+#[tokio::test]
+async fn usv_trading_bonding_curve() {
+    let mut context = program_test().start_with_context().await;
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let price_update = Pubkey::new_unique();
+    context.set_account(&price_update, &mock_price_update(150_00000000, 10_000000, now));
+
+    let mut banks_client = context.banks_client;
+    let authority = context.payer;
+    let usv_mint = pda::mint().0;
+    let (buyer, buyer_tokens) = (Keypair::new(), Keypair::new());
+    initialize_trading(&mut banks_client, &authority, &authority.pubkey()).await;
+    send(
+        &mut banks_client,
+        &[system_instruction::transfer(&authority.pubkey(), &buyer.pubkey(), 5_000_000_000)],
+        &[&authority],
+    )
+    .await;
+    let buy = |sol_amount: u64| {
+        trading::buy_tokens(
+            &buyer.pubkey(),
+            &buyer_tokens.pubkey(),
+            &authority.pubkey(),
+            &usv_mint,
+            &price_update,
+            sol_amount,
+            0,
+            now + 60,
+            None,
+            false,
+        )
+    };
+    let set_curve = |curve: Option<BondingCurve>| trading::set_bonding_curve(&authority.pubkey(), curve);
+    let claim_vault = token::authority_token_account(&authority.pubkey());
+    let set_bid = |bid_cents: u64| trading::set_redemption_config(&authority.pubkey(), &claim_vault, bid_cents, 0, 0);
+
+    // From 10 cents, rising a tenth of a cent per token, for at most 500 tokens
+    let curve = BondingCurve {
+        kind: CurveKind::Linear { slope_micros: 1_000 },
+        start_price_micros: 100_000,
+        supply_cap: 500_000_000,
+    };
+    send(&mut banks_client, &[set_curve(Some(curve))], &[&authority]).await;
+
+    // The bid may not exceed the curve's 10 cent start price, even below the fixed price
+    assert!(fails(&mut banks_client, &[set_bid(15)], &[&authority]).await);
+
+    // $150 buys about 456.8 tokens as the price climbs from 10 to 55.7 cents
+    send(&mut banks_client, &[buy(1_000_000_000)], &[&buyer, &buyer_tokens]).await;
+    let bought = curve.tokens_for_value(0, 150_000_000_000_000).unwrap();
+    assert_eq!(bought / 100_000, 4_567);
+    assert_eq!(token_balance(&mut banks_client, buyer_tokens.pubkey()).await, bought);
+
+    // Another SOL would pass the 500 token cap; a tenth of one does not
+    assert!(fails(&mut banks_client, &[buy(1_000_000_000)], &[&buyer, &buyer_tokens]).await);
+    send(&mut banks_client, &[buy(100_000_000)], &[&buyer, &buyer_tokens]).await;
+    let state: TradingState =
+        accounts::decode(&account_data(&mut banks_client, &pda::trading_state().0).await).unwrap();
+    assert_eq!(state.curve_sold, token_balance(&mut banks_client, buyer_tokens.pubkey()).await);
+    assert!(state.curve_sold > bought && state.curve_sold < 500_000_000);
+
+    // A curve may not start below the bid; clearing it returns to 20 cents a token
+    send(&mut banks_client, &[set_bid(10)], &[&authority]).await;
+    let cheap = BondingCurve { start_price_micros: 50_000, ..curve };
+    assert!(fails(&mut banks_client, &[set_curve(Some(cheap))], &[&authority]).await);
+    let before = token_balance(&mut banks_client, buyer_tokens.pubkey()).await;
+    send(&mut banks_client, &[set_curve(None), buy(1_000_000_000)], &[&authority, &buyer, &buyer_tokens]).await;
+    assert_eq!(token_balance(&mut banks_client, buyer_tokens.pubkey()).await - before, 750_000_000);
+}
+
+#[tokio::test]
+async fn usv_trading_sale_rounds() {
+    let mut context = program_test().start_with_context().await;
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let price_update = Pubkey::new_unique();
+    context.set_account(&price_update, &mock_price_update(150_00000000, 10_000000, now));
+
+    let mut banks_client = context.banks_client;
+    let authority = context.payer;
+    let usv_mint = pda::mint().0;
+    let treasury = Pubkey::new_unique();
+    let (alice, bob, carol) = (Keypair::new(), Keypair::new(), Keypair::new());
+    let (alice_tokens, bob_tokens, carol_tokens) = (Keypair::new(), Keypair::new(), Keypair::new());
+    initialize_trading(&mut banks_client, &authority, &treasury).await;
+    for buyer in [&alice, &bob, &carol] {
+        send(
+            &mut banks_client,
+            &[system_instruction::transfer(&authority.pubkey(), &buyer.pubkey(), 2_000_000_000)],
+            &[&authority],
+        )
+        .await;
+    }
+
+    // Round 0 sells 1,000 tokens at 10 cents, at most 600 each, to Alice and Carol;
+    // round 1 sells 10,000 at 15 cents to anyone. Rounds must end after they start
+    let leaves = [allowlist::leaf(&alice.pubkey()), allowlist::leaf(&carol.pubkey())];
+    let add_round = |index: u32, start: i64, end: i64, cents: u64, cap: u64, per_wallet: u64, root| {
+        trading::add_sale_round(&authority.pubkey(), index, start, end, cents, cap, per_wallet, root, false)
+    };
+    assert!(
+        fails(&mut banks_client, &[add_round(0, now, now - 1, 10, 1_000_000_000, 0, None)], &[&authority]).await
+    );
+    send(
+        &mut banks_client,
+        &[
+            add_round(0, now - 10, now + 1_000, 10, 1_000_000_000, 600_000_000, Some(allowlist::root(&leaves))),
+            add_round(1, now - 10, now + 1_000, 15, 10_000_000_000, 0, None),
+        ],
+        &[&authority],
+    )
+    .await;
+
+    let buy = |buyer: &Keypair, buyer_tokens: &Keypair, round: u32, sol_amount: u64, proof: Vec<[u8; 32]>| {
+        trading::buy_in_round(
+            &buyer.pubkey(),
+            &buyer_tokens.pubkey(),
+            &treasury,
+            &usv_mint,
+            &price_update,
+            round,
+            sol_amount,
+            0,
+            now + 60,
+            proof,
+            false,
+        )
+    };
+
+    // Bob is not on the allowlist, with or without someone else's proof
+    for proof in [vec![], allowlist::proof(&leaves, 0)] {
+        assert!(fails(&mut banks_client, &[buy(&bob, &bob_tokens, 0, 100_000_000, proof)], &[&bob, &bob_tokens]).await);
+    }
+
+    // Nor can he buy at the fixed price while a round is live, or by leaving the round out
+    let fixed_buy = |current_round: Option<u32>| {
+        trading::buy_tokens(
+            &bob.pubkey(),
+            &bob_tokens.pubkey(),
+            &treasury,
+            &usv_mint,
+            &price_update,
+            100_000_000,
+            0,
+            now + 60,
+            current_round,
+            false,
+        )
+    };
+    for current_round in [Some(0), None] {
+        assert!(fails(&mut banks_client, &[fixed_buy(current_round)], &[&bob, &bob_tokens]).await);
+    }
+
+    // 1 SOL would buy 1,500 tokens; Alice gets her 600 and pays 0.4 SOL for them
+    let alice_buy = buy(&alice, &alice_tokens, 0, 1_000_000_000, allowlist::proof(&leaves, 0));
+    send(&mut banks_client, std::slice::from_ref(&alice_buy), &[&alice, &alice_tokens]).await;
+    assert_eq!(token_balance(&mut banks_client, alice_tokens.pubkey()).await, 600_000_000);
+    assert_eq!(banks_client.get_balance(treasury).await.unwrap(), 400_000_000);
+    assert!(fails(&mut banks_client, &[alice_buy], &[&alice, &alice_tokens]).await);
+
+    // Carol buys the last 400 for $40 and the sale moves on to round 1
+    send(
+        &mut banks_client,
+        &[buy(&carol, &carol_tokens, 0, 1_000_000_000, allowlist::proof(&leaves, 1))],
+        &[&carol, &carol_tokens],
+    )
+    .await;
+    assert_eq!(token_balance(&mut banks_client, carol_tokens.pubkey()).await, 400_000_000);
+    assert_eq!(banks_client.get_balance(treasury).await.unwrap(), 666_666_667);
+    let state: TradingState =
+        accounts::decode(&account_data(&mut banks_client, &pda::trading_state().0).await).unwrap();
+    assert_eq!(state.current_round, 1);
+
+    // Anyone buys in round 1, which cannot be closed early; round 0 can no longer change
+    send(&mut banks_client, &[buy(&bob, &bob_tokens, 1, 300_000_000, vec![])], &[&bob, &bob_tokens]).await;
+    assert_eq!(token_balance(&mut banks_client, bob_tokens.pubkey()).await, 300_000_000);
+    assert!(fails(&mut banks_client, &[trading::advance_sale_round(1)], &[&authority]).await);
+    let update_round = |index: u32, start: i64, end: i64| {
+        trading::update_sale_round(&authority.pubkey(), index, start, end, 15, 10_000_000_000, 0, None, false)
+    };
+    assert!(fails(&mut banks_client, &[update_round(0, now - 10, now + 1_000)], &[&authority]).await);
+
+    // Once round 1 has ended it takes no purchases and anyone can close it
+    send(&mut banks_client, &[update_round(1, now - 10, now - 1)], &[&authority]).await;
+    assert!(fails(&mut banks_client, &[buy(&bob, &bob_tokens, 1, 300_000_000, vec![])], &[&bob, &bob_tokens]).await);
+
+    // Fixed-price sales resume as soon as the round ends; 0.1 SOL buys 75 tokens at 20 cents
+    send(&mut banks_client, &[fixed_buy(Some(1))], &[&bob, &bob_tokens]).await;
+    send(&mut banks_client, &[trading::advance_sale_round(1)], &[&bob]).await;
+    let state: TradingState =
+        accounts::decode(&account_data(&mut banks_client, &pda::trading_state().0).await).unwrap();
+    assert_eq!((state.current_round, state.round_count), (2, 2));
+    assert_eq!(trading::current_round(&state), None);
+    send(&mut banks_client, &[fixed_buy(None)], &[&bob, &bob_tokens]).await;
+    assert_eq!(token_balance(&mut banks_client, bob_tokens.pubkey()).await, 450_000_000);
+    let round: SaleRound = accounts::decode(&account_data(&mut banks_client, &pda::sale_round(1).0).await).unwrap();
+    assert_eq!(round.sold, 300_000_000);
+}
+
+// Move the cluster clock to `unix_timestamp` with a fresh SOL/USD price, and
+// wait for a new blockhash so repeated transactions are not deduplicated
+async fn warp_to(context: &mut ProgramTestContext, price_update: &Pubkey, unix_timestamp: i64) {
+    context.last_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    context.get_new_latest_blockhash().await.unwrap();
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+    context.set_account(price_update, &mock_price_update(150_00000000, 10_000000, unix_timestamp));
+}
+
+#[tokio::test]
+async fn usv_trading_vesting() {
+    let mut context = program_test().start_with_context().await;
+    let start = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let price_update = Pubkey::new_unique();
+    context.set_account(&price_update, &mock_price_update(150_00000000, 10_000000, start));
+
+    let authority = context.payer.insecure_clone();
+    let usv_mint = pda::mint().0;
+    let (buyer, buyer_tokens) = (Keypair::new(), Keypair::new());
+    let signers = [&buyer, &buyer_tokens];
+    initialize_trading(&mut context.banks_client, &authority, &authority.pubkey()).await;
+    send(
+        &mut context.banks_client,
+        &[system_instruction::transfer(&authority.pubkey(), &buyer.pubkey(), 5_000_000_000)],
+        &[&authority],
+    )
+    .await;
+
+    // Purchases of more than 1,000 tokens vest over 1,000 seconds after a 100 second cliff
+    let set_vesting =
+        |cliff_secs: i64| trading::set_vesting_config(&authority.pubkey(), 1_000_000_000, cliff_secs, 1_000);
+    assert!(fails(&mut context.banks_client, &[set_vesting(1_001)], &[&authority]).await);
+    send(&mut context.banks_client, &[set_vesting(100)], &[&authority]).await;
+
+    let buy = |sol_amount: u64, vest: bool| {
+        trading::buy_tokens(
+            &buyer.pubkey(),
+            &buyer_tokens.pubkey(),
+            &authority.pubkey(),
+            &usv_mint,
+            &price_update,
+            sol_amount,
+            0,
+            i64::MAX,
+            None,
+            vest,
+        )
+    };
+    let release = trading::release_vested(&buyer.pubkey(), &buyer_tokens.pubkey(), &usv_mint);
+    let escrow_address = pda::vesting_escrow(&buyer.pubkey()).0;
+
+    // 750 tokens go straight to the buyer; 1,500 need the escrow and are locked in it
+    send(&mut context.banks_client, &[buy(1_000_000_000, false)], &signers).await;
+    assert!(fails(&mut context.banks_client, &[buy(2_000_000_000, false)], &signers).await);
+    send(&mut context.banks_client, &[buy(2_000_000_000, true)], &signers).await;
+    assert_eq!(token_balance(&mut context.banks_client, buyer_tokens.pubkey()).await, 750_000_000);
+    assert_eq!(token_balance(&mut context.banks_client, pda::vesting_vault().0).await, 1_500_000_000);
+    let escrow: VestingEscrow =
+        accounts::decode(&account_data(&mut context.banks_client, &escrow_address).await).unwrap();
+    let schedule = escrow.schedule;
+    assert_eq!(schedule.total(), 1_500_000_000);
+    assert_eq!((schedule.tranches[0].start_time, schedule.end_time()), (start, start + 1_000));
+
+    // Nothing unlocks before the cliff; halfway through, half has
+    assert!(fails(&mut context.banks_client, std::slice::from_ref(&release), &signers).await);
+    warp_to(&mut context, &price_update, start + 99).await;
+    assert!(fails(&mut context.banks_client, std::slice::from_ref(&release), &signers).await);
+    warp_to(&mut context, &price_update, start + 500).await;
+    send(&mut context.banks_client, std::slice::from_ref(&release), &signers).await;
+    assert_eq!(token_balance(&mut context.banks_client, buyer_tokens.pubkey()).await, 1_500_000_000);
+
+    // Every purchase in a vesting round vests; the 150 tokens get their own
+    // tranche and the 750 still locked keep their unlock time
+    send(
+        &mut context.banks_client,
+        &[trading::add_sale_round(&authority.pubkey(), 0, start, start + 10_000, 10, 10_000_000_000, 0, None, true)],
+        &[&authority],
+    )
+    .await;
+    let buy_in_round = trading::buy_in_round(
+        &buyer.pubkey(),
+        &buyer_tokens.pubkey(),
+        &authority.pubkey(),
+        &usv_mint,
+        &price_update,
+        0,
+        100_000_000,
+        0,
+        i64::MAX,
+        vec![],
+        true,
+    );
+    send(&mut context.banks_client, &[buy_in_round], &signers).await;
+    let escrow: VestingEscrow =
+        accounts::decode(&account_data(&mut context.banks_client, &escrow_address).await).unwrap();
+    let schedule = escrow.schedule;
+    assert_eq!(schedule.total(), 900_000_000);
+    assert_eq!((schedule.tranches[1].start_time, schedule.tranches[1].amount), (start + 500, 150_000_000));
+    assert!(fails(&mut context.banks_client, std::slice::from_ref(&release), &signers).await);
+
+    // The first purchase has fully unlocked on its original schedule, the second halfway
+    warp_to(&mut context, &price_update, start + 1_000).await;
+    send(&mut context.banks_client, std::slice::from_ref(&release), &signers).await;
+    assert_eq!(token_balance(&mut context.banks_client, buyer_tokens.pubkey()).await, 2_325_000_000);
+
+    // Once the last tranche ends everything is released and the vault is empty
+    warp_to(&mut context, &price_update, start + 1_500).await;
+    send(&mut context.banks_client, std::slice::from_ref(&release), &signers).await;
+    assert_eq!(token_balance(&mut context.banks_client, buyer_tokens.pubkey()).await, 2_400_000_000);
+    assert_eq!(token_balance(&mut context.banks_client, pda::vesting_vault().0).await, 0);
+}
+
+// A 6-decimal stand-in for USDC with `payer` as mint authority
+async fn create_stablecoin(banks_client: &mut BanksClient, payer: &Keypair) -> Pubkey {
+    let mint = Keypair::new();
+    let rent = banks_client.get_rent().await.unwrap();
+    send(
+        banks_client,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint(&spl_token::ID, &mint.pubkey(), &payer.pubkey(), None, 6).unwrap(),
+        ],
+        &[payer, &mint],
+    )
+    .await;
+    mint.pubkey()
+}
+
+// A token account of `owner` holding `amount` freshly minted by `payer`
+async fn create_token_account(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Pubkey {
+    let account = Keypair::new();
+    let rent = banks_client.get_rent().await.unwrap();
+    send(
+        banks_client,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &account.pubkey(),
+                rent.minimum_balance(spl_token::state::Account::LEN),
+                spl_token::state::Account::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_account3(&spl_token::ID, &account.pubkey(), mint, owner).unwrap(),
+            spl_token::instruction::mint_to(&spl_token::ID, mint, &account.pubkey(), &payer.pubkey(), &[], amount)
+                .unwrap(),
+        ],
+        &[payer, &account],
+    )
+    .await;
+    account.pubkey()
+}
+
+async fn token_balance(banks_client: &mut BanksClient, address: Pubkey) -> u64 {
+    banks_client
+        .get_packed_account_data::<spl_token::state::Account>(address)
+        .await
+        .unwrap()
+        .amount
+}
+
+#[tokio::test]
+async fn usv_trading_buy_with_spl() {
+    let (mut banks_client, authority) = start().await;
+    let usv_mint = pda::mint().0;
+    let buyer = Keypair::new();
+    initialize_trading(&mut banks_client, &authority, &authority.pubkey()).await;
+    send(
+        &mut banks_client,
+        &[system_instruction::transfer(&authority.pubkey(), &buyer.pubkey(), 1_000_000_000)],
+        &[&authority],
+    )
+    .await;
+    let usdc = create_stablecoin(&mut banks_client, &authority).await;
+    let other = create_stablecoin(&mut banks_client, &authority).await;
+    let buyer_usdc = create_token_account(&mut banks_client, &authority, &usdc, &buyer.pubkey(), 100_000_000).await;
+    let treasury = create_token_account(&mut banks_client, &authority, &usdc, &authority.pubkey(), 0).await;
+    let other_treasury = create_token_account(&mut banks_client, &authority, &other, &authority.pubkey(), 0).await;
+
+    // A treasury holding another token is rejected
+    let blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[trading::add_payment_mint(&authority.pubkey(), &usdc, &other_treasury, 1_000_000)],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    assert!(banks_client.process_transaction(tx).await.is_err());
+    send(
+        &mut banks_client,
+        &[trading::add_payment_mint(&authority.pubkey(), &usdc, &treasury, 1_000_000)],
+        &[&authority],
+    )
+    .await;
+
+    let buyer_tokens = Keypair::new();
+    let buy = |payment_amount: u64| {
+        trading::buy_tokens_with_spl(
+            &buyer.pubkey(),
+            &buyer_usdc,
+            &buyer_tokens.pubkey(),
+            &usdc,
+            &treasury,
+            &usv_mint,
+            payment_amount,
+            0,
+            i64::MAX,
+            None,
+            false,
+        )
+    };
+
+    // 10 USDC at $1 buys 50 tokens at 20 cents each
+    send(&mut banks_client, &[buy(10_000_000)], &[&buyer, &buyer_tokens]).await;
+    assert_eq!(token_balance(&mut banks_client, buyer_tokens.pubkey()).await, 50_000_000);
+    assert_eq!(token_balance(&mut banks_client, treasury).await, 10_000_000);
+    assert_eq!(token_balance(&mut banks_client, buyer_usdc).await, 90_000_000);
+
+    // Disabled payment mints are refused
+    send(
+        &mut banks_client,
+        &[trading::update_payment_mint(&authority.pubkey(), &usdc, &treasury, 1_000_000, false)],
+        &[&authority],
+    )
+    .await;
+    assert!(fails(&mut banks_client, &[buy(10_000_000)], &[&buyer, &buyer_tokens]).await);
+}
+
+#[tokio::test]
+async fn usv_trading_sell_tokens() {
+    let mut context = program_test().start_with_context().await;
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let price_update = Pubkey::new_unique();
+    context.set_account(&price_update, &mock_price_update(150_00000000, 10_000000, now));
+
+    let mut banks_client = context.banks_client;
+    let authority = context.payer;
+    let usv_mint = pda::mint().0;
+    let claim_vault = token::authority_token_account(&authority.pubkey());
+    let seller = Keypair::new();
+    let seller_tokens = Keypair::new();
+    initialize_trading(&mut banks_client, &authority, &authority.pubkey()).await;
+    send(
+        &mut banks_client,
+        &[
+            system_instruction::transfer(&authority.pubkey(), &seller.pubkey(), 2_000_000_000),
+            system_instruction::transfer(&authority.pubkey(), &pda::sol_reserve().0, 1_000_000_000),
+        ],
+        &[&authority],
+    )
+    .await;
+
+    // 750 tokens bought for 1 SOL are the only ones in circulation
+    send(
+        &mut banks_client,
+        &[trading::buy_tokens(
+            &seller.pubkey(),
+            &seller_tokens.pubkey(),
+            &authority.pubkey(),
+            &usv_mint,
+            &price_update,
+            1_000_000_000,
+            0,
+            now + 60,
+            None,
+            false,
+        )],
+        &[&seller, &seller_tokens],
+    )
+    .await;
+
+    let usdc = create_stablecoin(&mut banks_client, &authority).await;
+    let treasury = create_token_account(&mut banks_client, &authority, &usdc, &authority.pubkey(), 0).await;
+    let seller_usdc = create_token_account(&mut banks_client, &authority, &usdc, &seller.pubkey(), 0).await;
+    send(
+        &mut banks_client,
+        &[
+            trading::add_payment_mint(&authority.pubkey(), &usdc, &treasury, 1_000_000),
+            spl_token::instruction::mint_to(
+                &spl_token::ID,
+                &usdc,
+                &pda::reserve(&usdc).0,
+                &authority.pubkey(),
+                &[],
+                100_000_000,
+            )
+            .unwrap(),
+        ],
+        &[&authority],
+    )
+    .await;
+
+    let sell = |token_amount: u64| {
+        trading::sell_tokens(
+            &seller.pubkey(),
+            &seller_tokens.pubkey(),
+            &usv_mint,
+            &claim_vault,
+            &price_update,
+            token_amount,
+            0,
+            now + 60,
+        )
+    };
+
+    // Redemption is off until a bid is set, and the bid cannot exceed the 20 cent ask
+    assert!(fails(&mut banks_client, &[sell(300_000_000)], &[&seller]).await);
+    assert!(
+        fails(
+            &mut banks_client,
+            &[trading::set_redemption_config(&authority.pubkey(), &claim_vault, 25, 0, 0)],
+            &[&authority]
+        )
+        .await
+    );
+    send(
+        &mut banks_client,
+        &[trading::set_redemption_config(&authority.pubkey(), &claim_vault, 10, 500_000_000, 0)],
+        &[&authority],
+    )
+    .await;
+
+    // 300 tokens at a 10 cent bid are $30, 0.2 SOL at $150
+    let reserve_before = banks_client.get_balance(pda::sol_reserve().0).await.unwrap();
+    send(&mut banks_client, &[sell(300_000_000)], &[&seller]).await;
+    let reserve_after = banks_client.get_balance(pda::sol_reserve().0).await.unwrap();
+    assert_eq!(reserve_before - reserve_after, 200_000_000);
+    assert_eq!(token_balance(&mut banks_client, seller_tokens.pubkey()).await, 450_000_000);
+
+    // 100 tokens are 10 USDC from the USDC reserve
+    send(
+        &mut banks_client,
+        &[trading::sell_tokens_for_spl(
+            &seller.pubkey(),
+            &seller_tokens.pubkey(),
+            &seller_usdc,
+            &usdc,
+            &usv_mint,
+            &claim_vault,
+            100_000_000,
+            10_000_000,
+            now + 60,
+        )],
+        &[&seller],
+    )
+    .await;
+    assert_eq!(token_balance(&mut banks_client, seller_usdc).await, 10_000_000);
+    assert_eq!(token_balance(&mut banks_client, pda::reserve(&usdc).0).await, 90_000_000);
+
+    // 400 of the 500 token daily cap are used
+    assert!(fails(&mut banks_client, &[sell(200_000_000)], &[&seller]).await);
+
+    // At a 500% reserve ratio, 0.77 SOL cannot back the $30 of tokens still out
+    send(
+        &mut banks_client,
+        &[trading::set_redemption_config(&authority.pubkey(), &claim_vault, 10, 500_000_000, 50_000)],
+        &[&authority],
+    )
+    .await;
+    assert!(fails(&mut banks_client, &[sell(50_000_000)], &[&seller]).await);
+
+    // 20,000 tokens waiting in the claim vault are not in circulation, so at 100% the
+    // reserve only has to back the seller's 350
+    send(
+        &mut banks_client,
+        &[
+            trading::set_redemption_config(&authority.pubkey(), &claim_vault, 10, 500_000_000, 10_000),
+            trading::withdraw_inventory(&authority.pubkey(), &claim_vault, 20_000_000_000),
+        ],
+        &[&authority],
+    )
+    .await;
+    send(&mut banks_client, &[sell(50_000_000)], &[&seller]).await;
+
+    let state: TradingState =
+        accounts::decode(&account_data(&mut banks_client, &pda::trading_state().0).await).unwrap();
+    assert_eq!(state.claim_vault, claim_vault);
+    assert_eq!(state.redeemed_in_window, 450_000_000);
+    assert_eq!(state.total_redeemed, 450_000_000);
+}
+
+#[tokio::test]
+async fn usv_trading_pool() {
+    let (mut banks_client, authority) = start().await;
+    let now = banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let usv_mint = pda::mint().0;
+    let authority_tokens = token::authority_token_account(&authority.pubkey());
+    let lp_tokens = get_associated_token_address(&authority.pubkey(), &pda::lp_mint().0);
+    initialize_trading(&mut banks_client, &authority, &authority.pubkey()).await;
+
+    // Fees above 10% are rejected
+    assert!(
+        fails(&mut banks_client, &[trading::initialize_pool(&authority.pubkey(), &usv_mint, 1_001, 500)], &[&authority])
+            .await
+    );
+    send(
+        &mut banks_client,
+        &[
+            trading::initialize_pool(&authority.pubkey(), &usv_mint, 30, 500),
+            trading::withdraw_inventory(&authority.pubkey(), &authority_tokens, 20_000_000_000),
+        ],
+        &[&authority],
+    )
+    .await;
+
+    // 100 SOL and 10,000 USV mint their geometric mean, less the locked minimum
+    send(
+        &mut banks_client,
+        &[trading::add_liquidity(&authority.pubkey(), &authority_tokens, 100_000_000_000, 10_000_000_000, 0)],
+        &[&authority],
+    )
+    .await;
+    assert_eq!(token_balance(&mut banks_client, lp_tokens).await, 31_622_775_601);
+    assert_eq!(token_balance(&mut banks_client, authority_tokens).await, 10_000_000_000);
+
+    // 1 SOL buys 98.715803 USV after the 0.3% fee
+    let swap = |amount_in: u64, sol_to_usv: bool, deadline: i64| {
+        trading::swap(&authority.pubkey(), &authority_tokens, amount_in, 0, sol_to_usv, deadline)
+    };
+    send(&mut banks_client, &[swap(1_000_000_000, true, now + 60)], &[&authority]).await;
+    assert_eq!(token_balance(&mut banks_client, authority_tokens).await, 10_098_715_803);
+
+    // Selling it straight back returns less than the SOL paid
+    send(&mut banks_client, &[swap(98_715_803, false, now + 60)], &[&authority]).await;
+    let pool: Pool = accounts::decode(&account_data(&mut banks_client, &pda::pool().0).await).unwrap();
+    assert!(pool.reserve_sol > 100_000_000_000);
+    assert_eq!(pool.reserve_usv, 10_000_000_000);
+
+    // 10 SOL would move the price by about 9%, over the 5% limit; expired swaps are rejected
+    assert!(fails(&mut banks_client, &[swap(10_000_000_000, true, now + 60)], &[&authority]).await);
+    assert!(fails(&mut banks_client, &[swap(1_000_000, true, now - 1)], &[&authority]).await);
+    send(&mut banks_client, &[trading::set_pool_config(&authority.pubkey(), 30, 1_000)], &[&authority]).await;
+    send(&mut banks_client, &[swap(10_000_000_000, true, now + 60)], &[&authority]).await;
+
+    // Pausing trading stops deposits but not withdrawals
+    send(&mut banks_client, &[trading::toggle_trading(&authority.pubkey(), false)], &[&authority]).await;
+    let deposit = trading::add_liquidity(&authority.pubkey(), &authority_tokens, 1_000_000_000, 1_000_000_000, 0);
+    assert!(fails(&mut banks_client, &[deposit], &[&authority]).await);
+
+    // Burning every LP token leaves only the locked minimum's share in the pool
+    send(
+        &mut banks_client,
+        &[trading::remove_liquidity(&authority.pubkey(), &authority_tokens, 31_622_775_601, 0, 0)],
+        &[&authority],
+    )
+    .await;
+    let pool: Pool = accounts::decode(&account_data(&mut banks_client, &pda::pool().0).await).unwrap();
+    assert_eq!(pool.lp_supply, 1_000);
+    assert!(pool.reserve_sol > 0 && pool.reserve_usv > 0);
+    assert_eq!(token_balance(&mut banks_client, lp_tokens).await, 0);
+    assert_eq!(token_balance(&mut banks_client, pda::pool_usv().0).await, pool.reserve_usv);
+}
+
+#[tokio::test]
+async fn nft_auth_register_and_mint() {
+    let (mut banks_client, authority) = start().await;
+    let qr_code = "PRODUCT_0001";
+    send(
+        &mut banks_client,
+        &[nft_auth::initialize(&authority.pubkey()), nft_auth::register_qr_code(&authority.pubkey(), qr_code)],
+        &[&authority],
+    )
+    .await;
+    send(&mut banks_client, &[nft_auth::mint_nft_piece(&authority.pubkey(), qr_code)], &[&authority]).await;
+
+    let qr_data: QRData = accounts::decode(&account_data(&mut banks_client, &pda::qr_data(qr_code).0).await).unwrap();
+    assert!(qr_data.is_claimed);
+    assert_eq!(qr_data.piece_number, 1);
+    assert_eq!(qr_data.claimed_by, authority.pubkey());
+
+    let state: ProgramState = accounts::decode(&account_data(&mut banks_client, &pda::program_state().0).await).unwrap();
+    assert_eq!(state.minted_pieces, 1);
+}
+
+#[tokio::test]
+async fn nft_auth_guardian_pause() {
+    let (mut banks_client, authority) = start().await;
+    let guardian = Keypair::new();
+    send(
+        &mut banks_client,
+        &[
+            nft_auth::initialize(&authority.pubkey()),
+            nft_auth::register_qr_code(&authority.pubkey(), "PRODUCT_0001"),
+            nft_auth::set_guardians(&authority.pubkey(), vec![guardian.pubkey(), Pubkey::new_unique()], 1),
+        ],
+        &[&authority],
+    )
+    .await;
+
+    // One vote reaches the threshold and stops minting until the authority resumes it
+    send(&mut banks_client, &[nft_auth::guardian_pause(&guardian.pubkey())], &[&authority, &guardian]).await;
+    let guard: MintGuard = accounts::decode(&account_data(&mut banks_client, &pda::mint_guard().0).await).unwrap();
+    assert!(guard.is_paused);
+    let mint = nft_auth::mint_nft_piece(&authority.pubkey(), "PRODUCT_0001");
+    assert!(fails(&mut banks_client, std::slice::from_ref(&mint), &[&authority]).await);
+
+    send(&mut banks_client, &[nft_auth::set_pause_state(&authority.pubkey(), false), mint], &[&authority]).await;
+    let state: ProgramState = accounts::decode(&account_data(&mut banks_client, &pda::program_state().0).await).unwrap();
+    assert_eq!(state.minted_pieces, 1);
+}
+
+// Banks clients don't return inner instructions, so event self-CPIs are
+// checked against instruction data in the format `emit_cpi!` produces
+#[test]
+fn decode_cpi_events() {
+    let claimer = Pubkey::new_unique();
+    let claimed = usv_token::TokensClaimed {
+        qr_code: [7; usv_token::QR_CODE_LEN],
+        claimer,
+        amount: 1_000_000,
+        user_email: Some("user@example.com".into()),
+        timestamp: 1_700_000_000,
+    };
+    let cpi_data = [&EVENT_IX_TAG_LE[..], &claimed.data()].concat();
+    let token_authority = [pda::event_authority(&usv_token::ID).0];
+
+    let event = events::decode_cpi_event(&usv_token::ID, &token_authority, &cpi_data).unwrap();
+    assert!(matches!(
+        event,
+        Some(UsvEvent::TokensClaimed(claimed)) if claimed.claimer == claimer && claimed.qr_code == [7; 16]
+    ));
+
+    // Same bytes from another program, or without the event authority, are not trusted
+    let spoofer = Pubkey::new_unique();
+    let spoofer_authority = [pda::event_authority(&spoofer).0];
+    assert!(events::decode_cpi_event(&spoofer, &spoofer_authority, &cpi_data).unwrap().is_none());
+    assert!(events::decode_cpi_event(&usv_token::ID, &[claimer], &cpi_data).unwrap().is_none());
+    assert!(events::decode_cpi_event(&usv_token::ID, &token_authority, &claimed.data()).unwrap().is_none());
+}
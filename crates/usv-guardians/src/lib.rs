@@ -0,0 +1,83 @@
+// crates/usv-guardians/src/lib.rs - M-of-N guardian pause votes shared by the programs
+//
+// Each program embeds a GuardianSet in the state account holding its pause flag.
+// Guardians can only vote to pause: the flag is set once `threshold` distinct
+// guardians vote within GUARDIAN_VOTE_WINDOW, and only the program's authority
+// can clear it again.
+
+use anchor_lang::prelude::*;
+
+// Guardians that can vote to pause; votes are a bitmask over the guardian list
+pub const MAX_GUARDIANS: usize = 8;
+
+// Guardian pause votes expire if the threshold is not reached within this window
+pub const GUARDIAN_VOTE_WINDOW: i64 = 60 * 60;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct GuardianSet {
+    #[max_len(MAX_GUARDIANS)]
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub pause_votes: u8, // Bit i is set once members[i] has voted
+    pub pause_votes_started_at: i64,
+}
+
+impl GuardianSet {
+    // Replace the guardians; `threshold` of them must vote to pause. An empty
+    // list with threshold 0 disables guardian pauses
+    pub fn replace(&mut self, members: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        require!(members.len() <= MAX_GUARDIANS, GuardianError::InvalidGuardians);
+        require!(
+            (threshold as usize) <= members.len() && (threshold > 0 || members.is_empty()),
+            GuardianError::InvalidGuardians
+        );
+        for (i, member) in members.iter().enumerate() {
+            require!(!members[..i].contains(member), GuardianError::InvalidGuardians);
+        }
+
+        self.members = members;
+        self.threshold = threshold;
+        self.clear_votes();
+        Ok(())
+    }
+
+    // Record `guardian`'s vote to pause at `now`, returning the votes counted so
+    // far and whether they reached the threshold. Reaching it clears the votes
+    pub fn vote(&mut self, guardian: Pubkey, now: i64) -> Result<(u8, bool)> {
+        let index = self
+            .members
+            .iter()
+            .position(|key| *key == guardian)
+            .ok_or(GuardianError::NotGuardian)?;
+
+        if self.pause_votes != 0 && now - self.pause_votes_started_at >= GUARDIAN_VOTE_WINDOW {
+            self.clear_votes();
+        }
+        if self.pause_votes == 0 {
+            self.pause_votes_started_at = now;
+        }
+        self.pause_votes |= 1 << index;
+
+        let votes = self.pause_votes.count_ones() as u8;
+        let reached = votes >= self.threshold;
+        if reached {
+            self.clear_votes();
+        }
+        Ok((votes, reached))
+    }
+
+    // Drop any votes in progress, e.g. once the authority unpauses
+    pub fn clear_votes(&mut self) {
+        self.pause_votes = 0;
+        self.pause_votes_started_at = 0;
+    }
+}
+
+// Offset clear of the programs' own error codes, which start at 6000
+#[error_code(offset = 7000)]
+pub enum GuardianError {
+    #[msg("Guardians must be distinct, at most MAX_GUARDIANS, with a threshold between 1 and their count")]
+    InvalidGuardians,
+    #[msg("Signer is not a guardian")]
+    NotGuardian,
+}
@@ -0,0 +1,44 @@
+// crates/usv-guardians/tests/guardians.rs - Guardian set validation and vote counting
+
+use anchor_lang::prelude::Pubkey;
+use usv_guardians::{GuardianError, GuardianSet, GUARDIAN_VOTE_WINDOW, MAX_GUARDIANS};
+
+fn set_of(count: usize, threshold: u8) -> (GuardianSet, Vec<Pubkey>) {
+    let members: Vec<Pubkey> = (0..count).map(|_| Pubkey::new_unique()).collect();
+    let mut set = GuardianSet::default();
+    set.replace(members.clone(), threshold).unwrap();
+    (set, members)
+}
+
+#[test]
+fn rejects_invalid_sets() {
+    let key = Pubkey::new_unique();
+    let too_many: Vec<Pubkey> = (0..=MAX_GUARDIANS).map(|_| Pubkey::new_unique()).collect();
+    for (members, threshold) in [(vec![key], 0), (vec![key], 2), (vec![key, key], 1), (too_many, 1)] {
+        let err = GuardianSet::default().replace(members, threshold).unwrap_err();
+        assert_eq!(err, GuardianError::InvalidGuardians.into());
+    }
+    GuardianSet::default().replace(Vec::new(), 0).unwrap();
+}
+
+#[test]
+fn reaches_the_threshold_once_per_round() {
+    let (mut set, members) = set_of(3, 2);
+    assert_eq!(set.vote(members[0], 100).unwrap(), (1, false));
+    // Voting twice does not count twice
+    assert_eq!(set.vote(members[0], 200).unwrap(), (1, false));
+    assert_eq!(set.vote(members[2], 300).unwrap(), (2, true));
+    assert_eq!((set.pause_votes, set.pause_votes_started_at), (0, 0));
+
+    let err = set.vote(Pubkey::new_unique(), 400).unwrap_err();
+    assert_eq!(err, GuardianError::NotGuardian.into());
+}
+
+#[test]
+fn votes_expire_after_the_window() {
+    let (mut set, members) = set_of(3, 2);
+    set.vote(members[0], 1_000).unwrap();
+    assert_eq!(set.vote(members[1], 1_000 + GUARDIAN_VOTE_WINDOW).unwrap(), (1, false));
+    assert_eq!(set.pause_votes_started_at, 1_000 + GUARDIAN_VOTE_WINDOW);
+    assert_eq!(set.vote(members[2], 1_000 + GUARDIAN_VOTE_WINDOW + 1).unwrap(), (2, true));
+}
@@ -0,0 +1,117 @@
+// crates/usv-indexer/src/follower.rs - Walks each program's signatures forward from its checkpoint
+
+use std::str::FromStr;
+
+use anyhow::{Context as _, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::UiTransactionEncoding;
+use usv_client::events::parse_transaction;
+
+use crate::store::{IndexedTransaction, Store};
+
+// Largest page getSignaturesForAddress returns
+const PAGE_LIMIT: usize = 1_000;
+
+pub struct Follower {
+    rpc: RpcClient,
+    store: Store,
+    programs: Vec<Pubkey>,
+}
+
+impl Follower {
+    pub fn new(rpc: RpcClient, store: Store, programs: Vec<Pubkey>) -> Self {
+        Follower { rpc, store, programs }
+    }
+
+    // One pass over every program; returns how many transactions were indexed
+    pub async fn poll(&self) -> Result<usize> {
+        let mut indexed = 0;
+        for program in &self.programs {
+            let checkpoint = self.store.checkpoint(program).await?;
+            let until = checkpoint
+                .map(|checkpoint| Signature::from_str(&checkpoint.signature))
+                .transpose()?;
+
+            for status in self.new_signatures(program, until).await? {
+                let tx = self.fetch(&status).await?;
+                self.store.record(program, &tx).await?;
+                indexed += 1;
+            }
+        }
+        Ok(indexed)
+    }
+
+    // Every signature after `until`, oldest first
+    async fn new_signatures(
+        &self,
+        program: &Pubkey,
+        until: Option<Signature>,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self
+                .rpc
+                .get_signatures_for_address_with_config(
+                    program,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until,
+                        limit: Some(PAGE_LIMIT),
+                        commitment: Some(CommitmentConfig::confirmed()),
+                    },
+                )
+                .await?;
+            let done = page.len() < PAGE_LIMIT;
+            before = page
+                .last()
+                .map(|status| Signature::from_str(&status.signature))
+                .transpose()?;
+            signatures.extend(page);
+            if done {
+                break;
+            }
+        }
+        signatures.reverse();
+        Ok(signatures)
+    }
+
+    async fn fetch(&self, status: &RpcConfirmedTransactionStatusWithSignature) -> Result<IndexedTransaction> {
+        // Failed transactions emit nothing but still move the checkpoint
+        if status.err.is_some() {
+            return Ok(IndexedTransaction {
+                signature: status.signature.clone(),
+                slot: status.slot,
+                block_time: status.block_time,
+                events: Vec::new(),
+            });
+        }
+
+        let signature = Signature::from_str(&status.signature)?;
+        let tx = self
+            .rpc
+            .get_transaction_with_config(
+                &signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Json),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await?;
+        let events = parse_transaction(&tx).with_context(|| format!("failed to decode events in {}", signature))?;
+
+        Ok(IndexedTransaction {
+            signature: status.signature.clone(),
+            slot: tx.slot,
+            block_time: tx.block_time,
+            events,
+        })
+    }
+}
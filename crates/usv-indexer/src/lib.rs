@@ -0,0 +1,12 @@
+// crates/usv-indexer/src/lib.rs - Follows the USV programs and stores their events in SQL
+//
+// Each program's transactions are walked oldest to newest from the last
+// checkpoint. A transaction's events and the checkpoint move forward in one
+// database transaction, so a restarted indexer resumes exactly where it stopped
+// and replaying a signature is a no-op.
+
+pub mod follower;
+pub mod store;
+
+pub use follower::Follower;
+pub use store::{Checkpoint, Store};
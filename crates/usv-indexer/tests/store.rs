@@ -0,0 +1,69 @@
+// crates/usv-indexer/tests/store.rs - Event rows and resumable checkpoints against SQLite
+
+use anchor_lang::prelude::Pubkey;
+use usv_client::events::{PriceUpdated, TokensClaimed, UsvEvent};
+use usv_client::usv_token;
+use usv_indexer::store::IndexedTransaction;
+use usv_indexer::{Checkpoint, Store};
+
+async fn store() -> Store {
+    let store = Store::connect("sqlite::memory:").await.unwrap();
+    store.migrate().await.unwrap();
+    // Migrations are idempotent
+    store.migrate().await.unwrap();
+    store
+}
+
+fn claim_tx(signature: &str, slot: u64) -> IndexedTransaction {
+    IndexedTransaction {
+        signature: signature.to_string(),
+        slot,
+        block_time: Some(1_700_000_000),
+        events: vec![
+            UsvEvent::TokensClaimed(TokensClaimed {
+                qr_code: [0xab; 16],
+                claimer: Pubkey::new_unique(),
+                amount: 1_000_000,
+                user_email: None,
+                timestamp: 1_700_000_000,
+            }),
+            UsvEvent::PriceUpdated(PriceUpdated {
+                old_price: 10,
+                new_price: 12,
+                timestamp: 1_700_000_000,
+            }),
+        ],
+    }
+}
+
+#[tokio::test]
+async fn records_events_and_advances_the_checkpoint() {
+    let store = store().await;
+    let program = usv_token::ID;
+    assert_eq!(store.checkpoint(&program).await.unwrap(), None);
+
+    store.record(&program, &claim_tx("sig-1", 10)).await.unwrap();
+    store.record(&program, &claim_tx("sig-2", 11)).await.unwrap();
+
+    assert_eq!(store.count("chain_token_claims").await.unwrap(), 2);
+    assert_eq!(store.count("chain_price_updates").await.unwrap(), 2);
+    assert_eq!(
+        store.checkpoint(&program).await.unwrap(),
+        Some(Checkpoint {
+            signature: "sig-2".to_string(),
+            slot: 11,
+        })
+    );
+}
+
+#[tokio::test]
+async fn replaying_a_transaction_is_a_no_op() {
+    let store = store().await;
+    let program = usv_token::ID;
+
+    store.record(&program, &claim_tx("sig-1", 10)).await.unwrap();
+    store.record(&program, &claim_tx("sig-1", 10)).await.unwrap();
+
+    assert_eq!(store.count("chain_token_claims").await.unwrap(), 1);
+    assert_eq!(store.count("chain_price_updates").await.unwrap(), 1);
+}
@@ -0,0 +1,162 @@
+// crates/usv-qr/src/lib.rs - Versioned QR payload format for printed labels and tools
+//
+// Version 1 payloads look like
+//
+//     usv:1/<batch>/<index>/<code>/<check>
+//
+//   batch  base58 address of the QRBatch account holding the code
+//   index  decimal position of the code within the batch
+//   code   the 16-byte code as 32 hex characters
+//   check  CRC-32 (IEEE) of everything before the last `/`, as 8 hex characters
+//
+// The index is carried alongside the code because claims address codes by
+// position. Decoding dispatches on the version number, so later formats can be
+// added next to this one without breaking printed labels.
+
+use std::fmt;
+use std::str::FromStr;
+
+// Codes are defined by the token program, which stores and hashes them
+pub use usv_token::QR_CODE_LEN;
+
+pub const SCHEME: &str = "usv";
+pub const VERSION: u32 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QrPayload {
+    pub batch: [u8; 32],
+    pub index: u32,
+    pub code: [u8; QR_CODE_LEN],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayloadError {
+    InvalidScheme,
+    UnsupportedVersion,
+    Malformed,
+    InvalidBatch,
+    InvalidIndex,
+    InvalidCode,
+    ChecksumMismatch,
+}
+
+impl fmt::Display for PayloadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            PayloadError::InvalidScheme => "not a USV payload",
+            PayloadError::UnsupportedVersion => "unsupported payload version",
+            PayloadError::Malformed => "malformed payload",
+            PayloadError::InvalidBatch => "invalid batch address",
+            PayloadError::InvalidIndex => "invalid code index",
+            PayloadError::InvalidCode => "invalid QR code",
+            PayloadError::ChecksumMismatch => "checksum mismatch",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for PayloadError {}
+
+impl QrPayload {
+    pub fn new(batch: [u8; 32], index: u32, code: [u8; QR_CODE_LEN]) -> Self {
+        QrPayload { batch, index, code }
+    }
+
+    pub fn encode(&self) -> String {
+        let body = format!(
+            "{}:{}/{}/{}/{}",
+            SCHEME,
+            VERSION,
+            bs58::encode(self.batch).into_string(),
+            self.index,
+            to_hex(&self.code),
+        );
+        let check = crc32(body.as_bytes());
+        format!("{}/{:08x}", body, check)
+    }
+
+    pub fn decode(payload: &str) -> Result<Self, PayloadError> {
+        let rest = payload
+            .strip_prefix(SCHEME)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .ok_or(PayloadError::InvalidScheme)?;
+        let (version, _) = rest.split_once('/').ok_or(PayloadError::Malformed)?;
+
+        match version.parse::<u32>() {
+            Ok(1) => decode_v1(payload),
+            Ok(_) => Err(PayloadError::UnsupportedVersion),
+            Err(_) => Err(PayloadError::Malformed),
+        }
+    }
+}
+
+fn decode_v1(payload: &str) -> Result<QrPayload, PayloadError> {
+    // Verify the checksum first so a typo anywhere reports as a typo
+    let (body, check) = payload.rsplit_once('/').ok_or(PayloadError::Malformed)?;
+    if check.len() != 8 || u32::from_str_radix(check, 16) != Ok(crc32(body.as_bytes())) {
+        return Err(PayloadError::ChecksumMismatch);
+    }
+
+    let mut parts = body.split('/').skip(1);
+    let (Some(batch), Some(index), Some(code), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(PayloadError::Malformed);
+    };
+
+    let mut batch_bytes = [0u8; 32];
+    match bs58::decode(batch).onto(&mut batch_bytes) {
+        Ok(32) => {}
+        _ => return Err(PayloadError::InvalidBatch),
+    }
+    if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(PayloadError::InvalidIndex);
+    }
+    let index = index.parse().map_err(|_| PayloadError::InvalidIndex)?;
+    let code = from_hex(code).ok_or(PayloadError::InvalidCode)?;
+
+    Ok(QrPayload::new(batch_bytes, index, code))
+}
+
+impl fmt::Display for QrPayload {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.encode())
+    }
+}
+
+impl FromStr for QrPayload {
+    type Err = PayloadError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        QrPayload::decode(s)
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Option<[u8; QR_CODE_LEN]> {
+    if s.len() != QR_CODE_LEN * 2 || !s.is_ascii() {
+        return None;
+    }
+    let mut out = [0u8; QR_CODE_LEN];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(out)
+}
+
+// Bitwise CRC-32 (IEEE 802.3, reflected, polynomial 0xEDB88320). Payloads are
+// short, so a lookup table isn't worth carrying
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
@@ -0,0 +1,67 @@
+// crates/usv-qr/tests/payload.rs - Round trips and typo rejection for QR payloads
+
+use usv_qr::{crc32, PayloadError, QrPayload, QR_CODE_LEN};
+
+fn sample() -> QrPayload {
+    let mut code = [0u8; QR_CODE_LEN];
+    for (i, byte) in code.iter_mut().enumerate() {
+        *byte = (i as u8).wrapping_mul(37).wrapping_add(11);
+    }
+    QrPayload::new([7u8; 32], 4_321, code)
+}
+
+#[test]
+fn crc32_matches_reference() {
+    assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    assert_eq!(crc32(b""), 0);
+}
+
+#[test]
+fn round_trips() {
+    let payload = sample();
+    let encoded = payload.encode();
+
+    assert!(encoded.starts_with("usv:1/"));
+    assert_eq!(encoded.split('/').count(), 5);
+    assert_eq!(QrPayload::decode(&encoded), Ok(payload));
+    assert_eq!(encoded.parse::<QrPayload>(), Ok(payload));
+}
+
+#[test]
+fn rejects_every_single_character_typo() {
+    let encoded = sample().encode();
+    let prefix_len = "usv:1/".len();
+
+    for (i, original) in encoded.char_indices().skip(prefix_len) {
+        for replacement in ['0', '1', '9', 'a', 'f', 'z', 'A', 'Z', '/'] {
+            if replacement == original {
+                continue;
+            }
+            let mut typo = encoded.clone();
+            typo.replace_range(i..i + 1, &replacement.to_string());
+            assert!(QrPayload::decode(&typo).is_err(), "accepted {}", typo);
+        }
+    }
+}
+
+#[test]
+fn rejects_other_schemes_and_versions() {
+    let encoded = sample().encode();
+
+    assert_eq!(
+        QrPayload::decode(&encoded.replacen("usv:", "xyz:", 1)),
+        Err(PayloadError::InvalidScheme)
+    );
+    assert_eq!(
+        QrPayload::decode(&encoded.replacen("usv:1/", "usv:2/", 1)),
+        Err(PayloadError::UnsupportedVersion)
+    );
+    assert_eq!(QrPayload::decode("a1b2c3d4e5f6a7b8"), Err(PayloadError::InvalidScheme));
+}
+
+#[test]
+fn rejects_well_checksummed_garbage() {
+    let body = "usv:1/not-base58/12/00";
+    let payload = format!("{}/{:08x}", body, crc32(body.as_bytes()));
+    assert_eq!(QrPayload::decode(&payload), Err(PayloadError::InvalidBatch));
+}
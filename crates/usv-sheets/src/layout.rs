@@ -0,0 +1,131 @@
+// crates/usv-sheets/src/layout.rs - Label sheet geometry
+
+use serde::Deserialize;
+
+use crate::{Result, SheetError};
+
+// All lengths are in pixels at `dpi`; SVG output converts them to millimetres
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Layout {
+    pub dpi: u32,
+    pub page_width: u32,
+    pub page_height: u32,
+    pub columns: u32,
+    pub rows: u32,
+    pub label_width: u32,
+    pub label_height: u32,
+    pub margin_x: u32,
+    pub margin_y: u32,
+    #[serde(default)]
+    pub gap_x: u32,
+    #[serde(default)]
+    pub gap_y: u32,
+    // Blank border around each code, in QR modules (the spec asks for 4)
+    #[serde(default = "default_quiet_zone")]
+    pub quiet_zone: u32,
+    // Print the serial under each code
+    #[serde(default = "default_caption")]
+    pub caption: bool,
+}
+
+fn default_quiet_zone() -> u32 {
+    4
+}
+
+fn default_caption() -> bool {
+    true
+}
+
+pub const PRESETS: &[&str] = &["a4-3x8", "letter-3x10"];
+
+impl Layout {
+    pub fn preset(name: &str) -> Option<Layout> {
+        match name {
+            // A4 with 3x8 labels of 70x37 mm, 300 dpi
+            "a4-3x8" => Some(Layout {
+                dpi: 300,
+                page_width: 2480,
+                page_height: 3508,
+                columns: 3,
+                rows: 8,
+                label_width: 826,
+                label_height: 437,
+                margin_x: 1,
+                margin_y: 6,
+                gap_x: 0,
+                gap_y: 0,
+                quiet_zone: 4,
+                caption: true,
+            }),
+            // US Letter with 3x10 labels of 2.625x1 in (Avery 5160), 300 dpi
+            "letter-3x10" => Some(Layout {
+                dpi: 300,
+                page_width: 2550,
+                page_height: 3300,
+                columns: 3,
+                rows: 10,
+                label_width: 788,
+                label_height: 300,
+                margin_x: 55,
+                margin_y: 150,
+                gap_x: 38,
+                gap_y: 0,
+                quiet_zone: 4,
+                caption: true,
+            }),
+            _ => None,
+        }
+    }
+
+    // A preset name or the path to a JSON file with the fields above
+    pub fn load(spec: &str) -> Result<Layout> {
+        if let Some(layout) = Layout::preset(spec) {
+            return Ok(layout);
+        }
+        if !std::path::Path::new(spec).is_file() {
+            return Err(SheetError::UnknownLayout(spec.to_string()));
+        }
+        let layout: Layout = serde_json::from_slice(&std::fs::read(spec)?)?;
+        layout.validate()?;
+        Ok(layout)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.dpi == 0 || self.columns == 0 || self.rows == 0 {
+            return Err(SheetError::InvalidLayout("dpi, columns and rows must be non-zero"));
+        }
+        if self.label_width == 0 || self.label_height == 0 {
+            return Err(SheetError::InvalidLayout("labels must have a non-zero size"));
+        }
+        let width = 2 * self.margin_x as u64
+            + self.columns as u64 * self.label_width as u64
+            + (self.columns as u64 - 1) * self.gap_x as u64;
+        let height = 2 * self.margin_y as u64
+            + self.rows as u64 * self.label_height as u64
+            + (self.rows as u64 - 1) * self.gap_y as u64;
+        if width > self.page_width as u64 || height > self.page_height as u64 {
+            return Err(SheetError::InvalidLayout("labels do not fit on the page"));
+        }
+        Ok(())
+    }
+
+    pub fn labels_per_page(&self) -> usize {
+        (self.columns * self.rows) as usize
+    }
+
+    // Row and column of a slot on the page, filled row by row
+    pub fn position(&self, slot: usize) -> (u32, u32) {
+        let slot = slot as u32;
+        (slot / self.columns, slot % self.columns)
+    }
+
+    // Top-left corner of a slot in pixels
+    pub fn origin(&self, slot: usize) -> (u32, u32) {
+        let (row, column) = self.position(slot);
+        (
+            self.margin_x + column * (self.label_width + self.gap_x),
+            self.margin_y + row * (self.label_height + self.gap_y),
+        )
+    }
+}
@@ -0,0 +1,89 @@
+// crates/usv-sheets/src/lib.rs - Printable label sheets and CSV manifests for QR batches
+//
+// Output is a pure function of the batch and the layout: no timestamps, no
+// randomness and fixed encoder settings, so re-exporting a batch reproduces
+// the same bytes and printed sheets can be audited against the chain.
+
+pub mod layout;
+pub mod manifest;
+pub mod render;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use usv_qr::{QrPayload, QR_CODE_LEN};
+
+pub use layout::Layout;
+pub use render::Format;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SheetError {
+    #[error("unknown layout {0}")]
+    UnknownLayout(String),
+    #[error("invalid layout: {0}")]
+    InvalidLayout(&'static str),
+    #[error("label too small for a {0}-module QR code")]
+    LabelTooSmall(usize),
+    #[error("QR encoding failed: {0}")]
+    Qr(#[from] qrcode::types::QrError),
+    #[error("PNG encoding failed: {0}")]
+    Png(#[from] png::EncodingError),
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("layout file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, SheetError>;
+
+// One printed label. Serials are 1-based positions within the batch
+#[derive(Clone, Debug)]
+pub struct Label {
+    pub serial: String,
+    pub payload: QrPayload,
+    pub batch: String,
+    pub batch_id: String,
+    pub partner_id: Option<String>,
+}
+
+pub fn labels(
+    batch: [u8; 32],
+    batch_id: &str,
+    partner_id: Option<&str>,
+    codes: &[[u8; QR_CODE_LEN]],
+) -> Vec<Label> {
+    let address = bs58::encode(batch).into_string();
+    codes
+        .iter()
+        .zip(0u32..)
+        .map(|(code, index)| Label {
+            serial: format!("{:06}", index + 1),
+            payload: QrPayload::new(batch, index, *code),
+            batch: address.clone(),
+            batch_id: batch_id.to_string(),
+            partner_id: partner_id.map(str::to_string),
+        })
+        .collect()
+}
+
+// Write `sheet-NNNN.<ext>` for every page in each format, plus `manifest.csv`
+pub fn export(dir: &Path, layout: &Layout, labels: &[Label], formats: &[Format]) -> Result<Vec<PathBuf>> {
+    layout.validate()?;
+    fs::create_dir_all(dir)?;
+
+    let mut written = Vec::new();
+    for (page, page_labels) in labels.chunks(layout.labels_per_page()).enumerate() {
+        for format in formats {
+            let path = dir.join(format!("sheet-{:04}.{}", page + 1, format.extension()));
+            fs::write(&path, render::render_page(layout, page_labels, *format)?)?;
+            written.push(path);
+        }
+    }
+
+    let path = dir.join("manifest.csv");
+    manifest::write_manifest(fs::File::create(&path)?, layout, labels)?;
+    written.push(path);
+    Ok(written)
+}
@@ -0,0 +1,44 @@
+// crates/usv-sheets/src/manifest.rs - CSV manifest of every printed label
+
+use std::io::Write;
+
+use serde::Serialize;
+
+use crate::{Label, Layout, Result};
+
+#[derive(Serialize)]
+struct ManifestRecord<'a> {
+    serial: &'a str,
+    code: String,
+    payload: String,
+    batch: &'a str,
+    batch_id: &'a str,
+    partner: &'a str,
+    page: usize,
+    row: u32,
+    column: u32,
+}
+
+// Rows are in serial order with 1-based page, row and column positions
+pub fn write_manifest<W: Write>(writer: W, layout: &Layout, labels: &[Label]) -> Result<()> {
+    let mut csv = csv::Writer::from_writer(writer);
+    let per_page = layout.labels_per_page();
+
+    for (i, label) in labels.iter().enumerate() {
+        let (row, column) = layout.position(i % per_page);
+        csv.serialize(ManifestRecord {
+            serial: &label.serial,
+            code: label.payload.code.iter().map(|b| format!("{:02x}", b)).collect(),
+            payload: label.payload.encode(),
+            batch: &label.batch,
+            batch_id: &label.batch_id,
+            partner: label.partner_id.as_deref().unwrap_or(""),
+            page: i / per_page + 1,
+            row: row + 1,
+            column: column + 1,
+        })?;
+    }
+
+    csv.flush()?;
+    Ok(())
+}
@@ -0,0 +1,214 @@
+// crates/usv-sheets/src/render.rs - PNG and SVG page rendering
+
+use std::fmt::Write as _;
+use std::str::FromStr;
+
+use qrcode::{Color, EcLevel, QrCode};
+
+use crate::{Label, Layout, Result, SheetError};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Png,
+    Svg,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Png => "png",
+            Format::Svg => "svg",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "png" => Ok(Format::Png),
+            "svg" => Ok(Format::Svg),
+            _ => Err(format!("unknown format {} (expected png or svg)", s)),
+        }
+    }
+}
+
+// Both backends receive the same filled rectangles, so PNG and SVG sheets match
+trait Canvas {
+    fn fill(&mut self, x: u32, y: u32, width: u32, height: u32);
+}
+
+// 8-bit grayscale, white background
+struct Raster {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas for Raster {
+    fn fill(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        for row in y..y + height {
+            let start = (row * self.width + x) as usize;
+            self.pixels[start..start + width as usize].fill(0);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Vector {
+    path: String,
+}
+
+impl Canvas for Vector {
+    fn fill(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        let _ = write!(self.path, "M{} {}h{}v{}h-{}z", x, y, width, height, width);
+    }
+}
+
+pub fn render_page(layout: &Layout, labels: &[Label], format: Format) -> Result<Vec<u8>> {
+    match format {
+        Format::Png => {
+            let mut raster = Raster {
+                width: layout.page_width,
+                height: layout.page_height,
+                pixels: vec![255; layout.page_width as usize * layout.page_height as usize],
+            };
+            draw_page(layout, labels, &mut raster)?;
+            encode_png(layout, &raster)
+        }
+        Format::Svg => {
+            let mut vector = Vector::default();
+            draw_page(layout, labels, &mut vector)?;
+            Ok(encode_svg(layout, &vector).into_bytes())
+        }
+    }
+}
+
+fn draw_page(layout: &Layout, labels: &[Label], canvas: &mut impl Canvas) -> Result<()> {
+    for (slot, label) in labels.iter().take(layout.labels_per_page()).enumerate() {
+        let (x, y) = layout.origin(slot);
+        draw_label(layout, label, x, y, canvas)?;
+    }
+    Ok(())
+}
+
+fn draw_label(layout: &Layout, label: &Label, x: u32, y: u32, canvas: &mut impl Canvas) -> Result<()> {
+    let code = QrCode::with_error_correction_level(label.payload.encode(), EcLevel::M)?;
+    let width = code.width();
+
+    // Largest whole-pixel module that fits the code and its quiet zone above the caption
+    let caption_height = if layout.caption { layout.label_height / 6 } else { 0 };
+    let modules = width as u32 + 2 * layout.quiet_zone;
+    let module = layout.label_width.min(layout.label_height - caption_height) / modules;
+    if module == 0 {
+        return Err(SheetError::LabelTooSmall(width));
+    }
+    let size = module * modules;
+    let left = x + (layout.label_width - size) / 2 + layout.quiet_zone * module;
+    let top = y + (layout.label_height - caption_height - size) / 2 + layout.quiet_zone * module;
+
+    // One rectangle per horizontal run of dark modules
+    let colors = code.to_colors();
+    for (row, line) in colors.chunks(width).enumerate() {
+        let mut column = 0;
+        while column < width {
+            if line[column] != Color::Dark {
+                column += 1;
+                continue;
+            }
+            let run = line[column..].iter().take_while(|c| **c == Color::Dark).count();
+            canvas.fill(
+                left + column as u32 * module,
+                top + row as u32 * module,
+                run as u32 * module,
+                module,
+            );
+            column += run;
+        }
+    }
+
+    if caption_height > 0 {
+        let center = x + layout.label_width / 2;
+        draw_caption(canvas, &label.serial, center, y + layout.label_height - caption_height, caption_height);
+    }
+    Ok(())
+}
+
+// 3x5 bitmap digits, one row per byte with the leftmost pixel in bit 2. Drawing
+// the caption as rectangles keeps it identical across formats and independent
+// of installed fonts
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+fn draw_caption(canvas: &mut impl Canvas, text: &str, center: u32, top: u32, height: u32) {
+    let scale = height / 7;
+    if scale == 0 || text.is_empty() {
+        return;
+    }
+    let text_width = (4 * text.len() as u32 - 1) * scale;
+    let left = center.saturating_sub(text_width / 2);
+    let top = top + (height - 5 * scale) / 2;
+
+    for (i, ch) in text.chars().enumerate() {
+        let Some(glyph) = ch.to_digit(10).map(|d| DIGITS[d as usize]) else {
+            continue;
+        };
+        let x = left + i as u32 * 4 * scale;
+        for (row, bits) in glyph.iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) != 0 {
+                    canvas.fill(x + column * scale, top + row as u32 * scale, scale, scale);
+                }
+            }
+        }
+    }
+}
+
+fn encode_png(layout: &Layout, raster: &Raster) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, raster.width, raster.height);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_compression(png::Compression::Best);
+    let pixels_per_meter = (layout.dpi as u64 * 10_000 / 254) as u32;
+    encoder.set_pixel_dims(Some(png::PixelDimensions {
+        xppu: pixels_per_meter,
+        yppu: pixels_per_meter,
+        unit: png::Unit::Meter,
+    }));
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&raster.pixels)?;
+    writer.finish()?;
+    Ok(out)
+}
+
+fn encode_svg(layout: &Layout, vector: &Vector) -> String {
+    let mm = |px: u32| px as f64 * 25.4 / layout.dpi as f64;
+    format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.2}mm\" height=\"{:.2}mm\" ",
+            "viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">\n",
+            "<rect width=\"100%\" height=\"100%\" fill=\"#fff\"/>\n",
+            "<path fill=\"#000\" d=\"{}\"/>\n",
+            "</svg>\n"
+        ),
+        mm(layout.page_width),
+        mm(layout.page_height),
+        layout.page_width,
+        layout.page_height,
+        vector.path,
+    )
+}
@@ -0,0 +1,72 @@
+// crates/usv-sheets/tests/sheets.rs - Layout paging, manifests and reproducible output
+
+use usv_sheets::{export, labels, manifest, render, Format, Layout, SheetError};
+
+fn sample_labels(count: u8) -> Vec<usv_sheets::Label> {
+    let codes: Vec<[u8; 16]> = (0..count).map(|i| [i; 16]).collect();
+    labels([9u8; 32], "BATCH_1700000000", Some("partner-a"), &codes)
+}
+
+#[test]
+fn presets_fit_their_pages() {
+    for name in usv_sheets::layout::PRESETS {
+        Layout::preset(name).unwrap().validate().unwrap();
+    }
+    assert!(matches!(Layout::load("no-such-layout"), Err(SheetError::UnknownLayout(_))));
+}
+
+#[test]
+fn rejects_layouts_larger_than_the_page() {
+    let mut layout = Layout::preset("a4-3x8").unwrap();
+    layout.columns = 4;
+    assert!(matches!(layout.validate(), Err(SheetError::InvalidLayout(_))));
+}
+
+#[test]
+fn manifest_lists_every_label_with_its_position() {
+    let layout = Layout::preset("a4-3x8").unwrap();
+    let labels = sample_labels(26);
+    let mut out = Vec::new();
+    manifest::write_manifest(&mut out, &layout, &labels).unwrap();
+
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "serial,code,payload,batch,batch_id,partner,page,row,column");
+    assert_eq!(lines.len(), 27);
+    assert!(lines[1].starts_with("000001,00000000000000000000000000000000,usv:1/"));
+    assert!(lines[1].ends_with(",BATCH_1700000000,partner-a,1,1,1"));
+    // The 25th label starts the second A4 page
+    assert!(lines[25].ends_with(",2,1,1"));
+    assert!(lines[26].ends_with(",2,1,2"));
+}
+
+#[test]
+fn renders_identical_bytes_every_time() {
+    let layout = Layout::preset("letter-3x10").unwrap();
+    let labels = sample_labels(4);
+
+    for format in [Format::Png, Format::Svg] {
+        let first = render::render_page(&layout, &labels, format).unwrap();
+        let second = render::render_page(&layout, &labels, format).unwrap();
+        assert!(!first.is_empty());
+        assert_eq!(first, second);
+    }
+}
+
+#[test]
+fn exports_one_sheet_per_page_and_format() {
+    let dir = std::env::temp_dir().join(format!("usv-sheets-{}", std::process::id()));
+    let layout = Layout::preset("a4-3x8").unwrap();
+
+    let written = export(&dir, &layout, &sample_labels(30), &[Format::Png, Format::Svg]).unwrap();
+    let names: Vec<String> = written
+        .iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(
+        names,
+        ["sheet-0001.png", "sheet-0001.svg", "sheet-0002.png", "sheet-0002.svg", "manifest.csv"]
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
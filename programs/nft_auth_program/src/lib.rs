@@ -6,6 +6,9 @@ use anchor_spl::{
 
 declare_id!("GdcoT7hsvLJsKWebA5em5127kQvEmh9JKma2YT9CWsYz");
 
+// Guardian pause votes are counted by the shared usv-guardians crate
+pub use usv_guardians::{GuardianSet, GUARDIAN_VOTE_WINDOW, MAX_GUARDIANS};
+
 #[program]
 pub mod nft_auth_program {
     use super::*;
@@ -16,6 +19,7 @@ pub mod nft_auth_program {
         state.authority = ctx.accounts.authority.key();
         state.total_pieces = 1_000_000_000; // 1 billion pieces
         state.minted_pieces = 0;
+        ctx.accounts.mint_guard.bump = ctx.bumps.mint_guard;
         
         msg!("🎯 Master NFT Collection initialized! Ready for QR scanning!");
         Ok(())
@@ -24,11 +28,17 @@ pub mod nft_auth_program {
     // Register QR code for product
     pub fn register_qr_code(
         ctx: Context<RegisterQR>,
-        _qr_code: String, // Prefix with _ to avoid warning
+        qr_code: String,
     ) -> Result<()> {
         let qr_data = &mut ctx.accounts.qr_data;
         qr_data.is_claimed = false;
         qr_data.piece_number = 0;
+
+        emit_cpi!(QRCodeRegistered {
+            qr_code,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         
         msg!("📱 QR Code registered!");
         Ok(())
@@ -37,8 +47,9 @@ pub mod nft_auth_program {
     // Mint NFT piece - SIMPLIFIED VERSION (no stack overflow)
     pub fn mint_nft_piece(
         ctx: Context<MintNFT>,
-        _qr_code: String, // Prefix with _ to avoid warning
+        qr_code: String,
     ) -> Result<()> {
+        require!(!ctx.accounts.mint_guard.is_paused, ErrorCode::MintingPaused);
         let qr_data = &mut ctx.accounts.qr_data;
         let state = &mut ctx.accounts.program_state;
         
@@ -70,9 +81,58 @@ pub mod nft_auth_program {
             1, // Mint exactly 1 NFT
         )?;
 
+        emit_cpi!(NftPieceMinted {
+            qr_code,
+            piece_number,
+            customer: ctx.accounts.customer.key(),
+            nft_mint: ctx.accounts.nft_mint.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
         msg!("🎉 NFT piece #{} minted successfully!", piece_number);
         Ok(())
     }
+
+    // Pause or resume minting. Also creates the mint guard for collections
+    // initialized before it existed; minting needs it
+    pub fn set_pause_state(ctx: Context<SetMintGuard>, is_paused: bool) -> Result<()> {
+        let mint_guard = &mut ctx.accounts.mint_guard;
+        mint_guard.bump = ctx.bumps.mint_guard;
+        mint_guard.is_paused = is_paused;
+        mint_guard.guardians.clear_votes();
+        Ok(())
+    }
+
+    // Replace the guardians; `threshold` of them must vote to pause minting. An
+    // empty list with threshold 0 disables guardian pauses
+    pub fn set_guardians(ctx: Context<SetMintGuard>, guardians: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        let mint_guard = &mut ctx.accounts.mint_guard;
+        mint_guard.bump = ctx.bumps.mint_guard;
+        mint_guard.guardians.replace(guardians, threshold)
+    }
+
+    // A guardian's vote to pause minting. Minting stops once `guardians.threshold`
+    // distinct guardians vote within GUARDIAN_VOTE_WINDOW; guardians can never resume it
+    pub fn guardian_pause(ctx: Context<GuardianPause>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let guardian = ctx.accounts.guardian.key();
+        let mint_guard = &mut ctx.accounts.mint_guard;
+        require!(!mint_guard.is_paused, ErrorCode::MintingPaused);
+        let (votes, paused) = mint_guard.guardians.vote(guardian, now)?;
+        if paused {
+            mint_guard.is_paused = true;
+        }
+
+        emit_cpi!(MintingPauseVoted {
+            guardian,
+            votes,
+            threshold: mint_guard.guardians.threshold,
+            paused,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
 }
 
 // SIMPLIFIED Account structures (reduced stack usage)
@@ -81,7 +141,7 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + 32 + 8 + 8, // Smaller space
+        space = 8 + ProgramState::INIT_SPACE,
         seeds = [b"state"],
         bump
     )]
@@ -96,6 +156,15 @@ pub struct Initialize<'info> {
         bump
     )]
     pub master_collection: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MintGuard::INIT_SPACE,
+        seeds = [b"mint_guard"],
+        bump
+    )]
+    pub mint_guard: Account<'info, MintGuard>,
     
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -104,13 +173,14 @@ pub struct Initialize<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(qr_code: String)]
 pub struct RegisterQR<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + 1 + 8 + 32, // Much smaller
+        space = 8 + QRData::INIT_SPACE,
         seeds = [b"qr", qr_code.as_bytes()],
         bump
     )]
@@ -122,6 +192,7 @@ pub struct RegisterQR<'info> {
 }
 
 // SIMPLIFIED MintNFT struct (no stack overflow)
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(qr_code: String)]
 pub struct MintNFT<'info> {
@@ -138,6 +209,9 @@ pub struct MintNFT<'info> {
         bump
     )]
     pub program_state: Account<'info, ProgramState>,
+
+    #[account(seeds = [b"mint_guard"], bump = mint_guard.bump)]
+    pub mint_guard: Account<'info, MintGuard>,
     
     #[account(
         mut,
@@ -172,23 +246,91 @@ pub struct MintNFT<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+pub struct SetMintGuard<'info> {
+    #[account(seeds = [b"state"], bump, has_one = authority)]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + MintGuard::INIT_SPACE,
+        seeds = [b"mint_guard"],
+        bump
+    )]
+    pub mint_guard: Account<'info, MintGuard>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct GuardianPause<'info> {
+    #[account(mut, seeds = [b"mint_guard"], bump = mint_guard.bump)]
+    pub mint_guard: Account<'info, MintGuard>,
+
+    pub guardian: Signer<'info>,
+}
+
 // SIMPLIFIED data structures
 #[account]
+#[derive(InitSpace)]
 pub struct ProgramState {
     pub authority: Pubkey,
     pub total_pieces: u64,
     pub minted_pieces: u64,
 }
 
+// Minting pause and the guardians who can vote for it; kept out of
+// ProgramState so existing collections keep their layout
+#[account]
+#[derive(InitSpace)]
+pub struct MintGuard {
+    pub is_paused: bool,
+    pub guardians: GuardianSet,
+    pub bump: u8,
+}
+
 #[account]
+#[derive(InitSpace)]
 pub struct QRData {
     pub is_claimed: bool,
     pub piece_number: u64,
     pub claimed_by: Pubkey,
 }
 
+#[event]
+pub struct QRCodeRegistered {
+    pub qr_code: String,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NftPieceMinted {
+    pub qr_code: String,
+    pub piece_number: u64,
+    pub customer: Pubkey,
+    pub nft_mint: Pubkey,
+    pub timestamp: i64,
+}
+
+// `paused` is set on the vote that reached the threshold
+#[event]
+pub struct MintingPauseVoted {
+    pub guardian: Pubkey,
+    pub votes: u8,
+    pub threshold: u8,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("QR code has already been claimed")]
     QRAlreadyClaimed,
+    #[msg("Minting is paused")]
+    MintingPaused,
 }
@@ -0,0 +1,1505 @@
+// programs/usv-token/src/lib.rs - Fixed for Anchor 0.29.0/0.30.0
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use anchor_spl::token::{self, Token, TokenAccount, Mint, Transfer, MintTo};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use anchor_lang::solana_program::system_instruction::MAX_PERMITTED_DATA_LENGTH;
+use sha2::{Sha256, Digest};
+
+declare_id!("BAagt8iyDDDConY335Dd49vvMww18L6mqd8sx4SvvxGX");
+
+// QR codes are the first 128 bits of a SHA-256 digest
+pub const QR_CODE_LEN: usize = 16;
+
+// Length limits for variable-size account fields
+pub const MAX_BATCH_ID_LEN: usize = 32;
+pub const MAX_PARTNER_ID_LEN: usize = 32;
+pub const MAX_BATCH_INFO_LEN: usize = 128;
+pub const MAX_USER_EMAIL_LEN: usize = 64;
+
+// Codes added per instruction. Keeps each allocation under the 10 KiB
+// per-instruction growth limit and the hashing loop inside one transaction's
+// compute budget
+pub const MAX_QR_CODES_PER_APPEND: usize = 200;
+
+// Total codes a batch can grow to across appends (50k-unit production runs)
+pub const MAX_QR_CODES_PER_BATCH: usize = 50_000;
+
+// Tokens paid per claim before any loyalty multiplier (1 token, 6 decimals)
+pub const BASE_CLAIM_AMOUNT: u64 = 1_000_000;
+
+// Loyalty tier limits. Multipliers are in basis points of BASE_CLAIM_AMOUNT
+pub const MAX_LOYALTY_TIERS: usize = 8;
+pub const MAX_TIER_NAME_LEN: usize = 16;
+pub const BASE_MULTIPLIER_BPS: u16 = 10_000;
+pub const MAX_MULTIPLIER_BPS: u16 = 50_000;
+
+// Length of the rolling window for per-wallet claim limits
+pub const CLAIM_LIMIT_WINDOW: i64 = 24 * 60 * 60;
+
+// Guardian pause votes are counted by the shared usv-guardians crate
+pub use usv_guardians::{GuardianSet, GUARDIAN_VOTE_WINDOW, MAX_GUARDIANS};
+
+// Bumped whenever fields are appended to USVState. Version 1 is the first layout
+// that appends them; the ones before it are listed in LEGACY_STATE_LENS
+pub const USV_STATE_LAYOUT_VERSION: u8 = 1;
+
+#[program]
+pub mod usv_token {
+    use super::*;
+
+    // Initialize the USV Token system
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        let usv_state = &mut ctx.accounts.usv_state;
+        
+        // Initialize state
+        usv_state.authority = ctx.accounts.authority.key();
+        usv_state.mint = ctx.accounts.mint.key();
+        usv_state.total_supply = 1_000_000_000 * 10_u64.pow(6); // 1 billion tokens with 6 decimals
+        usv_state.tokens_claimed = 0;
+        usv_state.total_qr_codes = 0;
+        usv_state.total_batches = 0;
+        usv_state.is_paused = false;
+        usv_state.bump = ctx.bumps.usv_state;
+      usv_state.mint_bump = ctx.bumps.mint;
+        usv_state.referral_bonus = 0;
+        usv_state.referral_rewards_paid = 0;
+        usv_state.max_claims_per_day = 0;
+        usv_state.max_tokens_per_day = 0;
+        usv_state.breaker_bucket_secs = 0;
+        usv_state.breaker_max_claims = 0;
+        usv_state.breaker_max_batch_claims = 0;
+        usv_state.bucket_start = 0;
+        usv_state.bucket_claims = 0;
+        usv_state.breaker_epoch = 0;
+        usv_state.guardians = GuardianSet::default();
+        usv_state.layout_version = USV_STATE_LAYOUT_VERSION;
+
+        // No tiers until the authority configures them; every claim pays the base amount
+        ctx.accounts.loyalty_tiers.bump = ctx.bumps.loyalty_tiers;
+
+        // Mint entire supply to authority
+       let mint_authority_bump = ctx.bumps.mint_authority;
+        let mint_seeds = &[b"mint_authority".as_ref(), &[mint_authority_bump]];
+
+        let signer = &[&mint_seeds[..]];
+
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.authority_token_account.to_account_info(),
+            authority: ctx.accounts.mint_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        
+        token::mint_to(cpi_ctx, usv_state.total_supply)?;
+
+        msg!("USV Token initialized with {} tokens minted to authority", usv_state.total_supply);
+        Ok(())
+    }
+
+    // Generate QR codes with unique hashes in a single, sealed batch
+    pub fn generate_qr_codes(
+        ctx: Context<GenerateQRCodes>,
+        count: u32,
+        partner_id: Option<String>,
+        batch_info: String,
+    ) -> Result<()> {
+        require!(!ctx.accounts.usv_state.is_paused, ErrorCode::ProgramPaused);
+        require!(
+            count > 0 && count as usize <= MAX_QR_CODES_PER_APPEND,
+            ErrorCode::InvalidQRCodeCount
+        );
+        validate_batch_metadata(&partner_id, &batch_info)?;
+        
+        let usv_state = &mut ctx.accounts.usv_state;
+        let authority = ctx.accounts.authority.key();
+
+        // Initialize QR batch header
+        let batch_id = {
+            let mut qr_batch = ctx.accounts.qr_batch.load_init()?;
+            qr_batch.init(authority, &partner_id, &batch_info, ctx.bumps.qr_batch)?;
+            qr_batch.count = count;
+            qr_batch.is_sealed = 1;
+            qr_batch.batch_id()
+        };
+
+        // Generate QR codes
+        derive_qr_codes(&ctx.accounts.qr_batch, 0, count, usv_state.total_qr_codes, &authority)?;
+        usv_state.total_qr_codes += count;
+        usv_state.total_batches += 1;
+
+        emit_cpi!(QRCodesGenerated {
+            batch_id,
+            count,
+            partner_id,
+            authority,
+        });
+
+        Ok(())
+    }
+
+    // Create an empty batch that is filled over several append_qr_codes calls
+    pub fn create_batch(
+        ctx: Context<CreateBatch>,
+        partner_id: Option<String>,
+        batch_info: String,
+    ) -> Result<()> {
+        require!(!ctx.accounts.usv_state.is_paused, ErrorCode::ProgramPaused);
+        validate_batch_metadata(&partner_id, &batch_info)?;
+
+        let mut qr_batch = ctx.accounts.qr_batch.load_init()?;
+        qr_batch.init(ctx.accounts.authority.key(), &partner_id, &batch_info, ctx.bumps.qr_batch)?;
+
+        ctx.accounts.usv_state.total_batches += 1;
+
+        Ok(())
+    }
+
+    // Grow an open batch by `count` codes, reallocating the account to fit
+    pub fn append_qr_codes(ctx: Context<AppendQRCodes>, count: u32) -> Result<()> {
+        require!(!ctx.accounts.usv_state.is_paused, ErrorCode::ProgramPaused);
+        require!(
+            count > 0 && count as usize <= MAX_QR_CODES_PER_APPEND,
+            ErrorCode::InvalidQRCodeCount
+        );
+
+        let usv_state = &mut ctx.accounts.usv_state;
+        let authority = ctx.accounts.authority.key();
+
+        let (first_slot, batch_id, partner_id) = {
+            let mut qr_batch = ctx.accounts.qr_batch.load_mut()?;
+            require!(!qr_batch.is_sealed(), ErrorCode::BatchSealed);
+            require!(
+                qr_batch.count as usize + count as usize <= MAX_QR_CODES_PER_BATCH,
+                ErrorCode::BatchCapacityExceeded
+            );
+            let first_slot = qr_batch.count;
+            qr_batch.count += count;
+            (first_slot, qr_batch.batch_id(), qr_batch.partner_id())
+        };
+
+        derive_qr_codes(&ctx.accounts.qr_batch, first_slot, count, usv_state.total_qr_codes, &authority)?;
+        usv_state.total_qr_codes += count;
+
+        emit_cpi!(QRCodesGenerated {
+            batch_id,
+            count,
+            partner_id,
+            authority,
+        });
+
+        Ok(())
+    }
+
+    // Freeze a batch so no further codes can be appended
+    pub fn seal_batch(ctx: Context<SealBatch>) -> Result<()> {
+        let event = {
+            let mut qr_batch = ctx.accounts.qr_batch.load_mut()?;
+            require!(!qr_batch.is_sealed(), ErrorCode::BatchSealed);
+
+            qr_batch.is_sealed = 1;
+
+            BatchSealed {
+                batch_id: qr_batch.batch_id(),
+                count: qr_batch.count,
+                authority: qr_batch.authority,
+                timestamp: Clock::get()?.unix_timestamp,
+            }
+        };
+
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    // Claim tokens using a QR code from a batch. `referrer` only counts on the
+    // claimer's first claim and must be a wallet that has already claimed
+    pub fn claim_tokens(
+        ctx: Context<ClaimTokens>,
+        qr_code: [u8; QR_CODE_LEN],
+        code_index: u32,
+        user_email: Option<String>,
+        referrer: Option<Pubkey>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.usv_state.is_paused, ErrorCode::ProgramPaused);
+        require!(
+            read_qr_code(&ctx.accounts.qr_batch, code_index)? == Some(qr_code),
+            ErrorCode::InvalidQRHash
+        );
+        if let Some(user_email) = &user_email {
+            require!(user_email.len() <= MAX_USER_EMAIL_LEN, ErrorCode::UserEmailTooLong);
+        }
+        
+        if referrer.is_some() {
+            require!(ctx.accounts.referrer_referral.is_some(), ErrorCode::ReferrerNotFound);
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let claimer = ctx.accounts.claimer.key();
+
+        // The tier reached before this claim sets its multiplier
+        let loyalty_tiers = &ctx.accounts.loyalty_tiers;
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.open(claimer, now, ctx.bumps.user_profile);
+        let first_claim = user_profile.claim_count == 0;
+        let token_amount = loyalty_tiers.reward(user_profile.claim_count)?;
+
+        let usv_state = &ctx.accounts.usv_state;
+        user_profile.record_windowed_claim(now, token_amount)?;
+        if !user_profile.is_trusted {
+            let (claims, tokens) = user_profile.windowed_totals(now);
+            require!(
+                usv_state.max_claims_per_day == 0 || claims <= usv_state.max_claims_per_day as u128,
+                ErrorCode::ClaimRateLimited
+            );
+            require!(
+                usv_state.max_tokens_per_day == 0 || tokens <= usv_state.max_tokens_per_day as u128,
+                ErrorCode::ClaimRateLimited
+            );
+        }
+
+        let old_tier = user_profile.tier;
+        user_profile.claim_count = user_profile.claim_count.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        user_profile.lifetime_rewards = user_profile.lifetime_rewards.checked_add(token_amount).ok_or(ErrorCode::ArithmeticOverflow)?;
+        user_profile.last_claim_at = now;
+        user_profile.tier = loyalty_tiers.tier_for(user_profile.claim_count);
+
+        let tier_changed = (user_profile.tier != old_tier).then(|| TierChanged {
+            user: claimer,
+            old_tier,
+            new_tier: user_profile.tier,
+            tier_name: loyalty_tiers.name(user_profile.tier),
+            claim_count: user_profile.claim_count,
+            timestamp: now,
+        });
+
+        // Circuit breaker: a claim that takes a bucket past its threshold pauses
+        // claims. It still completes, so the pause is not rolled back with it
+        let mut tripped = Vec::new();
+        let usv_state = &mut ctx.accounts.usv_state;
+        let bucket_secs = usv_state.breaker_bucket_secs;
+        if bucket_secs > 0 {
+            let (bucket_start, claims) = count_in_bucket(usv_state.bucket_start, usv_state.bucket_claims, now, bucket_secs);
+            (usv_state.bucket_start, usv_state.bucket_claims) = (bucket_start, claims);
+            if usv_state.breaker_max_claims > 0 && claims > usv_state.breaker_max_claims {
+                tripped.push((None, claims, bucket_start));
+            }
+            if usv_state.breaker_max_batch_claims > 0 {
+                let batch_breaker = ctx.accounts.batch_breaker.as_mut().ok_or(ErrorCode::BatchBreakerRequired)?;
+                if batch_breaker.epoch != usv_state.breaker_epoch {
+                    batch_breaker.epoch = usv_state.breaker_epoch;
+                    (batch_breaker.bucket_start, batch_breaker.bucket_claims) = (0, 0);
+                }
+                batch_breaker.bump = ctx.bumps.batch_breaker;
+                let (bucket_start, claims) =
+                    count_in_bucket(batch_breaker.bucket_start, batch_breaker.bucket_claims, now, bucket_secs);
+                (batch_breaker.bucket_start, batch_breaker.bucket_claims) = (bucket_start, claims);
+                if claims > usv_state.breaker_max_batch_claims {
+                    tripped.push((Some(ctx.accounts.qr_batch.key()), claims, bucket_start));
+                }
+            }
+            if !tripped.is_empty() {
+                usv_state.is_paused = true;
+            }
+        }
+
+        let qr_claim = &mut ctx.accounts.qr_claim;
+
+        // Initialize claim record
+        qr_claim.qr_code = qr_code;
+        qr_claim.claimer = ctx.accounts.claimer.key();
+        qr_claim.claimed_at = Clock::get()?.unix_timestamp;
+        qr_claim.user_email = user_email.clone();
+        qr_claim.is_claimed = true;
+       qr_claim.bump = ctx.bumps.qr_claim;
+
+        // Transfer the tier-adjusted reward from authority to claimer (gas paid by authority)
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.authority_token_account.to_account_info(),
+            to: ctx.accounts.claimer_token_account.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        
+        token::transfer(cpi_ctx, token_amount)?;
+
+        usv_state.tokens_claimed += token_amount;
+
+        emit_cpi!(TokensClaimed {
+            qr_code,
+            claimer: ctx.accounts.claimer.key(),
+            amount: token_amount,
+            user_email,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        if let Some(event) = tier_changed {
+            emit_cpi!(event);
+        }
+        for (qr_batch, claims, bucket_start) in tripped {
+            emit_cpi!(CircuitBreakerTripped {
+                qr_batch,
+                claims,
+                bucket_start,
+                bucket_secs,
+                timestamp: now,
+            });
+        }
+
+        // The referral record is filled in once. Only the claimer's first claim, going by
+        // their profile, may name a referrer, so a wallet that claimed before it had a
+        // record cannot attach one later
+        if ctx.accounts.referral.referee != Pubkey::default() {
+            return Ok(());
+        }
+        let referral = &mut ctx.accounts.referral;
+        referral.referee = claimer;
+        referral.created_at = Clock::get()?.unix_timestamp;
+        referral.bump = ctx.bumps.referral;
+
+        let Some(referrer) = referrer.filter(|_| first_claim) else {
+            return Ok(());
+        };
+        require!(referrer != claimer, ErrorCode::SelfReferral);
+        referral.referrer = Some(referrer);
+
+        // Referrers must already have their own referral record (required above and
+        // derived from `referrer`), so every referral points to an earlier claimer and
+        // chains can never loop
+
+        // The bonus comes out of the authority's supply, the same account claims are paid
+        // from; the program holds no vault of its own
+        let bonus = ctx.accounts.usv_state.referral_bonus;
+        if bonus == 0 {
+            return Ok(());
+        }
+        let referrer_token_account = ctx.accounts.referrer_token_account.as_ref().ok_or(ErrorCode::InvalidReferrerAccount)?;
+        require!(
+            referrer_token_account.owner == referrer && referrer_token_account.mint == ctx.accounts.mint.key(),
+            ErrorCode::InvalidReferrerAccount
+        );
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.authority_token_account.to_account_info(),
+            to: referrer_token_account.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_ctx, bonus)?;
+
+        ctx.accounts.usv_state.referral_rewards_paid += bonus;
+
+        emit_cpi!(ReferralRewarded {
+            referrer,
+            referee: claimer,
+            amount: bonus,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Manual transfer for partner/pharmacy distribution
+    pub fn transfer_to_partner(
+        ctx: Context<TransferToPartner>,
+        amount: u64,
+        partner_info: String,
+    ) -> Result<()> {
+        require!(!ctx.accounts.usv_state.is_paused, ErrorCode::ProgramPaused);
+        require!(amount >= 1000 * 10_u64.pow(6), ErrorCode::MinimumPartnerTransfer); // Min 1000 tokens
+        
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.authority_token_account.to_account_info(),
+            to: ctx.accounts.partner_token_account.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        
+        token::transfer(cpi_ctx, amount)?;
+
+        emit_cpi!(PartnerTransfer {
+            partner: ctx.accounts.partner.key(),
+            amount,
+            partner_info,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Security functions
+    pub fn set_pause_state(ctx: Context<SetPauseState>, is_paused: bool) -> Result<()> {
+        let usv_state = &mut ctx.accounts.usv_state;
+        usv_state.is_paused = is_paused;
+        usv_state.guardians.clear_votes();
+        // Unpausing starts the breaker counting afresh
+        if !is_paused {
+            usv_state.reset_breaker();
+        }
+        Ok(())
+    }
+
+    // Replace the guardians; `threshold` of them must vote to pause. An empty
+    // list with threshold 0 disables guardian pauses
+    pub fn set_guardians(ctx: Context<SetGuardians>, guardians: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        let usv_state = &mut ctx.accounts.usv_state;
+        usv_state.guardians.replace(guardians, threshold)
+    }
+
+    // A guardian's vote to pause. Claims pause once `guardians.threshold` distinct
+    // guardians vote within GUARDIAN_VOTE_WINDOW; guardians can never unpause
+    pub fn guardian_pause(ctx: Context<GuardianPause>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let guardian = ctx.accounts.guardian.key();
+        let usv_state = &mut ctx.accounts.usv_state;
+        require!(!usv_state.is_paused, ErrorCode::ProgramPaused);
+        let (votes, paused) = usv_state.guardians.vote(guardian, now)?;
+        if paused {
+            usv_state.is_paused = true;
+        }
+
+        emit_cpi!(GuardianPauseVoted {
+            guardian,
+            votes,
+            threshold: usv_state.guardians.threshold,
+            paused,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    // Rewrite a USVState from any earlier layout into the current one, growing the account
+    // to fit. Layouts up to the breaker epoch inserted their fields ahead of `is_paused`, so
+    // each is recognised by its length and parsed field by field
+    pub fn migrate_state(ctx: Context<MigrateState>) -> Result<()> {
+        let info = ctx.accounts.usv_state.to_account_info();
+        let usv_state = {
+            let data = info.try_borrow_data()?;
+            require!(data[..8] == USVState::DISCRIMINATOR, ErrorCode::AlreadyMigrated);
+            read_legacy_state(&data)?
+        };
+        require_keys_eq!(usv_state.authority, ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+
+        let space = 8 + USVState::INIT_SPACE;
+        let rent = Rent::get()?.minimum_balance(space).saturating_sub(info.lamports());
+        if rent > 0 {
+            let cpi_accounts = system_program::Transfer {
+                from: ctx.accounts.authority.to_account_info(),
+                to: info.clone(),
+            };
+            system_program::transfer(
+                CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts),
+                rent,
+            )?;
+        }
+        info.realloc(space, true)?;
+        usv_state.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        msg!("USV state migrated to {} bytes", space);
+        Ok(())
+    }
+
+    // Replace the loyalty tiers, creating the account on deployments initialized before
+    // tiers existed. Tiers are ordered by `min_claims`, starting at 0;
+    // existing profiles move to their new tier on their next claim
+    pub fn set_loyalty_tiers(ctx: Context<SetLoyaltyTiers>, tiers: Vec<LoyaltyTier>) -> Result<()> {
+        require!(tiers.len() <= MAX_LOYALTY_TIERS, ErrorCode::InvalidLoyaltyTiers);
+        if let Some(first) = tiers.first() {
+            require!(first.min_claims == 0, ErrorCode::InvalidLoyaltyTiers);
+        }
+        require!(
+            tiers.windows(2).all(|pair| pair[0].min_claims < pair[1].min_claims),
+            ErrorCode::InvalidLoyaltyTiers
+        );
+        for tier in &tiers {
+            require!(tier.name.len() <= MAX_TIER_NAME_LEN, ErrorCode::InvalidLoyaltyTiers);
+            require!(
+                tier.multiplier_bps > 0 && tier.multiplier_bps <= MAX_MULTIPLIER_BPS,
+                ErrorCode::InvalidLoyaltyTiers
+            );
+        }
+
+        ctx.accounts.loyalty_tiers.tiers = tiers;
+        ctx.accounts.loyalty_tiers.bump = ctx.bumps.loyalty_tiers;
+        Ok(())
+    }
+
+    // Per-wallet limits over a rolling day; 0 disables a limit
+    pub fn set_claim_limits(ctx: Context<SetClaimLimits>, max_claims_per_day: u32, max_tokens_per_day: u64) -> Result<()> {
+        let usv_state = &mut ctx.accounts.usv_state;
+        usv_state.max_claims_per_day = max_claims_per_day;
+        usv_state.max_tokens_per_day = max_tokens_per_day;
+        Ok(())
+    }
+
+    // Pause claims automatically once more than `max_claims` claims land in one bucket of
+    // `bucket_secs`, program-wide or within a single batch. 0 disables a threshold;
+    // `bucket_secs` 0 disables the breaker
+    pub fn set_circuit_breaker(
+        ctx: Context<SetCircuitBreaker>,
+        bucket_secs: u32,
+        max_claims: u32,
+        max_batch_claims: u32,
+    ) -> Result<()> {
+        let usv_state = &mut ctx.accounts.usv_state;
+        usv_state.breaker_bucket_secs = bucket_secs;
+        usv_state.breaker_max_claims = max_claims;
+        usv_state.breaker_max_batch_claims = max_batch_claims;
+        usv_state.reset_breaker();
+        Ok(())
+    }
+
+    // Exempt a wallet from claim limits, creating its profile if it has not claimed yet
+    pub fn set_trusted(ctx: Context<SetTrusted>, is_trusted: bool) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.open(ctx.accounts.wallet.key(), Clock::get()?.unix_timestamp, ctx.bumps.user_profile);
+        user_profile.is_trusted = is_trusted;
+        Ok(())
+    }
+
+    // Tokens paid to a referrer, from the authority's token account, when someone they
+    // referred makes a first claim; 0 disables
+    pub fn set_referral_bonus(ctx: Context<SetReferralBonus>, referral_bonus: u64) -> Result<()> {
+        ctx.accounts.usv_state.referral_bonus = referral_bonus;
+        Ok(())
+    }
+
+    // Get program statistics
+    pub fn get_stats(ctx: Context<GetStats>) -> Result<()> {
+        let usv_state = &ctx.accounts.usv_state;
+        
+        emit_cpi!(ProgramStats {
+            total_supply: usv_state.total_supply,
+            tokens_claimed: usv_state.tokens_claimed,
+            total_qr_codes: usv_state.total_qr_codes,
+            is_paused: usv_state.is_paused,
+            authority: usv_state.authority,
+        });
+
+        Ok(())
+    }
+}
+
+fn validate_batch_metadata(partner_id: &Option<String>, batch_info: &str) -> Result<()> {
+    require!(batch_info.len() <= MAX_BATCH_INFO_LEN, ErrorCode::BatchInfoTooLong);
+    if let Some(partner_id) = partner_id {
+        require!(partner_id.len() <= MAX_PARTNER_ID_LEN, ErrorCode::PartnerIdTooLong);
+    }
+    Ok(())
+}
+
+// Derive `count` codes into the batch's code region starting at `first_slot`.
+// `first_index` is the program-wide index of the first new code.
+fn derive_qr_codes(
+    qr_batch: &AccountLoader<QRBatch>,
+    first_slot: u32,
+    count: u32,
+    first_index: u32,
+    authority: &Pubkey,
+) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    let mut data = qr_batch.as_ref().try_borrow_mut_data()?;
+    let codes = &mut data[QRBatch::space(first_slot)..QRBatch::space(first_slot + count)];
+
+    for (i, qr_code) in codes.chunks_exact_mut(QR_CODE_LEN).enumerate() {
+        let mut hasher = Sha256::new();
+        hasher.update(b"USV");
+        hasher.update((first_index + i as u32).to_le_bytes());
+        hasher.update(timestamp.to_le_bytes());
+        hasher.update(authority.as_ref());
+        let hash = hasher.finalize();
+
+        qr_code.copy_from_slice(&hash[..QR_CODE_LEN]);
+    }
+    Ok(())
+}
+
+// Read the code at `index` without deserializing the rest of the batch
+fn read_qr_code(qr_batch: &AccountLoader<QRBatch>, index: u32) -> Result<Option<[u8; QR_CODE_LEN]>> {
+    if index >= qr_batch.load()?.count {
+        return Ok(None);
+    }
+    let data = qr_batch.as_ref().try_borrow_data()?;
+    let start = QRBatch::space(index);
+    let mut qr_code = [0u8; QR_CODE_LEN];
+    qr_code.copy_from_slice(&data[start..start + QR_CODE_LEN]);
+    Ok(Some(qr_code))
+}
+
+// Count a claim in the `bucket_secs` bucket containing `now`, starting a new
+// bucket if needed. Returns the bucket start and its claims including this one
+fn count_in_bucket(bucket_start: i64, bucket_claims: u32, now: i64, bucket_secs: u32) -> (i64, u32) {
+    let start = now - now.rem_euclid(bucket_secs as i64);
+    if bucket_start != start {
+        return (start, 1);
+    }
+    (start, bucket_claims.saturating_add(1))
+}
+
+// Copy a validated string into a fixed-size field, returning its length
+fn copy_str(dst: &mut [u8], src: &str) -> u8 {
+    dst[..src.len()].copy_from_slice(src.as_bytes());
+    src.len() as u8
+}
+
+fn read_str(src: &[u8], len: u8) -> String {
+    String::from_utf8_lossy(&src[..len as usize]).into_owned()
+}
+
+// State Accounts
+#[account]
+#[derive(Default, InitSpace)]
+pub struct USVState {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub total_supply: u64,
+    pub tokens_claimed: u64,
+    pub total_qr_codes: u32,
+    pub total_batches: u32,
+    pub is_paused: bool,
+    pub bump: u8,
+    pub mint_bump: u8,
+    // Fields added since the first release. New ones go at the end, so the released
+    // layout stays a prefix of this one and migrate_state only has to grow accounts
+    pub referral_bonus: u64,
+    pub referral_rewards_paid: u64,
+    pub max_claims_per_day: u32, // 0 = unlimited
+    pub max_tokens_per_day: u64, // 0 = unlimited
+    // Circuit breaker thresholds and the current global bucket
+    pub breaker_bucket_secs: u32, // 0 = disabled
+    pub breaker_max_claims: u32,
+    pub breaker_max_batch_claims: u32,
+    pub bucket_start: i64,
+    pub bucket_claims: u32,
+    pub breaker_epoch: u32, // Bumped to discard every batch's bucket
+    pub guardians: GuardianSet,
+    pub layout_version: u8, // USV_STATE_LAYOUT_VERSION once written by this program
+}
+
+// Account lengths of the USVState layouts that inserted fields ahead of `is_paused`
+// rather than appending them: the first release, then with referrals, claim limits,
+// the circuit breaker, guardians and the breaker epoch added in turn
+const LEGACY_STATE_LENS: [usize; 6] = [99, 115, 127, 151, 421, 425];
+
+// Parse a USVState in one of the layouts in LEGACY_STATE_LENS. Fields a layout
+// predates start out zeroed, which leaves their features disabled
+fn read_legacy_state(data: &[u8]) -> Result<USVState> {
+    let version = LEGACY_STATE_LENS.iter().position(|&len| len == data.len()).ok_or(ErrorCode::AlreadyMigrated)?;
+    let buf = &mut &data[8..];
+    let mut state = USVState {
+        authority: AnchorDeserialize::deserialize(buf)?,
+        mint: AnchorDeserialize::deserialize(buf)?,
+        total_supply: AnchorDeserialize::deserialize(buf)?,
+        tokens_claimed: AnchorDeserialize::deserialize(buf)?,
+        total_qr_codes: AnchorDeserialize::deserialize(buf)?,
+        total_batches: AnchorDeserialize::deserialize(buf)?,
+        ..Default::default()
+    };
+    if version >= 1 {
+        state.referral_bonus = AnchorDeserialize::deserialize(buf)?;
+        state.referral_rewards_paid = AnchorDeserialize::deserialize(buf)?;
+    }
+    if version >= 2 {
+        state.max_claims_per_day = AnchorDeserialize::deserialize(buf)?;
+        state.max_tokens_per_day = AnchorDeserialize::deserialize(buf)?;
+    }
+    if version >= 3 {
+        state.breaker_bucket_secs = AnchorDeserialize::deserialize(buf)?;
+        state.breaker_max_claims = AnchorDeserialize::deserialize(buf)?;
+        state.breaker_max_batch_claims = AnchorDeserialize::deserialize(buf)?;
+        state.bucket_start = AnchorDeserialize::deserialize(buf)?;
+        state.bucket_claims = AnchorDeserialize::deserialize(buf)?;
+    }
+    if version >= 5 {
+        state.breaker_epoch = AnchorDeserialize::deserialize(buf)?;
+    }
+    if version >= 4 {
+        state.guardians = AnchorDeserialize::deserialize(buf)?;
+    }
+    state.is_paused = AnchorDeserialize::deserialize(buf)?;
+    state.bump = AnchorDeserialize::deserialize(buf)?;
+    state.mint_bump = AnchorDeserialize::deserialize(buf)?;
+    state.layout_version = USV_STATE_LAYOUT_VERSION;
+    Ok(state)
+}
+
+impl USVState {
+    // Start every breaker bucket, program-wide and per batch, from zero
+    fn reset_breaker(&mut self) {
+        self.bucket_start = 0;
+        self.bucket_claims = 0;
+        self.breaker_epoch = self.breaker_epoch.wrapping_add(1);
+    }
+}
+
+// Fixed-size batch header, followed in the account by `count` raw 16-byte codes.
+// Loaded zero-copy so claims never deserialize the whole batch.
+#[account(zero_copy)]
+#[derive(InitSpace)]
+pub struct QRBatch {
+    pub authority: Pubkey,
+    pub created_at: i64,
+    pub batch_id: [u8; MAX_BATCH_ID_LEN],
+    pub partner_id: [u8; MAX_PARTNER_ID_LEN],
+    pub batch_info: [u8; MAX_BATCH_INFO_LEN],
+    pub count: u32,
+    pub batch_id_len: u8,
+    pub partner_id_len: u8, // 0 when the batch has no partner
+    pub batch_info_len: u8,
+    pub is_sealed: u8,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl QRBatch {
+    // Account space for a batch holding `count` codes; also the offset of code `count`
+    pub const fn space(count: u32) -> usize {
+        8 + QRBatch::INIT_SPACE + count as usize * QR_CODE_LEN
+    }
+
+    fn init(
+        &mut self,
+        authority: Pubkey,
+        partner_id: &Option<String>,
+        batch_info: &str,
+        bump: u8,
+    ) -> Result<()> {
+        let created_at = Clock::get()?.unix_timestamp;
+        self.authority = authority;
+        self.created_at = created_at;
+        self.batch_id_len = copy_str(&mut self.batch_id, &format!("BATCH_{}", created_at));
+        self.partner_id_len = copy_str(&mut self.partner_id, partner_id.as_deref().unwrap_or(""));
+        self.batch_info_len = copy_str(&mut self.batch_info, batch_info);
+        self.count = 0;
+        self.is_sealed = 0;
+        self.bump = bump;
+        Ok(())
+    }
+
+    pub fn batch_id(&self) -> String {
+        read_str(&self.batch_id, self.batch_id_len)
+    }
+
+    pub fn partner_id(&self) -> Option<String> {
+        (self.partner_id_len > 0).then(|| read_str(&self.partner_id, self.partner_id_len))
+    }
+
+    pub fn batch_info(&self) -> String {
+        read_str(&self.batch_info, self.batch_info_len)
+    }
+
+    pub fn is_sealed(&self) -> bool {
+        self.is_sealed != 0
+    }
+}
+
+// The header must have no implicit padding, each allocation step must fit in
+// one instruction, and a full batch in one account
+const _: () = assert!(QRBatch::INIT_SPACE == std::mem::size_of::<QRBatch>());
+const _: () = assert!(QRBatch::space(MAX_QR_CODES_PER_APPEND as u32) <= MAX_PERMITTED_DATA_INCREASE);
+const _: () = assert!(QRBatch::space(MAX_QR_CODES_PER_BATCH as u32) <= MAX_PERMITTED_DATA_LENGTH as usize);
+
+#[account]
+#[derive(InitSpace)]
+pub struct QRClaim {
+    pub qr_code: [u8; QR_CODE_LEN],
+    pub claimer: Pubkey,
+    pub claimed_at: i64,
+    #[max_len(MAX_USER_EMAIL_LEN)]
+    pub user_email: Option<String>,
+    pub is_claimed: bool,
+    pub bump: u8,
+}
+
+// Circuit breaker bucket for claims from one batch, created on its first claim
+// while the per-batch breaker is on. Kept out of the zero-copy batch header so
+// that layout stays fixed
+#[account]
+#[derive(InitSpace)]
+pub struct BatchBreaker {
+    pub bucket_start: i64,
+    pub bucket_claims: u32,
+    pub epoch: u32, // USVState.breaker_epoch the bucket was counted under
+    pub bump: u8,
+}
+
+// One per wallet, created on its first claim. `referrer` is fixed from then on
+#[account]
+#[derive(InitSpace)]
+pub struct Referral {
+    pub referee: Pubkey,
+    pub referrer: Option<Pubkey>,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+// Admin-configured loyalty tiers, ordered by `min_claims`
+#[account]
+#[derive(InitSpace)]
+pub struct LoyaltyTiers {
+    #[max_len(MAX_LOYALTY_TIERS)]
+    pub tiers: Vec<LoyaltyTier>,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LoyaltyTier {
+    #[max_len(MAX_TIER_NAME_LEN)]
+    pub name: String,
+    pub min_claims: u32,
+    pub multiplier_bps: u16,
+}
+
+impl LoyaltyTiers {
+    // Index of the highest tier reached after `claim_count` claims; 0 with no tiers
+    pub fn tier_for(&self, claim_count: u32) -> u8 {
+        self.tiers
+            .iter()
+            .rposition(|tier| tier.min_claims <= claim_count)
+            .unwrap_or(0) as u8
+    }
+
+    pub fn name(&self, tier: u8) -> String {
+        self.tiers.get(tier as usize).map(|tier| tier.name.clone()).unwrap_or_default()
+    }
+
+    // Reward for a claim made after `claim_count` earlier claims
+    pub fn reward(&self, claim_count: u32) -> Result<u64> {
+        let multiplier_bps = self
+            .tiers
+            .get(self.tier_for(claim_count) as usize)
+            .map_or(BASE_MULTIPLIER_BPS, |tier| tier.multiplier_bps);
+        let amount = BASE_CLAIM_AMOUNT as u128 * multiplier_bps as u128 / BASE_MULTIPLIER_BPS as u128;
+        u64::try_from(amount).map_err(|_| error!(ErrorCode::ArithmeticOverflow))
+    }
+}
+
+// One per wallet, created on its first claim
+#[account]
+#[derive(InitSpace)]
+pub struct UserProfile {
+    pub owner: Pubkey,
+    pub claim_count: u32,
+    pub lifetime_rewards: u64, // Claim rewards only; referral bonuses are tracked in USVState
+    pub tier: u8,
+    pub created_at: i64,
+    pub last_claim_at: i64,
+    pub is_trusted: bool, // Exempt from claim limits
+    // Claim limit counters for the current and previous CLAIM_LIMIT_WINDOW
+    pub window_start: i64,
+    pub window_claims: u32,
+    pub window_tokens: u64,
+    pub previous_window_claims: u32,
+    pub previous_window_tokens: u64,
+    pub bump: u8,
+}
+
+impl UserProfile {
+    // Set up a new profile; no-op if it already belongs to `owner`
+    fn open(&mut self, owner: Pubkey, now: i64, bump: u8) {
+        if self.owner == Pubkey::default() {
+            self.owner = owner;
+            self.created_at = now;
+            self.bump = bump;
+        }
+    }
+
+    // Move the window forward so that `now` falls inside the current one
+    fn roll_window(&mut self, now: i64) {
+        let elapsed = now - self.window_start;
+        if elapsed >= 2 * CLAIM_LIMIT_WINDOW {
+            self.previous_window_claims = 0;
+            self.previous_window_tokens = 0;
+        } else if elapsed >= CLAIM_LIMIT_WINDOW {
+            self.previous_window_claims = self.window_claims;
+            self.previous_window_tokens = self.window_tokens;
+        } else {
+            return;
+        }
+        self.window_claims = 0;
+        self.window_tokens = 0;
+        self.window_start = now - elapsed % CLAIM_LIMIT_WINDOW;
+    }
+
+    fn record_windowed_claim(&mut self, now: i64, amount: u64) -> Result<()> {
+        self.roll_window(now);
+        self.window_claims = self.window_claims.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.window_tokens = self.window_tokens.checked_add(amount).ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    // Claims and tokens over the last CLAIM_LIMIT_WINDOW seconds, counting the
+    // previous window in proportion to how much of it still overlaps
+    pub fn windowed_totals(&self, now: i64) -> (u128, u128) {
+        let elapsed = (now - self.window_start).clamp(0, CLAIM_LIMIT_WINDOW) as u128;
+        let overlap = CLAIM_LIMIT_WINDOW as u128 - elapsed;
+        let weigh = |previous: u128| previous * overlap / CLAIM_LIMIT_WINDOW as u128;
+        (
+            weigh(self.previous_window_claims as u128) + self.window_claims as u128,
+            weigh(self.previous_window_tokens as u128) + self.window_tokens as u128,
+        )
+    }
+}
+
+// Context Structs
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + USVState::INIT_SPACE,
+        seeds = [b"usv_state"],
+        bump
+    )]
+    pub usv_state: Account<'info, USVState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + LoyaltyTiers::INIT_SPACE,
+        seeds = [b"loyalty_tiers"],
+        bump
+    )]
+    pub loyalty_tiers: Box<Account<'info, LoyaltyTiers>>,
+
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = 6,
+        mint::authority = mint_authority,
+        seeds = [b"mint"],
+        bump
+    )]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA for mint authority
+    #[account(
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = authority
+    )]
+    pub authority_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(count: u32, partner_id: Option<String>, batch_info: String)]
+pub struct GenerateQRCodes<'info> {
+    #[account(
+        mut,
+        seeds = [b"usv_state"],
+        bump = usv_state.bump,
+        has_one = authority
+    )]
+    pub usv_state: Account<'info, USVState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = QRBatch::space(count),
+       // seeds = [b"qr_batch", authority.key().as_ref(), &Clock::get()?.unix_timestamp.to_le_bytes()],
+        seeds = [b"qr_batch", authority.key().as_ref(), &usv_state.total_batches.to_le_bytes()],
+        bump
+    )]
+    pub qr_batch: AccountLoader<'info, QRBatch>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"usv_state"],
+        bump = usv_state.bump,
+        has_one = authority
+    )]
+    pub usv_state: Account<'info, USVState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = QRBatch::space(0),
+        seeds = [b"qr_batch", authority.key().as_ref(), &usv_state.total_batches.to_le_bytes()],
+        bump
+    )]
+    pub qr_batch: AccountLoader<'info, QRBatch>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(count: u32)]
+pub struct AppendQRCodes<'info> {
+    #[account(
+        mut,
+        seeds = [b"usv_state"],
+        bump = usv_state.bump,
+        has_one = authority
+    )]
+    pub usv_state: Account<'info, USVState>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        // Grow from the current size rather than the loaded header so no borrow
+        // of the batch is held while it is reallocated
+        realloc = qr_batch.to_account_info().data_len() + count as usize * QR_CODE_LEN,
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub qr_batch: AccountLoader<'info, QRBatch>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SealBatch<'info> {
+    #[account(
+        seeds = [b"usv_state"],
+        bump = usv_state.bump,
+        has_one = authority
+    )]
+    pub usv_state: Account<'info, USVState>,
+
+    #[account(
+        mut,
+        has_one = authority
+    )]
+    pub qr_batch: AccountLoader<'info, QRBatch>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(qr_code: [u8; QR_CODE_LEN], code_index: u32, user_email: Option<String>, referrer: Option<Pubkey>)]
+pub struct ClaimTokens<'info> {
+    #[account(
+        mut,
+        seeds = [b"usv_state"],
+        bump = usv_state.bump
+    )]
+    pub usv_state: Account<'info, USVState>,
+
+    #[account(constraint = qr_batch.load()?.authority == usv_state.authority @ ErrorCode::Unauthorized)]
+    pub qr_batch: AccountLoader<'info, QRBatch>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + BatchBreaker::INIT_SPACE,
+        seeds = [b"batch_breaker", qr_batch.key().as_ref()],
+        bump
+    )]
+    // Required, and created by the first claim, only while the per-batch breaker is on
+    pub batch_breaker: Option<Box<Account<'info, BatchBreaker>>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + QRClaim::INIT_SPACE,
+        seeds = [b"qr_claim", qr_code.as_ref()],
+        bump
+    )]
+    pub qr_claim: Account<'info, QRClaim>,
+
+    #[account(mut)]
+    pub authority_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = claimer
+    )]
+    pub claimer_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Referral::INIT_SPACE,
+        seeds = [b"referral", claimer.key().as_ref()],
+        bump
+    )]
+    pub referral: Box<Account<'info, Referral>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + UserProfile::INIT_SPACE,
+        seeds = [b"user_profile", claimer.key().as_ref()],
+        bump
+    )]
+    pub user_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(seeds = [b"loyalty_tiers"], bump = loyalty_tiers.bump)]
+    pub loyalty_tiers: Box<Account<'info, LoyaltyTiers>>,
+
+    // The referrer's own referral record; required whenever `referrer` is set
+    #[account(
+        seeds = [b"referral", referrer.unwrap_or_default().as_ref()],
+        bump = referrer_referral.bump
+    )]
+    pub referrer_referral: Option<Box<Account<'info, Referral>>>,
+
+    // Receives the referral bonus; must belong to the referrer
+    #[account(mut)]
+    pub referrer_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == usv_state.authority
+    )]
+    pub authority: Signer<'info>,
+
+    /// CHECK: The wallet claiming tokens
+    pub claimer: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TransferToPartner<'info> {
+    #[account(
+        seeds = [b"usv_state"],
+        bump = usv_state.bump,
+        has_one = authority
+    )]
+    pub usv_state: Account<'info, USVState>,
+
+    #[account(mut)]
+    pub authority_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = partner
+    )]
+    pub partner_token_account: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: Partner wallet
+    pub partner: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseState<'info> {
+    #[account(
+        mut,
+        seeds = [b"usv_state"],
+        bump = usv_state.bump,
+        has_one = authority
+    )]
+    pub usv_state: Account<'info, USVState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateState<'info> {
+    /// CHECK: Still in an earlier layout, so `migrate_state` checks its discriminator and parses it
+    #[account(mut, seeds = [b"usv_state"], bump, owner = crate::ID)]
+    pub usv_state: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetLoyaltyTiers<'info> {
+    #[account(
+        seeds = [b"usv_state"],
+        bump = usv_state.bump,
+        has_one = authority
+    )]
+    pub usv_state: Account<'info, USVState>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + LoyaltyTiers::INIT_SPACE,
+        seeds = [b"loyalty_tiers"],
+        bump
+    )]
+    pub loyalty_tiers: Account<'info, LoyaltyTiers>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetClaimLimits<'info> {
+    #[account(
+        mut,
+        seeds = [b"usv_state"],
+        bump = usv_state.bump,
+        has_one = authority
+    )]
+    pub usv_state: Account<'info, USVState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardians<'info> {
+    #[account(
+        mut,
+        seeds = [b"usv_state"],
+        bump = usv_state.bump,
+        has_one = authority
+    )]
+    pub usv_state: Account<'info, USVState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct GuardianPause<'info> {
+    #[account(
+        mut,
+        seeds = [b"usv_state"],
+        bump = usv_state.bump
+    )]
+    pub usv_state: Account<'info, USVState>,
+
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCircuitBreaker<'info> {
+    #[account(
+        mut,
+        seeds = [b"usv_state"],
+        bump = usv_state.bump,
+        has_one = authority
+    )]
+    pub usv_state: Account<'info, USVState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetTrusted<'info> {
+    #[account(
+        seeds = [b"usv_state"],
+        bump = usv_state.bump,
+        has_one = authority
+    )]
+    pub usv_state: Account<'info, USVState>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + UserProfile::INIT_SPACE,
+        seeds = [b"user_profile", wallet.key().as_ref()],
+        bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    /// CHECK: Wallet the profile belongs to
+    pub wallet: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetReferralBonus<'info> {
+    #[account(
+        mut,
+        seeds = [b"usv_state"],
+        bump = usv_state.bump,
+        has_one = authority
+    )]
+    pub usv_state: Account<'info, USVState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct GetStats<'info> {
+    #[account(
+        seeds = [b"usv_state"],
+        bump = usv_state.bump
+    )]
+    pub usv_state: Account<'info, USVState>,
+}
+
+// Events
+#[event]
+pub struct QRCodesGenerated {
+    pub batch_id: String,
+    pub count: u32,
+    pub partner_id: Option<String>,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct BatchSealed {
+    pub batch_id: String,
+    pub count: u32,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensClaimed {
+    pub qr_code: [u8; QR_CODE_LEN],
+    pub claimer: Pubkey,
+    pub amount: u64,
+    pub user_email: Option<String>,
+    pub timestamp: i64,
+}
+
+// `paused` is set on the vote that reached the threshold
+#[event]
+pub struct GuardianPauseVoted {
+    pub guardian: Pubkey,
+    pub votes: u8,
+    pub threshold: u8,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+// `qr_batch` is None when the program-wide threshold tripped
+#[event]
+pub struct CircuitBreakerTripped {
+    pub qr_batch: Option<Pubkey>,
+    pub claims: u32,
+    pub bucket_start: i64,
+    pub bucket_secs: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TierChanged {
+    pub user: Pubkey,
+    pub old_tier: u8,
+    pub new_tier: u8,
+    pub tier_name: String,
+    pub claim_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferralRewarded {
+    pub referrer: Pubkey,
+    pub referee: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PartnerTransfer {
+    pub partner: Pubkey,
+    pub amount: u64,
+    pub partner_info: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProgramStats {
+    pub total_supply: u64,
+    pub tokens_claimed: u64,
+    pub total_qr_codes: u32,
+    pub is_paused: bool,
+    pub authority: Pubkey,
+}
+
+// Error Codes
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Program is currently paused")]
+    ProgramPaused,
+    #[msg("QR code has already been claimed")]
+    QRAlreadyClaimed,
+    #[msg("Minimum partner transfer is 1000 tokens")]
+    MinimumPartnerTransfer,
+    #[msg("Invalid QR code hash")]
+    InvalidQRHash,
+    #[msg("Unauthorized access")]
+    Unauthorized,
+    #[msg("QR code count must be between 1 and the maximum batch size")]
+    InvalidQRCodeCount,
+    #[msg("Partner ID exceeds maximum length")]
+    PartnerIdTooLong,
+    #[msg("Batch info exceeds maximum length")]
+    BatchInfoTooLong,
+    #[msg("User email exceeds maximum length")]
+    UserEmailTooLong,
+    #[msg("QR batch is sealed")]
+    BatchSealed,
+    #[msg("QR batch has reached its maximum size")]
+    BatchCapacityExceeded,
+    #[msg("A wallet cannot refer itself")]
+    SelfReferral,
+    #[msg("Referrer has not claimed yet")]
+    ReferrerNotFound,
+    #[msg("Referrer token account is missing or not owned by the referrer")]
+    InvalidReferrerAccount,
+    #[msg("Loyalty tiers must start at 0 claims, increase strictly and use valid names and multipliers")]
+    InvalidLoyaltyTiers,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Wallet has reached its daily claim limit")]
+    ClaimRateLimited,
+    #[msg("USV state is already in the current layout")]
+    AlreadyMigrated,
+    #[msg("The per-batch circuit breaker is on, so the claim needs the batch's breaker account")]
+    BatchBreakerRequired,
+}
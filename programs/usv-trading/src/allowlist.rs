@@ -0,0 +1,56 @@
+// programs/usv-trading/src/allowlist.rs - Merkle allowlists for sale rounds
+//
+// A leaf is the keccak hash of a wallet address and each parent is the keccak
+// hash of its two children in ascending order, so a proof is just the list of
+// siblings from the leaf up. A node without a sibling is carried up unchanged.
+// `root` and `proof` build trees off-chain; the program only calls `verify`.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+
+pub fn leaf(wallet: &Pubkey) -> [u8; 32] {
+    keccak::hashv(&[wallet.as_ref()]).0
+}
+
+// Whether `proof` links `leaf` to `root`
+pub fn verify(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    proof.iter().fold(leaf, |node, sibling| parent(node, *sibling)) == root
+}
+
+pub fn root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level.first().copied().unwrap_or_default()
+}
+
+// Siblings from `leaves[index]` up to the root
+pub fn proof(leaves: &[[u8; 32]], index: usize) -> Vec<[u8; 32]> {
+    let mut proof = Vec::new();
+    let (mut level, mut index) = (leaves.to_vec(), index);
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    proof
+}
+
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [a, b] => parent(*a, *b),
+            [a] => *a,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+fn parent(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    keccak::hashv(&[&low, &high]).0
+}
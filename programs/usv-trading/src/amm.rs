@@ -0,0 +1,88 @@
+// programs/usv-trading/src/amm.rs - Constant-product USV/SOL pool math
+//
+// The pool holds `reserve_sol` lamports and `reserve_usv` base units and keeps
+// their product from falling across a swap. Swap fees are charged on the input
+// and stay in the pool, so they accrue to liquidity providers. As in
+// pricing.rs, every amount is computed exactly in u128 and rounded once in the
+// pool's favour: outputs and minted LP round down, required deposits and fees
+// round up.
+
+use anchor_lang::prelude::*;
+
+use crate::ErrorCode;
+
+// LP units locked forever by the first deposit, so the pool can never be fully
+// drained and LP units can never be inflated to a price one unit can't buy
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+pub const MAX_FEE_BPS: u64 = 1_000;
+pub const LP_DECIMALS: u8 = 9;
+const BPS: u128 = 10_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub amount_out: u64,
+    pub fee: u64,
+    // How far the execution price falls short of the spot price
+    pub price_impact_bps: u64,
+}
+
+// Output for `amount_in` against the given reserves, after a `fee_bps` fee
+pub fn quote_swap(amount_in: u64, reserve_in: u64, reserve_out: u64, fee_bps: u64) -> Result<SwapQuote> {
+    require!(reserve_in > 0 && reserve_out > 0, ErrorCode::InsufficientLiquidity);
+
+    let fee = mul(amount_in as u128, fee_bps as u128)?.div_ceil(BPS);
+    let amount_in_after_fee = amount_in as u128 - fee;
+    let reserve_after = reserve_in as u128 + amount_in_after_fee;
+    let amount_out = mul(reserve_out as u128, amount_in_after_fee)? / reserve_after;
+
+    // Execution price over spot price is reserve_in / reserve_after
+    let price_impact_bps = mul(amount_in_after_fee, BPS)?.div_ceil(reserve_after);
+
+    Ok(SwapQuote {
+        amount_out: to_u64(amount_out)?,
+        fee: to_u64(fee)?,
+        price_impact_bps: to_u64(price_impact_bps)?,
+    })
+}
+
+// LP units for the first deposit, which sets the price; the provider receives
+// this less MINIMUM_LIQUIDITY
+pub fn initial_liquidity(sol_amount: u64, usv_amount: u64) -> Result<u64> {
+    let liquidity = to_u64(isqrt(sol_amount as u128 * usv_amount as u128))?;
+    require!(liquidity > MINIMUM_LIQUIDITY, ErrorCode::InsufficientLiquidity);
+    Ok(liquidity)
+}
+
+// USV that must accompany `sol_amount` to keep the pool's price
+pub fn usv_for_deposit(sol_amount: u64, reserve_sol: u64, reserve_usv: u64) -> Result<u64> {
+    require!(reserve_sol > 0, ErrorCode::InsufficientLiquidity);
+    to_u64(mul(sol_amount as u128, reserve_usv as u128)?.div_ceil(reserve_sol as u128))
+}
+
+// The share of `total` that `part` of `whole` is worth, rounded down; used both
+// for LP minted per deposit and for reserves paid out per LP unit burned
+pub fn pro_rata(part: u64, whole: u64, total: u64) -> Result<u64> {
+    require!(whole > 0, ErrorCode::InsufficientLiquidity);
+    to_u64(mul(part as u128, total as u128)? / whole as u128)
+}
+
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    let mut x = n;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+fn mul(a: u128, b: u128) -> Result<u128> {
+    a.checked_mul(b).ok_or_else(|| error!(ErrorCode::MathOverflow))
+}
+
+fn to_u64(value: u128) -> Result<u64> {
+    u64::try_from(value).map_err(|_| error!(ErrorCode::MathOverflow))
+}
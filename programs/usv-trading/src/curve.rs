@@ -0,0 +1,140 @@
+// programs/usv-trading/src/curve.rs - Bonding curve pricing
+//
+// A curve prices USV by how much has already been sold on it, starting at
+// `start_price_micros` USD per whole token. The cost of a purchase is the
+// integral of that price over the base units bought, in pico-dollars: a price
+// of one micro-dollar per whole token is one pico-dollar per base unit. As in
+// pricing.rs, costs round up and token amounts round down, so rounding always
+// favours the program.
+
+use anchor_lang::prelude::*;
+
+use crate::pricing::TOKEN_BASE_UNITS;
+use crate::ErrorCode;
+
+// Fixed-point scale for exponential growth factors
+const SCALE: u128 = 1_000_000_000;
+const BPS: u128 = 10_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum CurveKind {
+    // Price rises by `slope_micros` for every whole token sold
+    Linear { slope_micros: u64 },
+    // Price rises by `growth_bps` after every `step` base units sold
+    Exponential { growth_bps: u64, step: u64 },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct BondingCurve {
+    pub kind: CurveKind,
+    pub start_price_micros: u64,
+    // Most base units the curve will sell; 0 removes the cap
+    pub supply_cap: u64,
+}
+
+impl BondingCurve {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.start_price_micros > 0, ErrorCode::InvalidCurve);
+        if let CurveKind::Exponential { growth_bps, step } = self.kind {
+            require!(growth_bps > 0 && step > 0, ErrorCode::InvalidCurve);
+        }
+        Ok(())
+    }
+
+    // USD micros per whole token once `sold` base units have been sold, rounded down
+    pub fn price_micros(&self, sold: u64) -> Result<u64> {
+        let start = self.start_price_micros as u128;
+        let price = match self.kind {
+            CurveKind::Linear { slope_micros } => {
+                start + mul(slope_micros as u128, sold as u128)? / TOKEN_BASE_UNITS
+            }
+            CurveKind::Exponential { growth_bps, step } => {
+                mul(start, growth_factor(growth_bps, sold / step)?)? / SCALE
+            }
+        };
+        to_u64(price)
+    }
+
+    // Pico-dollars for the base units `sold..sold + amount`, rounded up
+    pub fn cost(&self, sold: u64, amount: u64) -> Result<u128> {
+        let start = self.start_price_micros as u128;
+        let (sold, amount) = (sold as u128, amount as u128);
+        match self.kind {
+            // start * amount + slope * ((sold + amount)^2 - sold^2) / 2
+            CurveKind::Linear { slope_micros } => {
+                let area = mul(mul(slope_micros as u128, amount)?, 2 * sold + amount)?;
+                add(mul(start, amount)?, area.div_ceil(2 * TOKEN_BASE_UNITS))
+            }
+            // The price is constant within a step, so the cost is the partial
+            // first and last steps plus a geometric series over the full ones
+            CurveKind::Exponential { growth_bps, step } => {
+                let step = step as u128;
+                let end = sold + amount;
+                let (first, last) = (sold / step, end / step);
+                let first_factor = growth_factor(growth_bps, to_u64(first)?)?;
+                let scaled = if first == last {
+                    mul(amount, first_factor)?
+                } else {
+                    let last_factor = growth_factor(growth_bps, to_u64(last)?)?;
+                    let after_first = growth_factor(growth_bps, to_u64(first + 1)?)?;
+                    // sum of factor^i for first < i < last, scaled
+                    let series = mul(last_factor.saturating_sub(after_first), BPS)?.div_ceil(growth_bps as u128);
+                    let head = mul((first + 1) * step - sold, first_factor)?;
+                    let tail = mul(end - last * step, last_factor)?;
+                    add(add(head, mul(step, series)?)?, tail)?
+                };
+                Ok(mul(scaled, start)?.div_ceil(SCALE))
+            }
+        }
+    }
+
+    // Base units that `value` pico-dollars buys once `sold` have been sold,
+    // rounded down; fails if that would take the curve past its supply cap
+    pub fn tokens_for_value(&self, sold: u64, value: u128) -> Result<u64> {
+        // The price never falls below the start price, which bounds the search
+        let mut high = (value / self.start_price_micros as u128).min((u64::MAX - sold) as u128) as u64;
+        let mut low = 0;
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if self.cost(sold, mid).is_ok_and(|cost| cost <= value) {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        require!(
+            self.supply_cap == 0 || sold + low <= self.supply_cap,
+            ErrorCode::CurveSoldOut
+        );
+        Ok(low)
+    }
+}
+
+// (1 + growth_bps / BPS)^exponent, scaled and rounded up
+fn growth_factor(growth_bps: u64, exponent: u64) -> Result<u128> {
+    let mut base = SCALE + growth_bps as u128 * SCALE / BPS;
+    let mut exponent = exponent;
+    let mut factor = SCALE;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            factor = mul(factor, base)?.div_ceil(SCALE);
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            base = mul(base, base)?.div_ceil(SCALE);
+        }
+    }
+    Ok(factor)
+}
+
+fn mul(a: u128, b: u128) -> Result<u128> {
+    a.checked_mul(b).ok_or_else(|| error!(ErrorCode::MathOverflow))
+}
+
+fn add(a: u128, b: u128) -> Result<u128> {
+    a.checked_add(b).ok_or_else(|| error!(ErrorCode::MathOverflow))
+}
+
+fn to_u64(value: u128) -> Result<u64> {
+    u64::try_from(value).map_err(|_| error!(ErrorCode::MathOverflow))
+}
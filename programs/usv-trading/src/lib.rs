@@ -0,0 +1,2568 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+
+pub mod allowlist;
+pub mod amm;
+pub mod curve;
+pub mod oracle;
+pub mod pricing;
+pub mod vesting;
+
+use curve::BondingCurve;
+use oracle::PriceUpdateV2;
+use vesting::{VestingSchedule, VestingTranche};
+
+declare_id!("DT43tfD1z2RvbocvkU2dc2a3XNrSpk8UKcxAtQ8xe5VP");
+
+// Oracle defaults until the authority calls `update_oracle_config`
+pub const DEFAULT_MAX_PRICE_AGE_SECS: u64 = 60;
+pub const DEFAULT_MAX_CONFIDENCE_BPS: u64 = 200;
+
+// Length of a redemption cap window
+pub const REDEMPTION_WINDOW: i64 = 86400;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+// Guardian pause votes are counted by the shared usv-guardians crate
+pub use usv_guardians::{GuardianSet, GUARDIAN_VOTE_WINDOW, MAX_GUARDIANS};
+
+#[program]
+pub mod usv_trading {
+    use super::*;
+
+    // Initialize trading contract; `price_feed_id` is the Pyth SOL/USD feed.
+    // Tokens are sold from the inventory vault, which is stocked by transferring
+    // USV into it, and SOL proceeds go to `treasury`. Vesting purchases are held
+    // in the vesting vault until released
+    pub fn initialize_trading(ctx: Context<InitializeTrading>, price_feed_id: [u8; 32]) -> Result<()> {
+        let trading_state = &mut ctx.accounts.trading_state;
+        
+        trading_state.authority = ctx.accounts.authority.key();
+        trading_state.usv_mint = ctx.accounts.usv_mint.key();
+        trading_state.treasury = ctx.accounts.treasury.key();
+        trading_state.fixed_price_cents = 20; // 20 cents USD
+        trading_state.price_feed_id = price_feed_id;
+        trading_state.max_price_age_secs = DEFAULT_MAX_PRICE_AGE_SECS;
+        trading_state.max_confidence_bps = DEFAULT_MAX_CONFIDENCE_BPS;
+        trading_state.is_active = true;
+        trading_state.total_sales_volume = 0;
+        trading_state.total_purchases = 0;
+        trading_state.guardians = GuardianSet::default();
+        trading_state.bump = ctx.bumps.trading_state;
+        trading_state.inventory_bump = ctx.bumps.inventory;
+        trading_state.vesting_vault_bump = ctx.bumps.vesting_vault;
+
+        msg!("USV Trading contract initialized with fixed price: {} cents", trading_state.fixed_price_cents);
+        Ok(())
+    }
+
+    // Token purchase paid in SOL at the oracle price, priced on the bonding curve
+    // if one is set and at `fixed_price_cents` per token otherwise. Fails rather
+    // than delivering fewer than `min_tokens_out`, or after `deadline`. Purchases
+    // over the vesting threshold go to the buyer's vesting escrow
+    pub fn buy_tokens(
+        ctx: Context<BuyTokens>,
+        sol_amount: u64, // Amount in lamports
+        min_tokens_out: u64,
+        deadline: i64, // Unix timestamp
+    ) -> Result<()> {
+        require!(ctx.accounts.trading_state.is_active, ErrorCode::TradingPaused);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= deadline, ErrorCode::DeadlineExceeded);
+        let sale_round = ctx.accounts.sale_round.as_deref().map(|round| &**round);
+        ctx.accounts.trading_state.check_no_live_round(sale_round, now)?;
+        
+        let trading_state = &mut ctx.accounts.trading_state;
+        
+        // SOL/USD from the configured Pyth feed
+        let price = PriceUpdateV2::load(&ctx.accounts.price_update)?.price(
+            &trading_state.price_feed_id,
+            now,
+            trading_state.max_price_age_secs,
+            trading_state.max_confidence_bps,
+        )?;
+
+        // Rounded down; see pricing.rs and curve.rs
+        let token_amount = match trading_state.curve {
+            Some(curve) => curve.tokens_for_value(
+                trading_state.curve_sold,
+                pricing::value_of_lamports(sol_amount, price)?,
+            )?,
+            None => pricing::tokens_for_lamports(sol_amount, price, trading_state.fixed_price_cents)?,
+        };
+        
+        require!(token_amount > 0, ErrorCode::InsufficientPayment);
+        require!(token_amount >= min_tokens_out, ErrorCode::SlippageExceeded);
+        trading_state.record_sale(token_amount)?;
+        let vesting = if trading_state.vests(token_amount, false) {
+            let escrow = ctx.accounts.vesting_escrow.as_mut().ok_or(ErrorCode::VestingEscrowRequired)?;
+            Some(escrow.lock(
+                ctx.accounts.buyer.key(),
+                ctx.bumps.vesting_escrow,
+                &ctx.accounts.trading_state,
+                token_amount,
+                now,
+            )?)
+        } else {
+            None
+        };
+
+        // Transfer SOL from buyer to treasury
+        let ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.buyer.key(),
+            &ctx.accounts.treasury.key(),
+            sol_amount,
+        );
+        anchor_lang::solana_program::program::invoke(
+            &ix,
+            &[
+                ctx.accounts.buyer.to_account_info(),
+                ctx.accounts.treasury.to_account_info(),
+            ],
+        )?;
+
+        // Transfer USV tokens from the inventory to buyer, or to the vesting vault
+        let destination: &Account<TokenAccount> = if vesting.is_some() {
+            &ctx.accounts.vesting_vault
+        } else {
+            &ctx.accounts.buyer_token_account
+        };
+        release_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.inventory,
+            destination,
+            &ctx.accounts.trading_state,
+            token_amount,
+        )?;
+        let trading_state = &mut ctx.accounts.trading_state;
+
+        // Update trading statistics
+        trading_state.total_sales_volume += sol_amount;
+        trading_state.total_purchases += 1;
+
+        emit_cpi!(TokenPurchase {
+            buyer: ctx.accounts.buyer.key(),
+            sol_amount,
+            token_amount,
+            timestamp: now,
+        });
+        if let Some((tranche, locked)) = vesting {
+            emit_cpi!(TokensVested {
+                buyer: ctx.accounts.buyer.key(),
+                token_amount,
+                locked,
+                cliff_time: tranche.cliff_time(),
+                end_time: tranche.end_time(),
+                timestamp: now,
+            });
+        }
+
+        Ok(())
+    }
+
+    // Token purchase priced like `buy_tokens`, paid in a whitelisted SPL token
+    // valued at its configured USD price and deposited into its treasury; it
+    // vests like a `buy_tokens` purchase
+    pub fn buy_tokens_with_spl(
+        ctx: Context<BuyTokensWithSpl>,
+        payment_amount: u64, // Payment token base units
+        min_tokens_out: u64,
+        deadline: i64, // Unix timestamp
+    ) -> Result<()> {
+        require!(ctx.accounts.trading_state.is_active, ErrorCode::TradingPaused);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= deadline, ErrorCode::DeadlineExceeded);
+        let sale_round = ctx.accounts.sale_round.as_deref().map(|round| &**round);
+        ctx.accounts.trading_state.check_no_live_round(sale_round, now)?;
+
+        let payment_config = &ctx.accounts.payment_config;
+        require!(payment_config.is_enabled, ErrorCode::PaymentMintDisabled);
+
+        // Rounded down; see pricing.rs and curve.rs
+        let trading_state = &mut ctx.accounts.trading_state;
+        let token_amount = match trading_state.curve {
+            Some(curve) => curve.tokens_for_value(
+                trading_state.curve_sold,
+                pricing::value_of_payment(payment_amount, payment_config.decimals, payment_config.usd_price_micros)?,
+            )?,
+            None => pricing::tokens_for_payment(
+                payment_amount,
+                payment_config.decimals,
+                payment_config.usd_price_micros,
+                trading_state.fixed_price_cents,
+            )?,
+        };
+        require!(token_amount > 0, ErrorCode::InsufficientPayment);
+        require!(token_amount >= min_tokens_out, ErrorCode::SlippageExceeded);
+        trading_state.record_sale(token_amount)?;
+        let vesting = if trading_state.vests(token_amount, false) {
+            let escrow = ctx.accounts.vesting_escrow.as_mut().ok_or(ErrorCode::VestingEscrowRequired)?;
+            Some(escrow.lock(
+                ctx.accounts.buyer.key(),
+                ctx.bumps.vesting_escrow,
+                &ctx.accounts.trading_state,
+                token_amount,
+                now,
+            )?)
+        } else {
+            None
+        };
+
+        // Transfer the payment from buyer to treasury
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.buyer_payment_account.to_account_info(),
+            to: ctx.accounts.treasury.to_account_info(),
+            authority: ctx.accounts.buyer.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_ctx, payment_amount)?;
+
+        // Transfer USV tokens from the inventory to buyer, or to the vesting vault
+        let destination: &Account<TokenAccount> = if vesting.is_some() {
+            &ctx.accounts.vesting_vault
+        } else {
+            &ctx.accounts.buyer_token_account
+        };
+        release_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.inventory,
+            destination,
+            &ctx.accounts.trading_state,
+            token_amount,
+        )?;
+
+        let payment_config = &mut ctx.accounts.payment_config;
+        payment_config.total_received = payment_config
+            .total_received
+            .checked_add(payment_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        ctx.accounts.trading_state.total_purchases += 1;
+
+        emit_cpi!(SplTokenPurchase {
+            buyer: ctx.accounts.buyer.key(),
+            payment_mint: ctx.accounts.payment_mint.key(),
+            payment_amount,
+            token_amount,
+            timestamp: now,
+        });
+        if let Some((tranche, locked)) = vesting {
+            emit_cpi!(TokensVested {
+                buyer: ctx.accounts.buyer.key(),
+                token_amount,
+                locked,
+                cliff_time: tranche.cliff_time(),
+                end_time: tranche.end_time(),
+                timestamp: now,
+            });
+        }
+
+        Ok(())
+    }
+
+    // Redeem `token_amount` USV back into the sale inventory for SOL from the
+    // reserve, at `bid_price_cents` per token and the oracle SOL/USD price
+    pub fn sell_tokens(
+        ctx: Context<SellTokens>,
+        token_amount: u64,
+        min_lamports_out: u64,
+        deadline: i64, // Unix timestamp
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let trading_state = &mut ctx.accounts.trading_state;
+        trading_state.check_redemption(now, deadline)?;
+
+        let price = PriceUpdateV2::load(&ctx.accounts.price_update)?.price(
+            &trading_state.price_feed_id,
+            now,
+            trading_state.max_price_age_secs,
+            trading_state.max_confidence_bps,
+        )?;
+
+        // Rounded down; see pricing.rs
+        let lamports = pricing::lamports_for_tokens(token_amount, price, trading_state.bid_price_cents)?;
+        require!(lamports > 0, ErrorCode::InsufficientPayment);
+        require!(lamports >= min_lamports_out, ErrorCode::SlippageExceeded);
+
+        // The reserve keeps its rent-exempt minimum and must still cover the
+        // tokens left in circulation
+        let reserve = ctx.accounts.sol_reserve.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+        let remaining = reserve.checked_sub(lamports).ok_or(ErrorCode::InsufficientReserve)?;
+        let outstanding = outstanding_after(
+            &ctx.accounts.usv_mint,
+            &[&ctx.accounts.inventory, &ctx.accounts.claim_vault, &ctx.accounts.vesting_vault],
+            &ctx.accounts.pool_usv,
+            token_amount,
+        )?;
+        let liability = pricing::lamports_for_tokens(outstanding, price, trading_state.bid_price_cents)?;
+        require!(
+            trading_state.reserve_covers(remaining, liability),
+            ErrorCode::InsufficientReserve
+        );
+        trading_state.record_redemption(now, token_amount)?;
+
+        return_to_inventory(
+            &ctx.accounts.token_program,
+            &ctx.accounts.seller_token_account,
+            &ctx.accounts.inventory,
+            &ctx.accounts.seller,
+            token_amount,
+        )?;
+
+        // Pay the seller from the reserve PDA
+        let ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.sol_reserve.key(),
+            &ctx.accounts.seller.key(),
+            lamports,
+        );
+        anchor_lang::solana_program::program::invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.sol_reserve.to_account_info(),
+                ctx.accounts.seller.to_account_info(),
+            ],
+            &[&[b"sol_reserve", &[ctx.bumps.sol_reserve]]],
+        )?;
+
+        emit_cpi!(TokenRedemption {
+            seller: ctx.accounts.seller.key(),
+            payout_mint: None,
+            token_amount,
+            payout_amount: lamports,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    // Redeem `token_amount` USV back into the sale inventory for a whitelisted
+    // SPL token from its reserve, at `bid_price_cents` per token
+    pub fn sell_tokens_for_spl(
+        ctx: Context<SellTokensForSpl>,
+        token_amount: u64,
+        min_payout: u64,
+        deadline: i64, // Unix timestamp
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let payment_config = &ctx.accounts.payment_config;
+        require!(payment_config.is_enabled, ErrorCode::PaymentMintDisabled);
+        let trading_state = &mut ctx.accounts.trading_state;
+        trading_state.check_redemption(now, deadline)?;
+
+        // Rounded down; see pricing.rs
+        let payout = pricing::payment_for_tokens(
+            token_amount,
+            payment_config.decimals,
+            payment_config.usd_price_micros,
+            trading_state.bid_price_cents,
+        )?;
+        require!(payout > 0, ErrorCode::InsufficientPayment);
+        require!(payout >= min_payout, ErrorCode::SlippageExceeded);
+
+        let remaining = ctx.accounts.reserve.amount.checked_sub(payout).ok_or(ErrorCode::InsufficientReserve)?;
+        let outstanding = outstanding_after(
+            &ctx.accounts.usv_mint,
+            &[&ctx.accounts.inventory, &ctx.accounts.claim_vault, &ctx.accounts.vesting_vault],
+            &ctx.accounts.pool_usv,
+            token_amount,
+        )?;
+        let liability = pricing::payment_for_tokens(
+            outstanding,
+            payment_config.decimals,
+            payment_config.usd_price_micros,
+            trading_state.bid_price_cents,
+        )?;
+        require!(
+            trading_state.reserve_covers(remaining, liability),
+            ErrorCode::InsufficientReserve
+        );
+        trading_state.record_redemption(now, token_amount)?;
+
+        return_to_inventory(
+            &ctx.accounts.token_program,
+            &ctx.accounts.seller_token_account,
+            &ctx.accounts.inventory,
+            &ctx.accounts.seller,
+            token_amount,
+        )?;
+
+        // Pay the seller from the reserve, which the trading state owns
+        let seeds: &[&[u8]] = &[b"trading_state", &[ctx.accounts.trading_state.bump]];
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.reserve.to_account_info(),
+            to: ctx.accounts.seller_payment_account.to_account_info(),
+            authority: ctx.accounts.trading_state.to_account_info(),
+        };
+        let signer_seeds = &[seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, payout)?;
+
+        emit_cpi!(TokenRedemption {
+            seller: ctx.accounts.seller.key(),
+            payout_mint: Some(ctx.accounts.payment_mint.key()),
+            token_amount,
+            payout_amount: payout,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    // Admin functions
+    pub fn update_fixed_price(
+        ctx: Context<UpdateFixedPrice>,
+        new_price_cents: u64,
+    ) -> Result<()> {
+        require!(new_price_cents > 0, ErrorCode::InvalidPrice);
+        require!(
+            new_price_cents >= ctx.accounts.trading_state.bid_price_cents,
+            ErrorCode::BidAboveAsk
+        );
+        let old_price = ctx.accounts.trading_state.fixed_price_cents;
+        ctx.accounts.trading_state.fixed_price_cents = new_price_cents;
+        
+        emit_cpi!(PriceUpdated {
+            old_price,
+            new_price: new_price_cents,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    // Point purchases at another Pyth feed or change how fresh and precise its price must be
+    pub fn update_oracle_config(
+        ctx: Context<UpdateOracleConfig>,
+        price_feed_id: [u8; 32],
+        max_price_age_secs: u64,
+        max_confidence_bps: u64,
+    ) -> Result<()> {
+        let trading_state = &mut ctx.accounts.trading_state;
+        trading_state.price_feed_id = price_feed_id;
+        trading_state.max_price_age_secs = max_price_age_secs;
+        trading_state.max_confidence_bps = max_confidence_bps;
+        Ok(())
+    }
+
+    // Whitelist a payment token. `usd_price_micros` is the USD value of one whole
+    // token in millionths (1_000_000 for a dollar stablecoin); payments go to `treasury`
+    pub fn add_payment_mint(ctx: Context<AddPaymentMint>, usd_price_micros: u64) -> Result<()> {
+        require!(usd_price_micros > 0, ErrorCode::InvalidPrice);
+        let payment_config = &mut ctx.accounts.payment_config;
+        payment_config.mint = ctx.accounts.payment_mint.key();
+        payment_config.decimals = ctx.accounts.payment_mint.decimals;
+        payment_config.usd_price_micros = usd_price_micros;
+        payment_config.treasury = ctx.accounts.treasury.key();
+        payment_config.is_enabled = true;
+        payment_config.total_received = 0;
+        payment_config.bump = ctx.bumps.payment_config;
+        Ok(())
+    }
+
+    pub fn update_payment_mint(
+        ctx: Context<UpdatePaymentMint>,
+        usd_price_micros: u64,
+        is_enabled: bool,
+    ) -> Result<()> {
+        require!(usd_price_micros > 0, ErrorCode::InvalidPrice);
+        let payment_config = &mut ctx.accounts.payment_config;
+        payment_config.usd_price_micros = usd_price_micros;
+        payment_config.treasury = ctx.accounts.treasury.key();
+        payment_config.is_enabled = is_enabled;
+        Ok(())
+    }
+
+    // Redemption terms. A `bid_price_cents` of 0 disables redemption and a
+    // `max_redeemed_per_day` of 0 removes the cap; `min_reserve_ratio_bps` of the
+    // bid value of the tokens still in circulation must stay in each reserve
+    // after a redemption. Tokens in `claim_vault` still await claims, so they
+    // do not count as in circulation
+    pub fn set_redemption_config(
+        ctx: Context<SetRedemptionConfig>,
+        bid_price_cents: u64,
+        max_redeemed_per_day: u64,
+        min_reserve_ratio_bps: u64,
+    ) -> Result<()> {
+        let trading_state = &mut ctx.accounts.trading_state;
+        trading_state.claim_vault = ctx.accounts.claim_vault.key();
+        trading_state.bid_price_cents = bid_price_cents;
+        trading_state.check_bid()?;
+        trading_state.max_redeemed_per_day = max_redeemed_per_day;
+        trading_state.min_reserve_ratio_bps = min_reserve_ratio_bps;
+        Ok(())
+    }
+
+    // Price purchases on `curve` from its start price, or at `fixed_price_cents`
+    // again if None. Setting a curve restarts it, and the bid must not exceed
+    // its start price
+    pub fn set_bonding_curve(ctx: Context<SetBondingCurve>, curve: Option<BondingCurve>) -> Result<()> {
+        if let Some(curve) = curve {
+            curve.validate()?;
+        }
+        let trading_state = &mut ctx.accounts.trading_state;
+        trading_state.curve = curve;
+        trading_state.curve_sold = 0;
+        trading_state.check_bid()
+    }
+
+    // Purchases of more than `threshold` base units (0 for none) and purchases
+    // in vesting rounds are locked in the buyer's vesting escrow, unlocking
+    // linearly over `duration_secs` once `cliff_secs` have passed. Changes
+    // apply from each escrow's next deposit
+    pub fn set_vesting_config(
+        ctx: Context<SetVestingConfig>,
+        threshold: u64,
+        cliff_secs: i64,
+        duration_secs: i64,
+    ) -> Result<()> {
+        require!(
+            cliff_secs >= 0 && cliff_secs <= duration_secs && duration_secs > 0,
+            ErrorCode::InvalidVestingConfig
+        );
+        let trading_state = &mut ctx.accounts.trading_state;
+        trading_state.vesting_threshold = threshold;
+        trading_state.vesting_cliff_secs = cliff_secs;
+        trading_state.vesting_duration_secs = duration_secs;
+        Ok(())
+    }
+
+    // Send the buyer everything in their vesting escrow that has unlocked
+    pub fn release_vested(ctx: Context<ReleaseVested>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let token_amount = ctx.accounts.vesting_escrow.schedule.release(now);
+        require!(token_amount > 0, ErrorCode::NothingToRelease);
+
+        release_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vesting_vault,
+            &ctx.accounts.buyer_token_account,
+            &ctx.accounts.trading_state,
+            token_amount,
+        )?;
+
+        emit_cpi!(VestedTokensReleased {
+            buyer: ctx.accounts.buyer.key(),
+            token_amount,
+            locked: ctx.accounts.vesting_escrow.schedule.locked(now),
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    // Where SOL proceeds from purchases are sent
+    pub fn set_treasury(ctx: Context<SetTreasury>) -> Result<()> {
+        ctx.accounts.trading_state.treasury = ctx.accounts.treasury.key();
+        Ok(())
+    }
+
+    // Open a presale round after the existing ones. Rounds sell at `price_cents`
+    // per token from `start_time` to `end_time` (Unix timestamps), at most `cap`
+    // base units in total and `per_wallet_cap` to each wallet (0 for no limit),
+    // only to wallets in `allowlist_root` if set. If `vests`, every purchase in
+    // the round goes to the buyer's vesting escrow
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_sale_round(
+        ctx: Context<AddSaleRound>,
+        start_time: i64,
+        end_time: i64,
+        price_cents: u64,
+        cap: u64,
+        per_wallet_cap: u64,
+        allowlist_root: Option<[u8; 32]>,
+        vests: bool,
+    ) -> Result<()> {
+        let trading_state = &mut ctx.accounts.trading_state;
+        let sale_round = &mut ctx.accounts.sale_round;
+        sale_round.index = trading_state.round_count;
+        sale_round.sold = 0;
+        sale_round.bump = ctx.bumps.sale_round;
+        sale_round.set_terms(start_time, end_time, price_cents, cap, per_wallet_cap, allowlist_root, vests)?;
+        require!(price_cents >= trading_state.bid_price_cents, ErrorCode::BidAboveAsk);
+        require!(!vests || trading_state.vesting_duration_secs > 0, ErrorCode::InvalidVestingConfig);
+        trading_state.round_count += 1;
+        Ok(())
+    }
+
+    // Change the terms of the current or a later round
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_sale_round(
+        ctx: Context<UpdateSaleRound>,
+        start_time: i64,
+        end_time: i64,
+        price_cents: u64,
+        cap: u64,
+        per_wallet_cap: u64,
+        allowlist_root: Option<[u8; 32]>,
+        vests: bool,
+    ) -> Result<()> {
+        let trading_state = &ctx.accounts.trading_state;
+        let sale_round = &mut ctx.accounts.sale_round;
+        sale_round.set_terms(start_time, end_time, price_cents, cap, per_wallet_cap, allowlist_root, vests)?;
+        require!(cap >= sale_round.sold, ErrorCode::InvalidSaleRound);
+        require!(price_cents >= trading_state.bid_price_cents, ErrorCode::BidAboveAsk);
+        require!(!vests || trading_state.vesting_duration_secs > 0, ErrorCode::InvalidVestingConfig);
+        Ok(())
+    }
+
+    // Buy in the current sale round, paid in SOL at the oracle price. A purchase
+    // larger than what is left in the round or the buyer's allocation is filled
+    // up to that and charged only for what is delivered; selling the round out
+    // opens the next one. `proof` links the buyer to the round's allowlist.
+    // Purchases vest if the round does or they are over the vesting threshold
+    pub fn buy_in_round(
+        ctx: Context<BuyInRound>,
+        sol_amount: u64, // Amount in lamports
+        min_tokens_out: u64,
+        deadline: i64, // Unix timestamp
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(ctx.accounts.trading_state.is_active, ErrorCode::TradingPaused);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= deadline, ErrorCode::DeadlineExceeded);
+
+        let buyer = ctx.accounts.buyer.key();
+        let sale_round = &ctx.accounts.sale_round;
+        require!(
+            now >= sale_round.start_time && now <= sale_round.end_time,
+            ErrorCode::SaleRoundClosed
+        );
+        if let Some(root) = sale_round.allowlist_root {
+            require!(
+                allowlist::verify(&proof, root, allowlist::leaf(&buyer)),
+                ErrorCode::NotAllowlisted
+            );
+        }
+
+        let trading_state = &ctx.accounts.trading_state;
+        let price = PriceUpdateV2::load(&ctx.accounts.price_update)?.price(
+            &trading_state.price_feed_id,
+            now,
+            trading_state.max_price_age_secs,
+            trading_state.max_confidence_bps,
+        )?;
+
+        // Rounded down, and the cost of a partial fill rounded up; see pricing.rs
+        let quoted = pricing::tokens_for_lamports(sol_amount, price, sale_round.price_cents)?;
+        let available = sale_round.available_to(ctx.accounts.round_buyer.purchased);
+        require!(available > 0, ErrorCode::SaleRoundSoldOut);
+        let token_amount = quoted.min(available);
+        let lamports = if token_amount < quoted {
+            pricing::lamports_for_purchase(token_amount, price, sale_round.price_cents)?
+        } else {
+            sol_amount
+        };
+        require!(token_amount > 0, ErrorCode::InsufficientPayment);
+        require!(token_amount >= min_tokens_out, ErrorCode::SlippageExceeded);
+
+        let vesting = if trading_state.vests(token_amount, sale_round.vests) {
+            let escrow = ctx.accounts.vesting_escrow.as_mut().ok_or(ErrorCode::VestingEscrowRequired)?;
+            Some(escrow.lock(buyer, ctx.bumps.vesting_escrow, &ctx.accounts.trading_state, token_amount, now)?)
+        } else {
+            None
+        };
+
+        let ix = anchor_lang::solana_program::system_instruction::transfer(
+            &buyer,
+            &ctx.accounts.treasury.key(),
+            lamports,
+        );
+        anchor_lang::solana_program::program::invoke(
+            &ix,
+            &[
+                ctx.accounts.buyer.to_account_info(),
+                ctx.accounts.treasury.to_account_info(),
+            ],
+        )?;
+        let destination: &Account<TokenAccount> = if vesting.is_some() {
+            &ctx.accounts.vesting_vault
+        } else {
+            &ctx.accounts.buyer_token_account
+        };
+        release_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.inventory,
+            destination,
+            &ctx.accounts.trading_state,
+            token_amount,
+        )?;
+
+        let round_buyer = &mut ctx.accounts.round_buyer;
+        round_buyer.buyer = buyer;
+        round_buyer.purchased += token_amount;
+        round_buyer.bump = ctx.bumps.round_buyer;
+        let sale_round = &mut ctx.accounts.sale_round;
+        sale_round.sold += token_amount;
+        let (index, sold, sold_out) = (sale_round.index, sale_round.sold, sale_round.sold == sale_round.cap);
+        let trading_state = &mut ctx.accounts.trading_state;
+        trading_state.total_sales_volume += lamports;
+        trading_state.total_purchases += 1;
+        if sold_out {
+            trading_state.current_round = index + 1;
+        }
+
+        emit_cpi!(TokenPurchase {
+            buyer,
+            sol_amount: lamports,
+            token_amount,
+            timestamp: now,
+        });
+        if let Some((tranche, locked)) = vesting {
+            emit_cpi!(TokensVested {
+                buyer,
+                token_amount,
+                locked,
+                cliff_time: tranche.cliff_time(),
+                end_time: tranche.end_time(),
+                timestamp: now,
+            });
+        }
+        if sold_out {
+            emit_cpi!(SaleRoundAdvanced {
+                from_round: index,
+                to_round: index + 1,
+                sold,
+                sold_out,
+                timestamp: now,
+            });
+        }
+
+        Ok(())
+    }
+
+    // Move past the current sale round once it has ended or sold out; anyone may call this
+    pub fn advance_sale_round(ctx: Context<AdvanceSaleRound>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let sale_round = &ctx.accounts.sale_round;
+        let sold_out = sale_round.sold == sale_round.cap;
+        require!(now > sale_round.end_time || sold_out, ErrorCode::SaleRoundOpen);
+
+        ctx.accounts.trading_state.current_round = sale_round.index + 1;
+        emit_cpi!(SaleRoundAdvanced {
+            from_round: sale_round.index,
+            to_round: sale_round.index + 1,
+            sold: sale_round.sold,
+            sold_out,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    // Move unsold tokens out of the inventory vault
+    pub fn withdraw_inventory(ctx: Context<WithdrawInventory>, amount: u64) -> Result<()> {
+        release_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.inventory,
+            &ctx.accounts.destination,
+            &ctx.accounts.trading_state,
+            amount,
+        )
+    }
+
+    // Create the USV/SOL pool. Swaps pay `fee_bps` of their input to liquidity
+    // providers and may move the price by at most `max_price_impact_bps`
+    pub fn initialize_pool(ctx: Context<InitializePool>, fee_bps: u64, max_price_impact_bps: u64) -> Result<()> {
+        validate_pool_config(fee_bps, max_price_impact_bps)?;
+
+        // The SOL vault's rent-exempt minimum sits outside the reserves, so
+        // withdrawals can never leave it below rent
+        let rent = Rent::get()?.minimum_balance(0);
+        let top_up = rent.saturating_sub(ctx.accounts.pool_sol.lamports());
+        if top_up > 0 {
+            let ix = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.authority.key(),
+                &ctx.accounts.pool_sol.key(),
+                top_up,
+            );
+            anchor_lang::solana_program::program::invoke(
+                &ix,
+                &[
+                    ctx.accounts.authority.to_account_info(),
+                    ctx.accounts.pool_sol.to_account_info(),
+                ],
+            )?;
+        }
+
+        let pool = &mut ctx.accounts.pool;
+        pool.usv_mint = ctx.accounts.usv_mint.key();
+        pool.lp_mint = ctx.accounts.lp_mint.key();
+        pool.reserve_sol = 0;
+        pool.reserve_usv = 0;
+        pool.lp_supply = 0;
+        pool.fee_bps = fee_bps;
+        pool.max_price_impact_bps = max_price_impact_bps;
+        pool.bump = ctx.bumps.pool;
+        pool.sol_vault_bump = ctx.bumps.pool_sol;
+        pool.usv_vault_bump = ctx.bumps.pool_usv;
+        pool.lp_mint_bump = ctx.bumps.lp_mint;
+        Ok(())
+    }
+
+    // Deposit `sol_amount` and the USV that matches the pool's price, up to
+    // `max_usv_amount`, for LP tokens. The first deposit sets the price with
+    // exactly `max_usv_amount`
+    pub fn add_liquidity(
+        ctx: Context<AddLiquidity>,
+        sol_amount: u64,
+        max_usv_amount: u64,
+        min_lp_out: u64,
+    ) -> Result<()> {
+        require!(ctx.accounts.trading_state.is_active, ErrorCode::TradingPaused);
+        let pool = &ctx.accounts.pool;
+        let (usv_amount, liquidity, lp_out) = if pool.lp_supply == 0 {
+            let liquidity = amm::initial_liquidity(sol_amount, max_usv_amount)?;
+            (max_usv_amount, liquidity, liquidity - amm::MINIMUM_LIQUIDITY)
+        } else {
+            let usv_amount = amm::usv_for_deposit(sol_amount, pool.reserve_sol, pool.reserve_usv)?;
+            require!(usv_amount <= max_usv_amount, ErrorCode::SlippageExceeded);
+            let liquidity = amm::pro_rata(sol_amount, pool.reserve_sol, pool.lp_supply)?;
+            (usv_amount, liquidity, liquidity)
+        };
+        require!(lp_out > 0, ErrorCode::InsufficientLiquidity);
+        require!(lp_out >= min_lp_out, ErrorCode::SlippageExceeded);
+
+        let ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.provider.key(),
+            &ctx.accounts.pool_sol.key(),
+            sol_amount,
+        );
+        anchor_lang::solana_program::program::invoke(
+            &ix,
+            &[
+                ctx.accounts.provider.to_account_info(),
+                ctx.accounts.pool_sol.to_account_info(),
+            ],
+        )?;
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.provider_token_account.to_account_info(),
+            to: ctx.accounts.pool_usv.to_account_info(),
+            authority: ctx.accounts.provider.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+            usv_amount,
+        )?;
+
+        let seeds: &[&[u8]] = &[b"pool", &[pool.bump]];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            to: ctx.accounts.provider_lp_account.to_account_info(),
+            authority: ctx.accounts.pool.to_account_info(),
+        };
+        token::mint_to(
+            CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer_seeds),
+            lp_out,
+        )?;
+
+        let pool = &mut ctx.accounts.pool;
+        pool.reserve_sol = pool.reserve_sol.checked_add(sol_amount).ok_or(ErrorCode::MathOverflow)?;
+        pool.reserve_usv = pool.reserve_usv.checked_add(usv_amount).ok_or(ErrorCode::MathOverflow)?;
+        pool.lp_supply = pool.lp_supply.checked_add(liquidity).ok_or(ErrorCode::MathOverflow)?;
+
+        emit_cpi!(LiquidityAdded {
+            provider: ctx.accounts.provider.key(),
+            sol_amount,
+            usv_amount,
+            lp_amount: lp_out,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Burn `lp_amount` LP tokens for their share of both reserves. Unlike deposits and
+    // swaps this stays open while trading is paused, so LPs can always exit
+    pub fn remove_liquidity(
+        ctx: Context<RemoveLiquidity>,
+        lp_amount: u64,
+        min_sol_out: u64,
+        min_usv_out: u64,
+    ) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let sol_amount = amm::pro_rata(lp_amount, pool.lp_supply, pool.reserve_sol)?;
+        let usv_amount = amm::pro_rata(lp_amount, pool.lp_supply, pool.reserve_usv)?;
+        require!(
+            sol_amount >= min_sol_out && usv_amount >= min_usv_out,
+            ErrorCode::SlippageExceeded
+        );
+
+        let cpi_accounts = Burn {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            from: ctx.accounts.provider_lp_account.to_account_info(),
+            authority: ctx.accounts.provider.to_account_info(),
+        };
+        token::burn(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+            lp_amount,
+        )?;
+
+        pay_from_pool(
+            &ctx.accounts.token_program,
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_usv,
+            &ctx.accounts.provider_token_account,
+            usv_amount,
+        )?;
+        pay_sol_from_pool(&ctx.accounts.pool, &ctx.accounts.pool_sol, &ctx.accounts.provider, sol_amount)?;
+
+        let pool = &mut ctx.accounts.pool;
+        pool.reserve_sol -= sol_amount;
+        pool.reserve_usv -= usv_amount;
+        pool.lp_supply -= lp_amount;
+
+        emit_cpi!(LiquidityRemoved {
+            provider: ctx.accounts.provider.key(),
+            sol_amount,
+            usv_amount,
+            lp_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Swap `amount_in` of SOL for USV, or of USV for SOL, against the pool.
+    // Fails rather than delivering less than `min_amount_out`, moving the price
+    // more than the pool allows, or after `deadline`
+    pub fn swap(
+        ctx: Context<Swap>,
+        amount_in: u64,
+        min_amount_out: u64,
+        sol_to_usv: bool,
+        deadline: i64, // Unix timestamp
+    ) -> Result<()> {
+        require!(ctx.accounts.trading_state.is_active, ErrorCode::TradingPaused);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= deadline, ErrorCode::DeadlineExceeded);
+
+        let pool = &ctx.accounts.pool;
+        let (reserve_in, reserve_out) = if sol_to_usv {
+            (pool.reserve_sol, pool.reserve_usv)
+        } else {
+            (pool.reserve_usv, pool.reserve_sol)
+        };
+        let quote = amm::quote_swap(amount_in, reserve_in, reserve_out, pool.fee_bps)?;
+        require!(quote.amount_out > 0, ErrorCode::InsufficientPayment);
+        require!(quote.amount_out >= min_amount_out, ErrorCode::SlippageExceeded);
+        require!(
+            quote.price_impact_bps <= pool.max_price_impact_bps,
+            ErrorCode::PriceImpactTooHigh
+        );
+
+        if sol_to_usv {
+            let ix = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.trader.key(),
+                &ctx.accounts.pool_sol.key(),
+                amount_in,
+            );
+            anchor_lang::solana_program::program::invoke(
+                &ix,
+                &[
+                    ctx.accounts.trader.to_account_info(),
+                    ctx.accounts.pool_sol.to_account_info(),
+                ],
+            )?;
+            pay_from_pool(
+                &ctx.accounts.token_program,
+                &ctx.accounts.pool,
+                &ctx.accounts.pool_usv,
+                &ctx.accounts.trader_token_account,
+                quote.amount_out,
+            )?;
+        } else {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.trader_token_account.to_account_info(),
+                to: ctx.accounts.pool_usv.to_account_info(),
+                authority: ctx.accounts.trader.to_account_info(),
+            };
+            token::transfer(
+                CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+                amount_in,
+            )?;
+            pay_sol_from_pool(&ctx.accounts.pool, &ctx.accounts.pool_sol, &ctx.accounts.trader, quote.amount_out)?;
+        }
+
+        // The whole input, fee included, stays in the pool
+        let reserve_in = reserve_in.checked_add(amount_in).ok_or(ErrorCode::MathOverflow)?;
+        let reserve_out = reserve_out - quote.amount_out;
+        let pool = &mut ctx.accounts.pool;
+        if sol_to_usv {
+            (pool.reserve_sol, pool.reserve_usv) = (reserve_in, reserve_out);
+        } else {
+            (pool.reserve_usv, pool.reserve_sol) = (reserve_in, reserve_out);
+        }
+
+        emit_cpi!(PoolSwap {
+            trader: ctx.accounts.trader.key(),
+            sol_to_usv,
+            amount_in,
+            amount_out: quote.amount_out,
+            fee: quote.fee,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_pool_config(ctx: Context<SetPoolConfig>, fee_bps: u64, max_price_impact_bps: u64) -> Result<()> {
+        validate_pool_config(fee_bps, max_price_impact_bps)?;
+        let pool = &mut ctx.accounts.pool;
+        pool.fee_bps = fee_bps;
+        pool.max_price_impact_bps = max_price_impact_bps;
+        Ok(())
+    }
+
+    pub fn toggle_trading(ctx: Context<ToggleTrading>, is_active: bool) -> Result<()> {
+        let trading_state = &mut ctx.accounts.trading_state;
+        trading_state.is_active = is_active;
+        trading_state.guardians.clear_votes();
+        Ok(())
+    }
+
+    // Replace the guardians; `threshold` of them must vote to pause trading. An
+    // empty list with threshold 0 disables guardian pauses
+    pub fn set_guardians(ctx: Context<SetGuardians>, guardians: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        let trading_state = &mut ctx.accounts.trading_state;
+        trading_state.guardians.replace(guardians, threshold)
+    }
+
+    // A guardian's vote to pause trading. Trading stops once `guardians.threshold`
+    // distinct guardians vote within GUARDIAN_VOTE_WINDOW; guardians can never resume it
+    pub fn guardian_pause(ctx: Context<GuardianPause>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let guardian = ctx.accounts.guardian.key();
+        let trading_state = &mut ctx.accounts.trading_state;
+        require!(trading_state.is_active, ErrorCode::TradingPaused);
+        let (votes, paused) = trading_state.guardians.vote(guardian, now)?;
+        if paused {
+            trading_state.is_active = false;
+        }
+
+        emit_cpi!(TradingPauseVoted {
+            guardian,
+            votes,
+            threshold: trading_state.guardians.threshold,
+            paused,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    // Get trading statistics
+    pub fn get_trading_stats(ctx: Context<GetTradingStats>) -> Result<()> {
+        let trading_state = &ctx.accounts.trading_state;
+        
+        emit_cpi!(TradingStats {
+            total_sales_volume: trading_state.total_sales_volume,
+            total_purchases: trading_state.total_purchases,
+            fixed_price_cents: trading_state.fixed_price_cents,
+            is_active: trading_state.is_active,
+        });
+
+        Ok(())
+    }
+}
+
+fn validate_pool_config(fee_bps: u64, max_price_impact_bps: u64) -> Result<()> {
+    require!(
+        fee_bps <= amm::MAX_FEE_BPS && max_price_impact_bps > 0 && max_price_impact_bps <= BPS_DENOMINATOR,
+        ErrorCode::InvalidPoolConfig
+    );
+    Ok(())
+}
+
+// Move USV out of the pool vault, signed by the pool that owns it
+fn pay_from_pool<'info>(
+    token_program: &Program<'info, Token>,
+    pool: &Account<'info, Pool>,
+    pool_usv: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    amount: u64,
+) -> Result<()> {
+    let seeds: &[&[u8]] = &[b"pool", &[pool.bump]];
+    let signer_seeds = &[seeds];
+    let cpi_accounts = Transfer {
+        from: pool_usv.to_account_info(),
+        to: to.to_account_info(),
+        authority: pool.to_account_info(),
+    };
+    token::transfer(
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds),
+        amount,
+    )
+}
+
+// Move lamports out of the pool's SOL vault PDA
+fn pay_sol_from_pool<'info>(
+    pool: &Account<'info, Pool>,
+    pool_sol: &SystemAccount<'info>,
+    to: &Signer<'info>,
+    lamports: u64,
+) -> Result<()> {
+    let ix = anchor_lang::solana_program::system_instruction::transfer(&pool_sol.key(), &to.key(), lamports);
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &[pool_sol.to_account_info(), to.to_account_info()],
+        &[&[b"pool_sol", &[pool.sol_vault_bump]]],
+    )?;
+    Ok(())
+}
+
+// USV a redemption leaves in circulation: the supply outside the accounts the
+// programs hold (`vaults` and the pool, if it exists), less the tokens being
+// returned to the inventory
+fn outstanding_after(
+    usv_mint: &Mint,
+    vaults: &[&TokenAccount],
+    pool_usv: &AccountInfo,
+    token_amount: u64,
+) -> Result<u64> {
+    let pooled = if pool_usv.data_is_empty() {
+        0
+    } else {
+        TokenAccount::try_deserialize(&mut &pool_usv.try_borrow_data()?[..])?.amount
+    };
+    let held = vaults.iter().fold(pooled, |held, vault| held.saturating_add(vault.amount));
+    Ok(usv_mint.supply.saturating_sub(held).saturating_sub(token_amount))
+}
+
+// Move tokens out of the inventory or vesting vault, signed by the trading
+// state that owns both
+fn release_from_vault<'info>(
+    token_program: &Program<'info, Token>,
+    vault: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    trading_state: &Account<'info, TradingState>,
+    amount: u64,
+) -> Result<()> {
+    let seeds: &[&[u8]] = &[b"trading_state", &[trading_state.bump]];
+    let signer_seeds = &[seeds];
+    let cpi_accounts = Transfer {
+        from: vault.to_account_info(),
+        to: to.to_account_info(),
+        authority: trading_state.to_account_info(),
+    };
+    token::transfer(
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds),
+        amount,
+    )
+}
+
+// Move redeemed tokens from the seller back into the sale inventory
+fn return_to_inventory<'info>(
+    token_program: &Program<'info, Token>,
+    seller_token_account: &Account<'info, TokenAccount>,
+    inventory: &Account<'info, TokenAccount>,
+    seller: &Signer<'info>,
+    token_amount: u64,
+) -> Result<()> {
+    let cpi_accounts = Transfer {
+        from: seller_token_account.to_account_info(),
+        to: inventory.to_account_info(),
+        authority: seller.to_account_info(),
+    };
+    token::transfer(CpiContext::new(token_program.to_account_info(), cpi_accounts), token_amount)
+}
+
+// State Accounts
+#[account]
+#[derive(InitSpace)]
+pub struct TradingState {
+    pub authority: Pubkey,
+    pub usv_mint: Pubkey,
+    pub treasury: Pubkey, // Receives SOL proceeds
+    pub fixed_price_cents: u64, // Price in USD cents
+    pub curve: Option<BondingCurve>, // Prices purchases instead of fixed_price_cents while set
+    pub curve_sold: u64,             // Base units sold since the curve was set
+    pub is_active: bool,
+    pub total_sales_volume: u64, // Total SOL received
+    pub total_purchases: u64,    // Number of purchases
+    pub price_feed_id: [u8; 32], // Pyth SOL/USD feed id
+    pub max_price_age_secs: u64,
+    pub max_confidence_bps: u64, // Widest accepted confidence interval, in bps of the price
+    pub bid_price_cents: u64,    // Redemption price in USD cents; 0 disables redemption
+    pub max_redeemed_per_day: u64,
+    pub min_reserve_ratio_bps: u64,
+    pub redemption_window_start: i64,
+    pub redeemed_in_window: u64,
+    pub total_redeemed: u64,
+    pub claim_vault: Pubkey, // usv_token's claim funding account, excluded from circulation
+    pub current_round: u32, // Sale round open for purchases; round_count once all have closed
+    pub round_count: u32,
+    pub vesting_threshold: u64, // Purchases of more base units vest; 0 vests only in vesting rounds
+    pub vesting_cliff_secs: i64,
+    pub vesting_duration_secs: i64,
+    pub guardians: GuardianSet,
+    pub bump: u8,
+    pub inventory_bump: u8,
+    pub vesting_vault_bump: u8,
+}
+
+impl TradingState {
+    fn check_redemption(&self, now: i64, deadline: i64) -> Result<()> {
+        require!(self.is_active, ErrorCode::TradingPaused);
+        require!(self.bid_price_cents > 0, ErrorCode::RedemptionDisabled);
+        require!(now <= deadline, ErrorCode::DeadlineExceeded);
+        Ok(())
+    }
+
+    // Redemption may not pay more than the lowest sale price
+    fn check_bid(&self) -> Result<()> {
+        let ask_micros = match self.curve {
+            Some(curve) => curve.start_price_micros as u128,
+            None => self.fixed_price_cents as u128 * (pricing::MICROS_PER_USD / pricing::CENTS_PER_USD),
+        };
+        require!(
+            self.bid_price_cents as u128 * (pricing::MICROS_PER_USD / pricing::CENTS_PER_USD) <= ask_micros,
+            ErrorCode::BidAboveAsk
+        );
+        Ok(())
+    }
+
+    // Purchases outside `buy_in_round` wait while the current round is live;
+    // `sale_round` must be the current round if any remain
+    fn check_no_live_round(&self, sale_round: Option<&SaleRound>, now: i64) -> Result<()> {
+        if self.current_round < self.round_count {
+            let sale_round = sale_round.ok_or(ErrorCode::SaleRoundRequired)?;
+            require!(!sale_round.is_live(now), ErrorCode::SaleRoundLive);
+        }
+        Ok(())
+    }
+
+    // Whether a purchase of `token_amount` goes to the buyer's vesting escrow
+    fn vests(&self, token_amount: u64, in_vesting_round: bool) -> bool {
+        in_vesting_round || (self.vesting_threshold > 0 && token_amount > self.vesting_threshold)
+    }
+
+    // Move the bonding curve along by a purchase of `token_amount`
+    fn record_sale(&mut self, token_amount: u64) -> Result<()> {
+        if self.curve.is_some() {
+            self.curve_sold = self.curve_sold.checked_add(token_amount).ok_or(ErrorCode::MathOverflow)?;
+        }
+        Ok(())
+    }
+
+    fn reserve_covers(&self, reserve: u64, liability: u64) -> bool {
+        reserve as u128 * BPS_DENOMINATOR as u128 >= liability as u128 * self.min_reserve_ratio_bps as u128
+    }
+
+    // Count `token_amount` against the daily cap, opening a new window once the last has ended
+    fn record_redemption(&mut self, now: i64, token_amount: u64) -> Result<()> {
+        if now.saturating_sub(self.redemption_window_start) >= REDEMPTION_WINDOW {
+            self.redemption_window_start = now;
+            self.redeemed_in_window = 0;
+        }
+        let redeemed = self
+            .redeemed_in_window
+            .checked_add(token_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(
+            self.max_redeemed_per_day == 0 || redeemed <= self.max_redeemed_per_day,
+            ErrorCode::RedemptionCapExceeded
+        );
+        self.redeemed_in_window = redeemed;
+        self.total_redeemed = self
+            .total_redeemed
+            .checked_add(token_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+}
+
+// One presale round; rounds open in index order, each until its end time or
+// until it sells out
+#[account]
+#[derive(InitSpace)]
+pub struct SaleRound {
+    pub index: u32,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub price_cents: u64,
+    pub cap: u64,            // Token base units
+    pub per_wallet_cap: u64, // 0 for no limit
+    pub allowlist_root: Option<[u8; 32]>, // See allowlist.rs; None lets any wallet buy
+    pub vests: bool, // Purchases go to the buyer's vesting escrow
+    pub sold: u64,
+    pub bump: u8,
+}
+
+impl SaleRound {
+    #[allow(clippy::too_many_arguments)]
+    fn set_terms(
+        &mut self,
+        start_time: i64,
+        end_time: i64,
+        price_cents: u64,
+        cap: u64,
+        per_wallet_cap: u64,
+        allowlist_root: Option<[u8; 32]>,
+        vests: bool,
+    ) -> Result<()> {
+        require!(price_cents > 0, ErrorCode::InvalidPrice);
+        require!(start_time < end_time && cap > 0, ErrorCode::InvalidSaleRound);
+        self.start_time = start_time;
+        self.end_time = end_time;
+        self.price_cents = price_cents;
+        self.cap = cap;
+        self.per_wallet_cap = per_wallet_cap;
+        self.allowlist_root = allowlist_root;
+        self.vests = vests;
+        Ok(())
+    }
+
+    // Whether the round is open for purchases at `now`
+    fn is_live(&self, now: i64) -> bool {
+        now >= self.start_time && now <= self.end_time && self.sold < self.cap
+    }
+
+    // Base units a wallet that has bought `purchased` in this round may still buy
+    fn available_to(&self, purchased: u64) -> u64 {
+        let left = self.cap.saturating_sub(self.sold);
+        if self.per_wallet_cap == 0 {
+            left
+        } else {
+            left.min(self.per_wallet_cap.saturating_sub(purchased))
+        }
+    }
+}
+
+// What one wallet has bought in one sale round
+#[account]
+#[derive(InitSpace)]
+pub struct RoundBuyer {
+    pub buyer: Pubkey,
+    pub purchased: u64,
+    pub bump: u8,
+}
+
+// Tokens one wallet has bought that have not vested or not been released yet;
+// they are held in the vesting vault
+#[account]
+#[derive(InitSpace)]
+pub struct VestingEscrow {
+    pub buyer: Pubkey,
+    pub schedule: VestingSchedule,
+    pub bump: u8,
+}
+
+impl VestingEscrow {
+    // Lock `token_amount` bought by `buyer` in a new tranche on the trading state's
+    // vesting schedule, returning the tranche and everything the escrow now has locked
+    fn lock(
+        &mut self,
+        buyer: Pubkey,
+        bump: u8,
+        trading_state: &TradingState,
+        token_amount: u64,
+        now: i64,
+    ) -> Result<(VestingTranche, u64)> {
+        self.buyer = buyer;
+        self.bump = bump;
+        let tranche = self.schedule.deposit(
+            token_amount,
+            now,
+            trading_state.vesting_cliff_secs,
+            trading_state.vesting_duration_secs,
+        )?;
+        Ok((tranche, self.schedule.locked(now)))
+    }
+}
+
+// A whitelisted SPL payment token, one per mint
+#[account]
+#[derive(InitSpace)]
+pub struct PaymentMintConfig {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub usd_price_micros: u64, // USD value of one whole token, in millionths
+    pub treasury: Pubkey,      // Token account that receives payments
+    pub is_enabled: bool,
+    pub total_received: u64,   // Payment token base units
+    pub bump: u8,
+}
+
+// The USV/SOL constant-product pool; reserves are tracked here rather than
+// read from the vaults, so tokens sent to them directly are never priced in
+#[account]
+#[derive(InitSpace)]
+pub struct Pool {
+    pub usv_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub reserve_sol: u64, // Lamports, excluding the vault's rent-exempt minimum
+    pub reserve_usv: u64,
+    pub lp_supply: u64, // Including the locked MINIMUM_LIQUIDITY
+    pub fee_bps: u64,
+    pub max_price_impact_bps: u64,
+    pub bump: u8,
+    pub sol_vault_bump: u8,
+    pub usv_vault_bump: u8,
+    pub lp_mint_bump: u8,
+}
+
+// Context Structs
+#[derive(Accounts)]
+pub struct InitializeTrading<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + TradingState::INIT_SPACE,
+        seeds = [b"trading_state"],
+        bump
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    pub usv_mint: Account<'info, Mint>,
+
+    // Tokens for sale, owned by the trading state
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"inventory"],
+        bump,
+        token::mint = usv_mint,
+        token::authority = trading_state
+    )]
+    pub inventory: Account<'info, TokenAccount>,
+
+    // Sold tokens waiting to vest, owned by the trading state
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"vesting_vault"],
+        bump,
+        token::mint = usv_mint,
+        token::authority = trading_state
+    )]
+    pub vesting_vault: Account<'info, TokenAccount>,
+
+    pub treasury: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BuyTokens<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_state"],
+        bump = trading_state.bump
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    // The current sale round; required while any round remains
+    #[account(
+        seeds = [b"sale_round", trading_state.current_round.to_le_bytes().as_ref()],
+        bump = sale_round.bump
+    )]
+    pub sale_round: Option<Box<Account<'info, SaleRound>>>,
+
+    #[account(
+        constraint = usv_mint.key() == trading_state.usv_mint @ ErrorCode::InvalidMint
+    )]
+    pub usv_mint: Account<'info, Mint>,
+
+    /// CHECK: Pyth price update; owner, layout and feed id are checked when the price is read
+    pub price_update: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"inventory"],
+        bump = trading_state.inventory_bump
+    )]
+    pub inventory: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        token::mint = usv_mint,
+        token::authority = buyer
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vesting_vault"],
+        bump = trading_state.vesting_vault_bump
+    )]
+    pub vesting_vault: Box<Account<'info, TokenAccount>>,
+
+    // Only needed when the purchase vests
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + VestingEscrow::INIT_SPACE,
+        seeds = [b"vesting", buyer.key().as_ref()],
+        bump
+    )]
+    pub vesting_escrow: Option<Box<Account<'info, VestingEscrow>>>,
+
+    #[account(
+        mut,
+        address = trading_state.treasury @ ErrorCode::InvalidTreasury
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BuyTokensWithSpl<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_state"],
+        bump = trading_state.bump
+    )]
+    pub trading_state: Box<Account<'info, TradingState>>,
+
+    // The current sale round; required while any round remains
+    #[account(
+        seeds = [b"sale_round", trading_state.current_round.to_le_bytes().as_ref()],
+        bump = sale_round.bump
+    )]
+    pub sale_round: Option<Box<Account<'info, SaleRound>>>,
+
+    #[account(
+        constraint = usv_mint.key() == trading_state.usv_mint @ ErrorCode::InvalidMint
+    )]
+    pub usv_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"payment_mint", payment_mint.key().as_ref()],
+        bump = payment_config.bump,
+        has_one = treasury @ ErrorCode::InvalidTreasury
+    )]
+    pub payment_config: Box<Account<'info, PaymentMintConfig>>,
+
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = buyer
+    )]
+    pub buyer_payment_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub treasury: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"inventory"],
+        bump = trading_state.inventory_bump
+    )]
+    pub inventory: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        token::mint = usv_mint,
+        token::authority = buyer
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vesting_vault"],
+        bump = trading_state.vesting_vault_bump
+    )]
+    pub vesting_vault: Box<Account<'info, TokenAccount>>,
+
+    // Only needed when the purchase vests
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + VestingEscrow::INIT_SPACE,
+        seeds = [b"vesting", buyer.key().as_ref()],
+        bump
+    )]
+    pub vesting_escrow: Option<Box<Account<'info, VestingEscrow>>>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddPaymentMint<'info> {
+    #[account(
+        seeds = [b"trading_state"],
+        bump = trading_state.bump,
+        has_one = authority
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PaymentMintConfig::INIT_SPACE,
+        seeds = [b"payment_mint", payment_mint.key().as_ref()],
+        bump
+    )]
+    pub payment_config: Account<'info, PaymentMintConfig>,
+
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(token::mint = payment_mint)]
+    pub treasury: Account<'info, TokenAccount>,
+
+    // Pays out redemptions in this token; funded by transferring into it
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"reserve", payment_mint.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = trading_state
+    )]
+    pub reserve: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePaymentMint<'info> {
+    #[account(
+        seeds = [b"trading_state"],
+        bump = trading_state.bump,
+        has_one = authority
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    #[account(
+        mut,
+        seeds = [b"payment_mint", payment_config.mint.as_ref()],
+        bump = payment_config.bump
+    )]
+    pub payment_config: Account<'info, PaymentMintConfig>,
+
+    #[account(
+        constraint = treasury.mint == payment_config.mint @ ErrorCode::InvalidTreasury
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SellTokens<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_state"],
+        bump = trading_state.bump
+    )]
+    pub trading_state: Box<Account<'info, TradingState>>,
+
+    #[account(
+        constraint = usv_mint.key() == trading_state.usv_mint @ ErrorCode::InvalidMint
+    )]
+    pub usv_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Pyth price update; owner, layout and feed id are checked when the price is read
+    pub price_update: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = usv_mint,
+        token::authority = seller
+    )]
+    pub seller_token_account: Box<Account<'info, TokenAccount>>,
+
+    // The sale inventory redeemed tokens return to
+    #[account(
+        mut,
+        seeds = [b"inventory"],
+        bump = trading_state.inventory_bump
+    )]
+    pub inventory: Box<Account<'info, TokenAccount>>,
+
+    // usv_token's claim vault and the vesting vault; with the inventory and the
+    // pool, their balances are not in circulation
+    #[account(
+        address = trading_state.claim_vault @ ErrorCode::InvalidClaimVault
+    )]
+    pub claim_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"vesting_vault"],
+        bump = trading_state.vesting_vault_bump
+    )]
+    pub vesting_vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The pool's USV vault; read only once the pool has created it
+    #[account(
+        seeds = [b"pool_usv"],
+        bump
+    )]
+    pub pool_usv: UncheckedAccount<'info>,
+
+    // Pays out SOL redemptions; funded by transferring SOL to it
+    #[account(
+        mut,
+        seeds = [b"sol_reserve"],
+        bump
+    )]
+    pub sol_reserve: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SellTokensForSpl<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_state"],
+        bump = trading_state.bump
+    )]
+    pub trading_state: Box<Account<'info, TradingState>>,
+
+    #[account(
+        constraint = usv_mint.key() == trading_state.usv_mint @ ErrorCode::InvalidMint
+    )]
+    pub usv_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [b"payment_mint", payment_mint.key().as_ref()],
+        bump = payment_config.bump
+    )]
+    pub payment_config: Box<Account<'info, PaymentMintConfig>>,
+
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = usv_mint,
+        token::authority = seller
+    )]
+    pub seller_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = seller
+    )]
+    pub seller_payment_account: Box<Account<'info, TokenAccount>>,
+
+    // The sale inventory redeemed tokens return to
+    #[account(
+        mut,
+        seeds = [b"inventory"],
+        bump = trading_state.inventory_bump
+    )]
+    pub inventory: Box<Account<'info, TokenAccount>>,
+
+    // usv_token's claim vault and the vesting vault; with the inventory and the
+    // pool, their balances are not in circulation
+    #[account(
+        address = trading_state.claim_vault @ ErrorCode::InvalidClaimVault
+    )]
+    pub claim_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"vesting_vault"],
+        bump = trading_state.vesting_vault_bump
+    )]
+    pub vesting_vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The pool's USV vault; read only once the pool has created it
+    #[account(
+        seeds = [b"pool_usv"],
+        bump
+    )]
+    pub pool_usv: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"reserve", payment_mint.key().as_ref()],
+        bump
+    )]
+    pub reserve: Box<Account<'info, TokenAccount>>,
+
+    pub seller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_state"],
+        bump = trading_state.bump,
+        has_one = authority
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    pub treasury: SystemAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddSaleRound<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_state"],
+        bump = trading_state.bump,
+        has_one = authority
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SaleRound::INIT_SPACE,
+        seeds = [b"sale_round", trading_state.round_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub sale_round: Account<'info, SaleRound>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSaleRound<'info> {
+    #[account(
+        seeds = [b"trading_state"],
+        bump = trading_state.bump,
+        has_one = authority
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    #[account(
+        mut,
+        seeds = [b"sale_round", sale_round.index.to_le_bytes().as_ref()],
+        bump = sale_round.bump,
+        constraint = sale_round.index >= trading_state.current_round @ ErrorCode::SaleRoundClosed
+    )]
+    pub sale_round: Account<'info, SaleRound>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BuyInRound<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_state"],
+        bump = trading_state.bump
+    )]
+    pub trading_state: Box<Account<'info, TradingState>>,
+
+    #[account(
+        mut,
+        seeds = [b"sale_round", trading_state.current_round.to_le_bytes().as_ref()],
+        bump = sale_round.bump
+    )]
+    pub sale_round: Box<Account<'info, SaleRound>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + RoundBuyer::INIT_SPACE,
+        seeds = [b"round_buyer", sale_round.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub round_buyer: Box<Account<'info, RoundBuyer>>,
+
+    #[account(
+        constraint = usv_mint.key() == trading_state.usv_mint @ ErrorCode::InvalidMint
+    )]
+    pub usv_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Pyth price update; owner, layout and feed id are checked when the price is read
+    pub price_update: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"inventory"],
+        bump = trading_state.inventory_bump
+    )]
+    pub inventory: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        token::mint = usv_mint,
+        token::authority = buyer
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vesting_vault"],
+        bump = trading_state.vesting_vault_bump
+    )]
+    pub vesting_vault: Box<Account<'info, TokenAccount>>,
+
+    // Only needed when the purchase vests
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + VestingEscrow::INIT_SPACE,
+        seeds = [b"vesting", buyer.key().as_ref()],
+        bump
+    )]
+    pub vesting_escrow: Option<Box<Account<'info, VestingEscrow>>>,
+
+    #[account(
+        mut,
+        address = trading_state.treasury @ ErrorCode::InvalidTreasury
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AdvanceSaleRound<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_state"],
+        bump = trading_state.bump
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    #[account(
+        seeds = [b"sale_round", trading_state.current_round.to_le_bytes().as_ref()],
+        bump = sale_round.bump
+    )]
+    pub sale_round: Account<'info, SaleRound>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawInventory<'info> {
+    #[account(
+        seeds = [b"trading_state"],
+        bump = trading_state.bump,
+        has_one = authority
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    #[account(
+        mut,
+        seeds = [b"inventory"],
+        bump = trading_state.inventory_bump
+    )]
+    pub inventory: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = destination.mint == trading_state.usv_mint @ ErrorCode::InvalidMint
+    )]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(
+        seeds = [b"trading_state"],
+        bump = trading_state.bump,
+        has_one = authority,
+        has_one = usv_mint @ ErrorCode::InvalidMint
+    )]
+    pub trading_state: Box<Account<'info, TradingState>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Pool::INIT_SPACE,
+        seeds = [b"pool"],
+        bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    pub usv_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"lp_mint"],
+        bump,
+        mint::decimals = amm::LP_DECIMALS,
+        mint::authority = pool
+    )]
+    pub lp_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"pool_usv"],
+        bump,
+        token::mint = usv_mint,
+        token::authority = pool
+    )]
+    pub pool_usv: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_sol"],
+        bump
+    )]
+    pub pool_sol: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AddLiquidity<'info> {
+    #[account(
+        seeds = [b"trading_state"],
+        bump = trading_state.bump
+    )]
+    pub trading_state: Box<Account<'info, TradingState>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool"],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_usv"],
+        bump = pool.usv_vault_bump
+    )]
+    pub pool_usv: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_sol"],
+        bump = pool.sol_vault_bump
+    )]
+    pub pool_sol: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"lp_mint"],
+        bump = pool.lp_mint_bump
+    )]
+    pub lp_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::authority = provider,
+        constraint = provider_token_account.mint == pool.usv_mint @ ErrorCode::InvalidMint
+    )]
+    pub provider_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = provider,
+        associated_token::mint = lp_mint,
+        associated_token::authority = provider
+    )]
+    pub provider_lp_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RemoveLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool"],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_usv"],
+        bump = pool.usv_vault_bump
+    )]
+    pub pool_usv: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_sol"],
+        bump = pool.sol_vault_bump
+    )]
+    pub pool_sol: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"lp_mint"],
+        bump = pool.lp_mint_bump
+    )]
+    pub lp_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = provider
+    )]
+    pub provider_lp_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = provider_token_account.mint == pool.usv_mint @ ErrorCode::InvalidMint
+    )]
+    pub provider_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(
+        seeds = [b"trading_state"],
+        bump = trading_state.bump
+    )]
+    pub trading_state: Box<Account<'info, TradingState>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool"],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_usv"],
+        bump = pool.usv_vault_bump
+    )]
+    pub pool_usv: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_sol"],
+        bump = pool.sol_vault_bump
+    )]
+    pub pool_sol: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        token::authority = trader,
+        constraint = trader_token_account.mint == pool.usv_mint @ ErrorCode::InvalidMint
+    )]
+    pub trader_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub trader: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPoolConfig<'info> {
+    #[account(
+        seeds = [b"trading_state"],
+        bump = trading_state.bump,
+        has_one = authority
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    #[account(
+        mut,
+        seeds = [b"pool"],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetVestingConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_state"],
+        bump = trading_state.bump,
+        has_one = authority
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReleaseVested<'info> {
+    #[account(
+        seeds = [b"trading_state"],
+        bump = trading_state.bump
+    )]
+    pub trading_state: Box<Account<'info, TradingState>>,
+
+    #[account(
+        mut,
+        seeds = [b"vesting", buyer.key().as_ref()],
+        bump = vesting_escrow.bump
+    )]
+    pub vesting_escrow: Box<Account<'info, VestingEscrow>>,
+
+    #[account(
+        constraint = usv_mint.key() == trading_state.usv_mint @ ErrorCode::InvalidMint
+    )]
+    pub usv_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"vesting_vault"],
+        bump = trading_state.vesting_vault_bump
+    )]
+    pub vesting_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        token::mint = usv_mint,
+        token::authority = buyer
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetBondingCurve<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_state"],
+        bump = trading_state.bump,
+        has_one = authority
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRedemptionConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_state"],
+        bump = trading_state.bump,
+        has_one = authority
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    // usv_token's account that funds QR claims; its balance is not in circulation
+    #[account(
+        constraint = claim_vault.mint == trading_state.usv_mint @ ErrorCode::InvalidMint
+    )]
+    pub claim_vault: Account<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateFixedPrice<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_state"],
+        bump = trading_state.bump,
+        has_one = authority
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateOracleConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_state"],
+        bump = trading_state.bump,
+        has_one = authority
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ToggleTrading<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_state"],
+        bump = trading_state.bump,
+        has_one = authority
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardians<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_state"],
+        bump = trading_state.bump,
+        has_one = authority
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct GuardianPause<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_state"],
+        bump = trading_state.bump
+    )]
+    pub trading_state: Account<'info, TradingState>,
+
+    pub guardian: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct GetTradingStats<'info> {
+    #[account(
+        seeds = [b"trading_state"],
+        bump = trading_state.bump
+    )]
+    pub trading_state: Account<'info, TradingState>,
+}
+
+// Events
+#[event]
+pub struct TokenPurchase {
+    pub buyer: Pubkey,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SplTokenPurchase {
+    pub buyer: Pubkey,
+    pub payment_mint: Pubkey,
+    pub payment_amount: u64,
+    pub token_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LiquidityAdded {
+    pub provider: Pubkey,
+    pub sol_amount: u64,
+    pub usv_amount: u64,
+    pub lp_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LiquidityRemoved {
+    pub provider: Pubkey,
+    pub sol_amount: u64,
+    pub usv_amount: u64,
+    pub lp_amount: u64,
+    pub timestamp: i64,
+}
+
+// `fee` is in units of the input
+#[event]
+pub struct PoolSwap {
+    pub trader: Pubkey,
+    pub sol_to_usv: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+// `to_round` equals the number of rounds once the last one closes
+#[event]
+pub struct SaleRoundAdvanced {
+    pub from_round: u32,
+    pub to_round: u32,
+    pub sold: u64,
+    pub sold_out: bool,
+    pub timestamp: i64,
+}
+
+// A purchase locked in the buyer's vesting escrow as a new tranche, which unlocks
+// linearly from `cliff_time` to `end_time`; `locked` is everything the escrow has locked
+#[event]
+pub struct TokensVested {
+    pub buyer: Pubkey,
+    pub token_amount: u64,
+    pub locked: u64,
+    pub cliff_time: i64,
+    pub end_time: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestedTokensReleased {
+    pub buyer: Pubkey,
+    pub token_amount: u64,
+    pub locked: u64, // Still locked after the release
+    pub timestamp: i64,
+}
+
+// `payout_mint` is None for SOL redemptions
+#[event]
+pub struct TokenRedemption {
+    pub seller: Pubkey,
+    pub payout_mint: Option<Pubkey>,
+    pub token_amount: u64,
+    pub payout_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PriceUpdated {
+    pub old_price: u64,
+    pub new_price: u64,
+    pub timestamp: i64,
+}
+
+// `paused` is set on the vote that reached the threshold
+#[event]
+pub struct TradingPauseVoted {
+    pub guardian: Pubkey,
+    pub votes: u8,
+    pub threshold: u8,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TradingStats {
+    pub total_sales_volume: u64,
+    pub total_purchases: u64,
+    pub fixed_price_cents: u64,
+    pub is_active: bool,
+}
+
+// Error Codes
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Trading is currently paused")]
+    TradingPaused,
+    #[msg("Insufficient payment amount")]
+    InsufficientPayment,
+    #[msg("Invalid mint provided")]
+    InvalidMint,
+    #[msg("Price account is not a verified update for the configured feed")]
+    InvalidPriceFeed,
+    #[msg("Oracle price is too old")]
+    StalePrice,
+    #[msg("Oracle price confidence interval is too wide")]
+    PriceConfidenceTooLow,
+    #[msg("Token price must be greater than zero")]
+    InvalidPrice,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Trade would deliver less than the minimum requested")]
+    SlippageExceeded,
+    #[msg("Trade deadline has passed")]
+    DeadlineExceeded,
+    #[msg("Payment token is not accepted")]
+    PaymentMintDisabled,
+    #[msg("Treasury account does not match the configured treasury")]
+    InvalidTreasury,
+    #[msg("Redemption is disabled")]
+    RedemptionDisabled,
+    #[msg("Daily redemption cap reached")]
+    RedemptionCapExceeded,
+    #[msg("Reserve cannot cover this redemption")]
+    InsufficientReserve,
+    #[msg("Bid price cannot exceed the sale price")]
+    BidAboveAsk,
+    #[msg("Pool does not have enough liquidity")]
+    InsufficientLiquidity,
+    #[msg("Swap would move the price more than the pool allows")]
+    PriceImpactTooHigh,
+    #[msg("Pool fee or price impact limit is out of range")]
+    InvalidPoolConfig,
+    #[msg("Bonding curve parameters are out of range")]
+    InvalidCurve,
+    #[msg("Purchase would exceed the bonding curve's supply cap")]
+    CurveSoldOut,
+    #[msg("Sale round must end after it starts and have a cap")]
+    InvalidSaleRound,
+    #[msg("Sale round is not open")]
+    SaleRoundClosed,
+    #[msg("Sale round is still open")]
+    SaleRoundOpen,
+    #[msg("Nothing left to buy in this sale round")]
+    SaleRoundSoldOut,
+    #[msg("Wallet is not on the sale round's allowlist")]
+    NotAllowlisted,
+    #[msg("Vesting cliff must fall within a non-zero vesting duration")]
+    InvalidVestingConfig,
+    #[msg("Purchase vests but no vesting escrow was provided")]
+    VestingEscrowRequired,
+    #[msg("Nothing has vested since the last release")]
+    NothingToRelease,
+    #[msg("Claim vault does not match the redemption config")]
+    InvalidClaimVault,
+    #[msg("Purchases go through buy_in_round while the current sale round is live")]
+    SaleRoundLive,
+    #[msg("The current sale round account is required while any round remains")]
+    SaleRoundRequired,
+}
@@ -0,0 +1,94 @@
+// programs/usv-trading/src/oracle.rs - SOL/USD prices from Pyth price update accounts
+
+use anchor_lang::prelude::*;
+
+use crate::ErrorCode;
+
+// Pyth Solana receiver program; it owns every verified price update account
+pub mod pyth_receiver {
+    anchor_lang::declare_id!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+}
+
+// Pyth push oracle program; its PDAs hold the continuously updated sponsored feeds
+pub mod pyth_push_oracle {
+    anchor_lang::declare_id!("pythWSnswVUd12oZpeFP8e9CVaEqJg25g1Vtc2biRsT");
+}
+
+// Pyth SOL/USD feed id
+pub const SOL_USD_FEED_ID: [u8; 32] = [
+    0xef, 0x0d, 0x8b, 0x6f, 0xda, 0x2c, 0xeb, 0xa4, 0x1d, 0xa1, 0x5d, 0x40, 0x95, 0xd1, 0xda, 0x39,
+    0x2a, 0x0d, 0x2f, 0x8e, 0xd0, 0xc6, 0xc7, 0xbc, 0x0f, 0x4c, 0xfa, 0xc8, 0xc2, 0x80, 0xb5, 0x6d,
+];
+
+// Anchor discriminator of the receiver's `PriceUpdateV2` account
+pub const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationLevel {
+    Partial { num_signatures: u8 },
+    Full,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct PriceFeedMessage {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+}
+
+// Layout of the receiver's `PriceUpdateV2` account after its discriminator
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct PriceUpdateV2 {
+    pub write_authority: Pubkey,
+    pub verification_level: VerificationLevel,
+    pub price_message: PriceFeedMessage,
+    pub posted_slot: u64,
+}
+
+// A checked, positive price of `price * 10^exponent` USD
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Price {
+    pub price: u64,
+    pub exponent: i32,
+}
+
+impl PriceUpdateV2 {
+    pub fn load(account: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(*account.owner, pyth_receiver::ID, ErrorCode::InvalidPriceFeed);
+        let data = account.try_borrow_data()?;
+        let mut body = data
+            .strip_prefix(&PRICE_UPDATE_V2_DISCRIMINATOR)
+            .ok_or(ErrorCode::InvalidPriceFeed)?;
+        PriceUpdateV2::deserialize(&mut body).map_err(|_| error!(ErrorCode::InvalidPriceFeed))
+    }
+
+    // The price for `feed_id`, rejected if it was published more than
+    // `max_age_secs` before `now` or its confidence interval is wider than
+    // `max_confidence_bps` of the price
+    pub fn price(&self, feed_id: &[u8; 32], now: i64, max_age_secs: u64, max_confidence_bps: u64) -> Result<Price> {
+        let message = &self.price_message;
+        require!(
+            message.feed_id == *feed_id && self.verification_level == VerificationLevel::Full,
+            ErrorCode::InvalidPriceFeed
+        );
+        require!(
+            now.saturating_sub(message.publish_time) <= max_age_secs as i64,
+            ErrorCode::StalePrice
+        );
+        let price = u64::try_from(message.price).map_err(|_| error!(ErrorCode::InvalidPriceFeed))?;
+        require!(price > 0, ErrorCode::InvalidPriceFeed);
+        require!(
+            message.conf as u128 * 10_000 <= price as u128 * max_confidence_bps as u128,
+            ErrorCode::PriceConfidenceTooLow
+        );
+        Ok(Price {
+            price,
+            exponent: message.exponent,
+        })
+    }
+}
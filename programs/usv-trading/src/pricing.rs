@@ -0,0 +1,171 @@
+// programs/usv-trading/src/pricing.rs - Integer token pricing
+//
+// All amounts are integers in their smallest unit: lamports, USV base units
+// (6 decimals) and USD cents. The SOL/USD price is `price * 10^exponent` as
+// reported by the oracle. Every quote is computed exactly in u128 and rounded
+// down once at the end, so rounding always favours the program: a buyer never
+// receives a base unit they did not pay for in full, and a redeemer is never
+// paid more than the bid value of the tokens they return.
+
+use anchor_lang::prelude::*;
+
+use crate::oracle::Price;
+use crate::ErrorCode;
+
+pub const LAMPORTS_PER_SOL: u128 = 1_000_000_000;
+pub const TOKEN_BASE_UNITS: u128 = 1_000_000;
+pub const CENTS_PER_USD: u128 = 100;
+pub const MICROS_PER_USD: u128 = 1_000_000;
+pub const PICOS_PER_USD: u128 = 1_000_000_000_000;
+
+// Tokens in base units bought with `lamports` at `sol_usd` per SOL and
+// `fixed_price_cents` per token:
+//
+//   lamports * price * 10^exponent * CENTS_PER_USD * TOKEN_BASE_UNITS
+//   -----------------------------------------------------------------
+//                 LAMPORTS_PER_SOL * fixed_price_cents
+pub fn tokens_for_lamports(lamports: u64, sol_usd: Price, fixed_price_cents: u64) -> Result<u64> {
+    require!(fixed_price_cents > 0, ErrorCode::InvalidPrice);
+
+    let mut numerator = mul(lamports as u128, sol_usd.price as u128)?;
+    numerator = mul(numerator, CENTS_PER_USD * TOKEN_BASE_UNITS)?;
+    let mut denominator = LAMPORTS_PER_SOL * fixed_price_cents as u128;
+
+    let scale = 10_u128
+        .checked_pow(sol_usd.exponent.unsigned_abs())
+        .ok_or(ErrorCode::MathOverflow)?;
+    if sol_usd.exponent >= 0 {
+        numerator = mul(numerator, scale)?;
+    } else {
+        denominator = mul(denominator, scale)?;
+    }
+
+    u64::try_from(numerator / denominator).map_err(|_| error!(ErrorCode::MathOverflow))
+}
+
+// Lamports that buy exactly `tokens` base units at `fixed_price_cents`, rounded
+// up so that `tokens_for_lamports` of the result is never less than `tokens`:
+//
+//   tokens * fixed_price_cents * LAMPORTS_PER_SOL
+//   --------------------------------------------------------
+//   TOKEN_BASE_UNITS * CENTS_PER_USD * price * 10^exponent
+pub fn lamports_for_purchase(tokens: u64, sol_usd: Price, fixed_price_cents: u64) -> Result<u64> {
+    require!(sol_usd.price > 0, ErrorCode::InvalidPriceFeed);
+
+    let mut numerator = mul(mul(tokens as u128, fixed_price_cents as u128)?, LAMPORTS_PER_SOL)?;
+    let mut denominator = mul(TOKEN_BASE_UNITS * CENTS_PER_USD, sol_usd.price as u128)?;
+
+    let scale = 10_u128
+        .checked_pow(sol_usd.exponent.unsigned_abs())
+        .ok_or(ErrorCode::MathOverflow)?;
+    if sol_usd.exponent >= 0 {
+        denominator = mul(denominator, scale)?;
+    } else {
+        numerator = mul(numerator, scale)?;
+    }
+
+    u64::try_from(numerator.div_ceil(denominator)).map_err(|_| error!(ErrorCode::MathOverflow))
+}
+
+// Tokens in base units bought with `amount` base units of a payment token with
+// `decimals` decimals, worth `usd_price_micros` per whole token:
+//
+//   amount * usd_price_micros * CENTS_PER_USD * TOKEN_BASE_UNITS
+//   ------------------------------------------------------------
+//        10^decimals * MICROS_PER_USD * fixed_price_cents
+pub fn tokens_for_payment(
+    amount: u64,
+    decimals: u8,
+    usd_price_micros: u64,
+    fixed_price_cents: u64,
+) -> Result<u64> {
+    require!(fixed_price_cents > 0, ErrorCode::InvalidPrice);
+
+    let mut numerator = mul(amount as u128, usd_price_micros as u128)?;
+    numerator = mul(numerator, CENTS_PER_USD * TOKEN_BASE_UNITS)?;
+    let scale = 10_u128.checked_pow(decimals as u32).ok_or(ErrorCode::MathOverflow)?;
+    let denominator = mul(mul(scale, MICROS_PER_USD)?, fixed_price_cents as u128)?;
+
+    u64::try_from(numerator / denominator).map_err(|_| error!(ErrorCode::MathOverflow))
+}
+
+// Lamports paid for redeeming `tokens` base units at `bid_price_cents` per token;
+// the inverse of `tokens_for_lamports`:
+//
+//   tokens * bid_price_cents * LAMPORTS_PER_SOL
+//   --------------------------------------------------------
+//   TOKEN_BASE_UNITS * CENTS_PER_USD * price * 10^exponent
+pub fn lamports_for_tokens(tokens: u64, sol_usd: Price, bid_price_cents: u64) -> Result<u64> {
+    require!(sol_usd.price > 0, ErrorCode::InvalidPriceFeed);
+
+    let mut numerator = mul(tokens as u128, bid_price_cents as u128)?;
+    numerator = mul(numerator, LAMPORTS_PER_SOL)?;
+    let mut denominator = mul(TOKEN_BASE_UNITS * CENTS_PER_USD, sol_usd.price as u128)?;
+
+    let scale = 10_u128
+        .checked_pow(sol_usd.exponent.unsigned_abs())
+        .ok_or(ErrorCode::MathOverflow)?;
+    if sol_usd.exponent >= 0 {
+        denominator = mul(denominator, scale)?;
+    } else {
+        numerator = mul(numerator, scale)?;
+    }
+
+    u64::try_from(numerator / denominator).map_err(|_| error!(ErrorCode::MathOverflow))
+}
+
+// Payment token base units paid for redeeming `tokens` base units at
+// `bid_price_cents` per token; the inverse of `tokens_for_payment`:
+//
+//   tokens * bid_price_cents * 10^decimals * MICROS_PER_USD
+//   -------------------------------------------------------
+//     TOKEN_BASE_UNITS * CENTS_PER_USD * usd_price_micros
+pub fn payment_for_tokens(
+    tokens: u64,
+    decimals: u8,
+    usd_price_micros: u64,
+    bid_price_cents: u64,
+) -> Result<u64> {
+    require!(usd_price_micros > 0, ErrorCode::InvalidPrice);
+
+    let scale = 10_u128.checked_pow(decimals as u32).ok_or(ErrorCode::MathOverflow)?;
+    let mut numerator = mul(tokens as u128, bid_price_cents as u128)?;
+    numerator = mul(mul(numerator, scale)?, MICROS_PER_USD)?;
+    let denominator = TOKEN_BASE_UNITS * CENTS_PER_USD * usd_price_micros as u128;
+
+    u64::try_from(numerator / denominator).map_err(|_| error!(ErrorCode::MathOverflow))
+}
+
+// USD value of `lamports` at `sol_usd` per SOL in pico-dollars, the unit of
+// bonding curve costs:
+//
+//   lamports * price * 10^exponent * PICOS_PER_USD / LAMPORTS_PER_SOL
+pub fn value_of_lamports(lamports: u64, sol_usd: Price) -> Result<u128> {
+    let mut numerator = mul(mul(lamports as u128, sol_usd.price as u128)?, PICOS_PER_USD)?;
+    let mut denominator = LAMPORTS_PER_SOL;
+
+    let scale = 10_u128
+        .checked_pow(sol_usd.exponent.unsigned_abs())
+        .ok_or(ErrorCode::MathOverflow)?;
+    if sol_usd.exponent >= 0 {
+        numerator = mul(numerator, scale)?;
+    } else {
+        denominator = mul(denominator, scale)?;
+    }
+
+    Ok(numerator / denominator)
+}
+
+// USD value in pico-dollars of `amount` base units of a payment token with
+// `decimals` decimals, worth `usd_price_micros` per whole token:
+//
+//   amount * usd_price_micros * PICOS_PER_USD / (10^decimals * MICROS_PER_USD)
+pub fn value_of_payment(amount: u64, decimals: u8, usd_price_micros: u64) -> Result<u128> {
+    let numerator = mul(mul(amount as u128, usd_price_micros as u128)?, PICOS_PER_USD)?;
+    let scale = 10_u128.checked_pow(decimals as u32).ok_or(ErrorCode::MathOverflow)?;
+    Ok(numerator / mul(scale, MICROS_PER_USD)?)
+}
+
+fn mul(a: u128, b: u128) -> Result<u128> {
+    a.checked_mul(b).ok_or_else(|| error!(ErrorCode::MathOverflow))
+}
@@ -0,0 +1,130 @@
+// programs/usv-trading/src/vesting.rs - Vesting schedules for escrowed purchases
+//
+// Each vesting purchase is a tranche that unlocks linearly over `duration_secs`
+// from its `start_time`, but none until `cliff_secs` have passed. A buyer has a
+// single schedule however many of their purchases vest, holding up to
+// MAX_VESTING_TRANCHES tranches. Fully unlocked tranches are settled into
+// `unlocked` to make room, and once the schedule is full a deposit is merged
+// into the tranche ending last, keeping the later cliff and end of the two so no
+// token unlocks sooner than it would have. Unlocked amounts round down, so
+// rounding never releases tokens early.
+
+use anchor_lang::prelude::*;
+
+use crate::ErrorCode;
+
+pub const MAX_VESTING_TRANCHES: usize = 8;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct VestingTranche {
+    pub amount: u64,   // Base units locked by the purchase
+    pub released: u64, // Base units of `amount` already released
+    pub start_time: i64,
+    pub cliff_secs: i64,
+    pub duration_secs: i64,
+}
+
+impl VestingTranche {
+    // Base units of `amount` unlocked at `now`
+    pub fn vested(&self, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.start_time);
+        if elapsed < self.cliff_secs {
+            0
+        } else if elapsed >= self.duration_secs {
+            self.amount
+        } else {
+            (self.amount as u128 * elapsed as u128 / self.duration_secs as u128) as u64
+        }
+    }
+
+    // When the tranche's first tokens unlock
+    pub fn cliff_time(&self) -> i64 {
+        self.start_time.saturating_add(self.cliff_secs)
+    }
+
+    // When the whole tranche has unlocked
+    pub fn end_time(&self) -> i64 {
+        self.start_time.saturating_add(self.duration_secs)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct VestingSchedule {
+    #[max_len(MAX_VESTING_TRANCHES)]
+    pub tranches: Vec<VestingTranche>,
+    pub unlocked: u64, // Base units of settled tranches not yet released
+}
+
+impl VestingSchedule {
+    // Base units deposited and not yet released
+    pub fn total(&self) -> u64 {
+        self.unlocked + self.tranches.iter().map(|tranche| tranche.amount - tranche.released).sum::<u64>()
+    }
+
+    // Base units the buyer may release at `now`
+    pub fn releasable(&self, now: i64) -> u64 {
+        self.unlocked + self.tranches.iter().map(|tranche| tranche.vested(now) - tranche.released).sum::<u64>()
+    }
+
+    // Base units still locked at `now`
+    pub fn locked(&self, now: i64) -> u64 {
+        self.tranches.iter().map(|tranche| tranche.amount - tranche.vested(now)).sum()
+    }
+
+    // When the last tranche has fully unlocked
+    pub fn end_time(&self) -> i64 {
+        self.tranches.iter().map(VestingTranche::end_time).max().unwrap_or_default()
+    }
+
+    // Lock `amount` more from `now`, returning the tranche that holds it
+    pub fn deposit(&mut self, amount: u64, now: i64, cliff_secs: i64, duration_secs: i64) -> Result<VestingTranche> {
+        self.total().checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        let unlocked = &mut self.unlocked;
+        self.tranches.retain(|tranche| {
+            let settled = tranche.vested(now) == tranche.amount;
+            if settled {
+                *unlocked += tranche.amount - tranche.released;
+            }
+            !settled
+        });
+
+        // Purchases in the same second on the same terms share a tranche
+        if let Some(tranche) = self.tranches.iter_mut().find(|tranche| {
+            (tranche.start_time, tranche.cliff_secs, tranche.duration_secs) == (now, cliff_secs, duration_secs)
+        }) {
+            tranche.amount += amount;
+            return Ok(*tranche);
+        }
+
+        let mut tranche = VestingTranche {
+            amount,
+            released: 0,
+            start_time: now,
+            cliff_secs,
+            duration_secs,
+        };
+        if self.tranches.len() == MAX_VESTING_TRANCHES {
+            // Its unlocked part is settled and its locked part joins the deposit, which
+            // vests from now until the later of the two cliffs and the two ends
+            let (last, _) = self.tranches.iter().enumerate().max_by_key(|(_, t)| t.end_time()).unwrap();
+            let last = self.tranches.swap_remove(last);
+            let vested = last.vested(now);
+            self.unlocked += vested - last.released;
+            tranche.amount += last.amount - vested;
+            tranche.cliff_secs = cliff_secs.max(last.cliff_time() - now);
+            tranche.duration_secs = duration_secs.max(last.end_time() - now);
+        }
+        self.tranches.push(tranche);
+        Ok(tranche)
+    }
+
+    // Mark everything releasable at `now` as released and return how much that is
+    pub fn release(&mut self, now: i64) -> u64 {
+        let amount = self.releasable(now);
+        self.unlocked = 0;
+        for tranche in &mut self.tranches {
+            tranche.released = tranche.vested(now);
+        }
+        amount
+    }
+}
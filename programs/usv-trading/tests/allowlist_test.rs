@@ -0,0 +1,37 @@
+// programs/usv-trading/tests/allowlist_test.rs - Merkle allowlist proofs
+
+use anchor_lang::prelude::Pubkey;
+use proptest::prelude::*;
+use usv_trading::allowlist::{leaf, proof, root, verify};
+
+#[test]
+fn proves_small_trees() {
+    let wallets: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+    let leaves: Vec<[u8; 32]> = wallets.iter().map(leaf).collect();
+
+    // A single wallet is its own root and needs no proof
+    assert_eq!(root(&leaves[..1]), leaves[0]);
+    assert!(verify(&[], root(&leaves[..1]), leaves[0]));
+
+    // The odd third leaf is carried up and proven by the first pair's parent alone
+    let root = root(&leaves);
+    assert_eq!(proof(&leaves, 2).len(), 1);
+    for (index, leaf) in leaves.iter().enumerate() {
+        assert!(verify(&proof(&leaves, index), root, *leaf));
+    }
+    assert!(!verify(&proof(&leaves, 0), root, leaf(&Pubkey::new_unique())));
+    assert!(!verify(&proof(&leaves, 0), root, leaves[1]));
+}
+
+proptest! {
+    // Every member proves membership and nobody else does with a member's proof
+    #[test]
+    fn proofs_verify_only_members(count in 1usize..40, index in any::<prop::sample::Index>()) {
+        let leaves: Vec<[u8; 32]> = (0..count).map(|_| leaf(&Pubkey::new_unique())).collect();
+        let root = root(&leaves);
+        let index = index.index(count);
+        let proof = proof(&leaves, index);
+        prop_assert!(verify(&proof, root, leaves[index]));
+        prop_assert!(!verify(&proof, root, leaf(&Pubkey::new_unique())));
+    }
+}
@@ -0,0 +1,91 @@
+// programs/usv-trading/tests/amm_test.rs - Properties of the constant-product pool math
+
+use proptest::prelude::*;
+use usv_trading::amm::{initial_liquidity, pro_rata, quote_swap, usv_for_deposit, MINIMUM_LIQUIDITY};
+
+// Reserves from a thousand base units up to a billion whole tokens or SOL
+fn reserve() -> impl Strategy<Value = u64> {
+    1_000u64..1_000_000_000_000_000_000
+}
+
+fn fee_bps() -> impl Strategy<Value = u64> {
+    0u64..=1_000
+}
+
+#[test]
+fn quotes_known_swaps() {
+    // 1 SOL into 100 SOL / 10,000 USV with no fee: 10,000 * 1 / 101
+    let quote = quote_swap(1_000_000_000, 100_000_000_000, 10_000_000_000, 0).unwrap();
+    assert_eq!(quote.amount_out, 99_009_900);
+    assert_eq!(quote.fee, 0);
+    assert_eq!(quote.price_impact_bps, 100);
+
+    // A 0.3% fee is taken from the input and rounds up
+    let quote = quote_swap(1_000_000_000, 100_000_000_000, 10_000_000_000, 30).unwrap();
+    assert_eq!(quote.fee, 3_000_000);
+    assert_eq!(quote.amount_out, 98_715_803);
+    assert_eq!(quote_swap(1, 1_000, 1_000, 30).unwrap().fee, 1);
+
+    assert!(quote_swap(1_000, 0, 1_000, 30).is_err());
+    assert!(quote_swap(1_000, 1_000, 0, 30).is_err());
+}
+
+#[test]
+fn quotes_known_deposits() {
+    // The first deposit mints the geometric mean of the two amounts
+    assert_eq!(initial_liquidity(100_000_000_000, 10_000_000_000).unwrap(), 31_622_776_601);
+    assert!(initial_liquidity(1_000, 1_000).is_err());
+    assert_eq!(initial_liquidity(1_001, 1_001).unwrap(), MINIMUM_LIQUIDITY + 1);
+
+    // Later deposits round the required USV up and the minted LP down
+    assert_eq!(usv_for_deposit(1, 3, 10).unwrap(), 4);
+    assert_eq!(pro_rata(1, 3, 10).unwrap(), 3);
+    assert!(usv_for_deposit(1, 0, 10).is_err());
+    assert!(pro_rata(1, 0, 10).is_err());
+}
+
+proptest! {
+    // Fees stay in the pool, so the product of the reserves never falls
+    #[test]
+    fn swaps_never_decrease_k(amount_in in 0u64..1_000_000_000_000_000, reserve_in in reserve(), reserve_out in reserve(), fee in fee_bps()) {
+        let quote = quote_swap(amount_in, reserve_in, reserve_out, fee).unwrap();
+        prop_assert!(quote.amount_out < reserve_out);
+        let k_before = reserve_in as u128 * reserve_out as u128;
+        let k_after = (reserve_in as u128 + amount_in as u128) * (reserve_out - quote.amount_out) as u128;
+        prop_assert!(k_after >= k_before);
+    }
+
+    // Swapping straight back never returns more than went in
+    #[test]
+    fn round_trips_never_profit(amount_in in 0u64..1_000_000_000_000_000, reserve_in in reserve(), reserve_out in reserve(), fee in fee_bps()) {
+        let there = quote_swap(amount_in, reserve_in, reserve_out, fee).unwrap();
+        let back = quote_swap(
+            there.amount_out,
+            reserve_out - there.amount_out,
+            reserve_in + amount_in,
+            fee,
+        ).unwrap();
+        prop_assert!(back.amount_out <= amount_in);
+    }
+
+    // The reported impact never understates how far the execution price falls short of spot
+    #[test]
+    fn price_impact_is_bounded(amount_in in 1u64..1_000_000_000_000_000, reserve_in in reserve(), reserve_out in reserve()) {
+        let quote = quote_swap(amount_in, reserve_in, reserve_out, 0).unwrap();
+        prop_assert!(quote.price_impact_bps <= 10_000);
+        // amount_out / amount_in >= spot * (1 - impact)
+        let spot_out = amount_in as u128 * reserve_out as u128;
+        let floor = spot_out * (10_000 - quote.price_impact_bps as u128);
+        prop_assert!((quote.amount_out as u128 + 1) * reserve_in as u128 * 10_000 >= floor);
+    }
+
+    // Depositing and immediately withdrawing never returns more than was deposited
+    #[test]
+    fn liquidity_round_trips_never_profit(sol in 1u64..1_000_000_000_000_000, reserve_sol in reserve(), reserve_usv in reserve(), supply in reserve()) {
+        let usv = usv_for_deposit(sol, reserve_sol, reserve_usv).unwrap();
+        let minted = pro_rata(sol, reserve_sol, supply).unwrap();
+        let (reserve_sol, reserve_usv, supply) = (reserve_sol + sol, reserve_usv + usv, supply + minted);
+        prop_assert!(pro_rata(minted, supply, reserve_sol).unwrap() <= sol);
+        prop_assert!(pro_rata(minted, supply, reserve_usv).unwrap() <= usv);
+    }
+}
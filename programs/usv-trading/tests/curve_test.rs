@@ -0,0 +1,108 @@
+// programs/usv-trading/tests/curve_test.rs - Properties of bonding curve pricing
+
+use proptest::prelude::*;
+use usv_trading::curve::{BondingCurve, CurveKind};
+use usv_trading::oracle::Price;
+use usv_trading::pricing::{value_of_lamports, value_of_payment};
+
+const TOKEN: u64 = 1_000_000;
+
+fn linear(start_price_micros: u64, slope_micros: u64) -> BondingCurve {
+    BondingCurve {
+        kind: CurveKind::Linear { slope_micros },
+        start_price_micros,
+        supply_cap: 0,
+    }
+}
+
+fn exponential(start_price_micros: u64, growth_bps: u64, step: u64) -> BondingCurve {
+    BondingCurve {
+        kind: CurveKind::Exponential { growth_bps, step },
+        start_price_micros,
+        supply_cap: 0,
+    }
+}
+
+// Starting prices from $0.0001 to $10, rising by up to $0.001 per token or
+// up to 10% per ten thousand tokens
+fn curve() -> impl Strategy<Value = BondingCurve> {
+    prop_oneof![
+        (100u64..10_000_000, 0u64..1_000).prop_map(|(start, slope)| linear(start, slope)),
+        (100u64..10_000_000, 1u64..1_000, 10_000 * TOKEN..1_000_000 * TOKEN)
+            .prop_map(|(start, growth, step)| exponential(start, growth, step)),
+    ]
+}
+
+// Up to a million tokens sold or bought
+fn tokens() -> impl Strategy<Value = u64> {
+    0u64..1_000_000 * TOKEN
+}
+
+#[test]
+fn quotes_known_curves() {
+    // From 20 to 30 cents over the first 100 tokens: $25 on average
+    let curve = linear(200_000, 1_000);
+    assert_eq!(curve.price_micros(100 * TOKEN).unwrap(), 300_000);
+    assert_eq!(curve.cost(0, 100 * TOKEN).unwrap(), 25_000_000_000_000);
+    assert_eq!(curve.tokens_for_value(0, 25_000_000_000_000).unwrap(), 100 * TOKEN);
+
+    // 10% more every 100 tokens: $20 for the first hundred, $22 for the next
+    let curve = exponential(200_000, 1_000, 100 * TOKEN);
+    assert_eq!(curve.price_micros(150 * TOKEN).unwrap(), 220_000);
+    assert_eq!(curve.cost(0, 100 * TOKEN).unwrap(), 20_000_000_000_000);
+    assert_eq!(curve.cost(0, 300 * TOKEN).unwrap(), 66_200_000_000_000);
+    assert_eq!(curve.cost(50 * TOKEN, 100 * TOKEN).unwrap(), 21_000_000_000_000);
+
+    // A capped curve refuses purchases that would pass the cap
+    let capped = BondingCurve { supply_cap: 100 * TOKEN, ..linear(200_000, 1_000) };
+    assert_eq!(capped.tokens_for_value(0, 25_000_000_000_000).unwrap(), 100 * TOKEN);
+    assert!(capped.tokens_for_value(0, 26_000_000_000_000).is_err());
+
+    assert!(linear(0, 1_000).validate().is_err());
+    assert!(exponential(200_000, 0, TOKEN).validate().is_err());
+    assert!(exponential(200_000, 1_000, 0).validate().is_err());
+}
+
+#[test]
+fn values_payments() {
+    // 1 SOL at $150 and 10 USDC, in pico-dollars
+    let sol_150 = Price { price: 150_00000000, exponent: -8 };
+    assert_eq!(value_of_lamports(1_000_000_000, sol_150).unwrap(), 150_000_000_000_000);
+    assert_eq!(value_of_payment(10_000_000, 6, 1_000_000).unwrap(), 10_000_000_000_000);
+}
+
+proptest! {
+    // Every base unit costs at least the price when the purchase starts and at
+    // most the price when it ends
+    #[test]
+    fn cost_is_bounded_by_prices(curve in curve(), sold in tokens(), amount in tokens()) {
+        let cost = curve.cost(sold, amount).unwrap();
+        let low = amount as u128 * curve.price_micros(sold).unwrap() as u128;
+        let high = amount as u128 * (curve.price_micros(sold + amount).unwrap() as u128 + 1);
+        prop_assert!(low <= cost && cost <= high);
+    }
+
+    // Buying in two parts never costs less than buying at once
+    #[test]
+    fn split_purchases_never_save(start in 100u64..10_000_000, slope in 0u64..1_000, sold in tokens(), a in tokens(), b in tokens()) {
+        let curve = linear(start, slope);
+        let whole = curve.cost(sold, a + b).unwrap();
+        let parts = curve.cost(sold, a).unwrap() + curve.cost(sold + a, b).unwrap();
+        prop_assert!(parts >= whole && parts - whole <= 1);
+    }
+
+    // The buyer gets every base unit they can afford and no more
+    #[test]
+    fn buys_the_most_affordable(curve in curve(), sold in tokens(), value in 0u128..1_000_000_000_000_000_000) {
+        let tokens = curve.tokens_for_value(sold, value).unwrap();
+        prop_assert!(curve.cost(sold, tokens).unwrap() <= value);
+        prop_assert!(curve.cost(sold, tokens + 1).unwrap() > value);
+    }
+
+    // Later buyers never pay less for the same amount
+    #[test]
+    fn price_never_falls(curve in curve(), a in tokens(), b in tokens(), amount in tokens()) {
+        let (low, high) = (a.min(b), a.max(b));
+        prop_assert!(curve.cost(low, amount).unwrap() <= curve.cost(high, amount).unwrap());
+    }
+}
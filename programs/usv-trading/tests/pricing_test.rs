@@ -0,0 +1,129 @@
+// programs/usv-trading/tests/pricing_test.rs - Properties of the integer token pricing
+
+use proptest::prelude::*;
+use usv_trading::oracle::Price;
+use usv_trading::pricing::{
+    lamports_for_purchase, lamports_for_tokens, payment_for_tokens, tokens_for_lamports, tokens_for_payment,
+    CENTS_PER_USD, LAMPORTS_PER_SOL, TOKEN_BASE_UNITS,
+};
+
+// Pyth SOL/USD uses exponent -8; prices here range from $0.01 to $100,000
+fn sol_usd() -> impl Strategy<Value = Price> {
+    (1_000_000u64..10_000_000_000_000).prop_map(|price| Price { price, exponent: -8 })
+}
+
+// Up to 1M SOL per purchase
+fn lamports() -> impl Strategy<Value = u64> {
+    0u64..1_000_000 * LAMPORTS_PER_SOL as u64
+}
+
+// What the buyer pays and what `tokens` cost, both in the exact common unit of
+// 10^-8 cents / (LAMPORTS_PER_SOL * TOKEN_BASE_UNITS)
+fn paid(lamports: u64, price: Price) -> u128 {
+    lamports as u128 * price.price as u128 * CENTS_PER_USD * TOKEN_BASE_UNITS
+}
+
+fn cost(tokens: u64, fixed_price_cents: u64) -> u128 {
+    tokens as u128 * fixed_price_cents as u128 * LAMPORTS_PER_SOL * 100_000_000
+}
+
+#[test]
+fn quotes_known_prices() {
+    let sol_150 = Price { price: 150_00000000, exponent: -8 };
+    assert_eq!(tokens_for_lamports(1_000_000_000, sol_150, 20).unwrap(), 750_000_000);
+    assert_eq!(tokens_for_lamports(1_000_000_000, sol_150, 25).unwrap(), 600_000_000);
+
+    // $150 / 7 cents = 2142.857142... tokens, rounded down to the base unit
+    assert_eq!(tokens_for_lamports(1_000_000_000, sol_150, 7).unwrap(), 2_142_857_142);
+
+    // Positive exponents scale the price up
+    let sol_150_e0 = Price { price: 150, exponent: 0 };
+    assert_eq!(tokens_for_lamports(1_000_000_000, sol_150_e0, 20).unwrap(), 750_000_000);
+}
+
+#[test]
+fn rejects_zero_price_and_overflow() {
+    let sol_150 = Price { price: 150_00000000, exponent: -8 };
+    assert!(tokens_for_lamports(1_000_000_000, sol_150, 0).is_err());
+    assert!(tokens_for_lamports(u64::MAX, Price { price: u64::MAX, exponent: 0 }, 1).is_err());
+}
+
+#[test]
+fn quotes_stablecoin_payments() {
+    // 10 USDC (6 decimals) at $1 buys 50 tokens at 20 cents
+    assert_eq!(tokens_for_payment(10_000_000, 6, 1_000_000, 20).unwrap(), 50_000_000);
+    // A 9-decimal token worth $0.9995 buys proportionally less
+    assert_eq!(tokens_for_payment(10_000_000_000, 9, 999_500, 20).unwrap(), 49_975_000);
+    // One base unit of a 6-decimal dollar is 5 token base units; of a 9-decimal dollar, dust
+    assert_eq!(tokens_for_payment(1, 6, 1_000_000, 20).unwrap(), 5);
+    assert_eq!(tokens_for_payment(1, 9, 1_000_000, 20).unwrap(), 0);
+
+    assert!(tokens_for_payment(10_000_000, 6, 1_000_000, 0).is_err());
+    assert!(tokens_for_payment(u64::MAX, 0, u64::MAX, 1).is_err());
+}
+
+#[test]
+fn quotes_redemptions() {
+    let sol_150 = Price { price: 150_00000000, exponent: -8 };
+    // 750 tokens at a 10 cent bid are $75, half a SOL at $150
+    assert_eq!(lamports_for_tokens(750_000_000, sol_150, 10).unwrap(), 500_000_000);
+    assert_eq!(lamports_for_tokens(750_000_000, Price { price: 150, exponent: 0 }, 10).unwrap(), 500_000_000);
+    // 50 tokens at a 10 cent bid are 5 USDC
+    assert_eq!(payment_for_tokens(50_000_000, 6, 1_000_000, 10).unwrap(), 5_000_000);
+
+    // A single base unit is worth less than a lamport and rounds down to nothing
+    assert_eq!(lamports_for_tokens(1, sol_150, 10).unwrap(), 0);
+    // but costs a whole lamport to buy
+    assert_eq!(lamports_for_purchase(1, sol_150, 10).unwrap(), 1);
+    assert_eq!(lamports_for_purchase(750_000_000, sol_150, 20).unwrap(), 1_000_000_000);
+    assert!(payment_for_tokens(50_000_000, 6, 0, 10).is_err());
+}
+
+proptest! {
+    // The buyer never receives more than they paid for, and never loses a whole base unit
+    #[test]
+    fn rounds_down_to_the_base_unit(lamports in lamports(), price in sol_usd(), cents in 1u64..10_000) {
+        let tokens = tokens_for_lamports(lamports, price, cents).unwrap();
+        prop_assert!(cost(tokens, cents) <= paid(lamports, price));
+        prop_assert!(cost(tokens + 1, cents) > paid(lamports, price));
+    }
+
+    #[test]
+    fn more_sol_never_buys_fewer_tokens(a in lamports(), b in lamports(), price in sol_usd(), cents in 1u64..10_000) {
+        let (low, high) = (a.min(b), a.max(b));
+        prop_assert!(
+            tokens_for_lamports(low, price, cents).unwrap() <= tokens_for_lamports(high, price, cents).unwrap()
+        );
+    }
+
+    #[test]
+    fn higher_token_price_never_buys_more(lamports in lamports(), price in sol_usd(), a in 1u64..10_000, b in 1u64..10_000) {
+        let (low, high) = (a.min(b), a.max(b));
+        prop_assert!(
+            tokens_for_lamports(lamports, price, high).unwrap() <= tokens_for_lamports(lamports, price, low).unwrap()
+        );
+    }
+
+    // Selling back what was just bought at the same price never pays out more than was paid
+    #[test]
+    fn round_trips_never_profit(lamports in lamports(), price in sol_usd(), cents in 1u64..10_000) {
+        let tokens = tokens_for_lamports(lamports, price, cents).unwrap();
+        prop_assert!(lamports_for_tokens(tokens, price, cents).unwrap() <= lamports);
+    }
+
+    // Paying the quoted cost of a purchase always buys at least that many tokens
+    #[test]
+    fn purchase_costs_round_up(tokens in 0u64..1_000_000_000_000_000, price in sol_usd(), cents in 1u64..10_000) {
+        let lamports = lamports_for_purchase(tokens, price, cents).unwrap();
+        prop_assert!(tokens_for_lamports(lamports, price, cents).unwrap() >= tokens);
+        prop_assert!(lamports == 0 || tokens_for_lamports(lamports - 1, price, cents).unwrap() < tokens);
+    }
+
+    // Splitting a purchase can only lose rounding dust, at most one base unit per part
+    #[test]
+    fn split_purchases_lose_at_most_rounding(a in lamports(), b in lamports(), price in sol_usd(), cents in 1u64..10_000) {
+        let whole = tokens_for_lamports(a + b, price, cents).unwrap();
+        let parts = tokens_for_lamports(a, price, cents).unwrap() + tokens_for_lamports(b, price, cents).unwrap();
+        prop_assert!(parts <= whole && whole - parts <= 1);
+    }
+}
@@ -0,0 +1,106 @@
+// programs/usv-trading/tests/vesting_test.rs - Properties of vesting schedules
+
+use proptest::prelude::*;
+use usv_trading::vesting::{VestingSchedule, VestingTranche, MAX_VESTING_TRANCHES};
+
+const TOKEN: u64 = 1_000_000;
+
+#[test]
+fn unlocks_after_the_cliff() {
+    // 1,000 tokens over 1,000 seconds with a 100 second cliff
+    let mut schedule = VestingSchedule::default();
+    schedule.deposit(1_000 * TOKEN, 0, 100, 1_000).unwrap();
+    assert_eq!(schedule.releasable(99), 0);
+    assert_eq!(schedule.releasable(100), 100 * TOKEN);
+    assert_eq!(schedule.release(500), 500 * TOKEN);
+    assert_eq!(schedule.releasable(500), 0);
+    assert_eq!(schedule.locked(500), 500 * TOKEN);
+
+    // A deposit leaves the earlier purchase on its own schedule: its last 250
+    // still unlock by 1,000 while the new 500 only pass their cliff at 850
+    let tranche = schedule.deposit(500 * TOKEN, 750, 100, 1_000).unwrap();
+    assert_eq!((tranche.cliff_time(), tranche.end_time()), (850, 1_750));
+    assert_eq!(schedule.releasable(750), 250 * TOKEN);
+    assert_eq!(schedule.locked(750), 750 * TOKEN);
+    assert_eq!(schedule.release(1_000), 625 * TOKEN);
+    assert_eq!((schedule.total(), schedule.end_time()), (375 * TOKEN, 1_750));
+    assert_eq!(schedule.release(1_750), 375 * TOKEN);
+    assert_eq!(schedule.releasable(u32::MAX as i64), 0);
+}
+
+#[test]
+fn full_schedules_merge_deposits() {
+    let mut schedule = VestingSchedule::default();
+    // Purchases in the same second on the same terms share a tranche
+    for i in 0..MAX_VESTING_TRANCHES as i64 {
+        schedule.deposit(500 * TOKEN, i * 100, 0, 1_000).unwrap();
+        schedule.deposit(500 * TOKEN, i * 100, 0, 1_000).unwrap();
+    }
+    assert_eq!(schedule.tranches.len(), MAX_VESTING_TRANCHES);
+
+    // The last tranche (700 to 1,700) has unlocked 90 at 790; they stay releasable
+    // while its locked 910 join the new deposit, vesting from 790 to 1,790
+    let tranche = schedule.deposit(100 * TOKEN, 790, 500, 1_000).unwrap();
+    assert_eq!((tranche.amount, tranche.cliff_time(), tranche.end_time()), (1_010 * TOKEN, 1_290, 1_790));
+    assert_eq!(schedule.tranches.len(), MAX_VESTING_TRANCHES);
+    assert_eq!(schedule.unlocked, 90 * TOKEN);
+    assert_eq!(schedule.total(), 8_100 * TOKEN);
+
+    // The first tranche has fully unlocked by 1,000, so it is settled to make room
+    schedule.deposit(TOKEN, 1_000, 0, 1_000).unwrap();
+    assert_eq!(schedule.unlocked, 1_090 * TOKEN);
+    assert_eq!(schedule.tranches.len(), MAX_VESTING_TRANCHES);
+    assert_eq!(schedule.release(2_000), 8_101 * TOKEN);
+    assert_eq!(schedule.total(), 0);
+}
+
+proptest! {
+    // However purchases and releases interleave, every token deposited is
+    // released, releasable or locked, and all are released by the end
+    #[test]
+    fn releases_everything_once(
+        deposits in prop::collection::vec((1u64..1_000_000 * TOKEN, 0i64..10_000), 1..24),
+        releases in prop::collection::vec(0i64..10_000, 0..24),
+        cliff_secs in 0i64..1_000,
+        duration_secs in 1_000i64..100_000,
+    ) {
+        let mut schedule = VestingSchedule::default();
+        let (mut now, mut deposited, mut released) = (0, 0, 0);
+        for (i, (amount, wait)) in deposits.iter().enumerate() {
+            now += wait;
+            schedule.deposit(*amount, now, cliff_secs, duration_secs).unwrap();
+            deposited += amount;
+            if let Some(wait) = releases.get(i) {
+                released += schedule.release(now + wait);
+                now += wait;
+            }
+            prop_assert_eq!(released + schedule.releasable(now) + schedule.locked(now), deposited);
+        }
+        released += schedule.release(schedule.end_time());
+        prop_assert_eq!(released, deposited);
+    }
+}
+
+proptest! {
+    // Merging into a full schedule never unlocks a token sooner than keeping every
+    // purchase in its own tranche would, beyond a base unit of rounding per merge
+    #[test]
+    fn merging_never_unlocks_early(
+        deposits in prop::collection::vec((1u64..1_000_000 * TOKEN, 0i64..1_000, 0i64..1_000, 1i64..10_000), 1..24),
+        at in prop::collection::vec(0i64..100_000, 1..8),
+    ) {
+        let mut schedule = VestingSchedule::default();
+        let mut separate = Vec::new();
+        let mut now = 0;
+        for (amount, wait, cliff_secs, extra_secs) in deposits.iter() {
+            now += wait;
+            let duration_secs = cliff_secs + extra_secs;
+            schedule.deposit(*amount, now, *cliff_secs, duration_secs).unwrap();
+            separate.push(VestingTranche { amount: *amount, released: 0, start_time: now, cliff_secs: *cliff_secs, duration_secs });
+        }
+        for t in at.iter().map(|t| now + t) {
+            let unlocked: u64 = separate.iter().map(|tranche| tranche.vested(t)).sum();
+            prop_assert!(schedule.releasable(t) <= unlocked + deposits.len() as u64);
+        }
+    }
+}
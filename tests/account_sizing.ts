@@ -0,0 +1,198 @@
+import * as anchor from "@coral-xyz/anchor";
+import { PublicKey, Connection, SystemProgram, Keypair } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID, getAssociatedTokenAddressSync } from "@solana/spl-token";
+import { assert } from "chai";
+
+// Must match the limits in programs/usv-token/src/lib.rs
+const MAX_PARTNER_ID_LEN = 32;
+const MAX_BATCH_INFO_LEN = 128;
+const MAX_USER_EMAIL_LEN = 64;
+const MAX_QR_CODES_PER_APPEND = 200;
+const QR_CODE_LEN = 16;
+const QR_BATCH_HEADER_LEN = 8 + 248; // discriminator + zero-copy QRBatch header
+
+describe("USV Token Account Sizing", () => {
+  const connection = new Connection("http://localhost:8899", "confirmed");
+  const idl = require("../target/idl/usv_token.json");
+  const programId = new PublicKey(idl.metadata.address);
+
+  const authority = Keypair.generate();
+  const wallet = new anchor.Wallet(authority);
+  const provider = new anchor.AnchorProvider(connection, wallet, {});
+  const program = new anchor.Program(idl, programId, provider);
+
+  const [usvStatePDA] = PublicKey.findProgramAddressSync([Buffer.from("usv_state")], programId);
+  const [loyaltyTiersPDA] = PublicKey.findProgramAddressSync([Buffer.from("loyalty_tiers")], programId);
+  const [mintPDA] = PublicKey.findProgramAddressSync([Buffer.from("mint")], programId);
+  const [mintAuthorityPDA] = PublicKey.findProgramAddressSync([Buffer.from("mint_authority")], programId);
+  const authorityTokenAccount = getAssociatedTokenAddressSync(mintPDA, authority.publicKey);
+
+  const batchPDA = (batchNumber: number) => {
+    const seed = Buffer.alloc(4);
+    seed.writeUInt32LE(batchNumber);
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("qr_batch"), authority.publicKey.toBuffer(), seed],
+      programId
+    )[0];
+  };
+
+  const readQrCode = async (qrBatch: PublicKey, index: number) => {
+    const info = await connection.getAccountInfo(qrBatch);
+    const start = QR_BATCH_HEADER_LEN + index * QR_CODE_LEN;
+    return Array.from(info!.data.subarray(start, start + QR_CODE_LEN));
+  };
+
+  before(async () => {
+    const signature = await connection.requestAirdrop(authority.publicKey, 20 * anchor.web3.LAMPORTS_PER_SOL);
+    await connection.confirmTransaction(signature);
+
+    await program.methods
+      .initialize()
+      .accounts({
+        usvState: usvStatePDA,
+        loyaltyTiers: loyaltyTiersPDA,
+        mint: mintPDA,
+        mintAuthority: mintAuthorityPDA,
+        authorityTokenAccount,
+        authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+  });
+
+  it("Fits a maximal batch with maximal strings", async () => {
+    const qrBatch = batchPDA(0);
+
+    await program.methods
+      .generateQrCodes(MAX_QR_CODES_PER_APPEND, "P".repeat(MAX_PARTNER_ID_LEN), "B".repeat(MAX_BATCH_INFO_LEN))
+      .accounts({
+        usvState: usvStatePDA,
+        qrBatch,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .preInstructions([anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 })])
+      .rpc();
+
+    const batch = await program.account.qrBatch.fetch(qrBatch);
+    const info = await connection.getAccountInfo(qrBatch);
+    assert.equal(batch.count, MAX_QR_CODES_PER_APPEND);
+    assert.equal(batch.batchInfoLen, MAX_BATCH_INFO_LEN);
+    assert.equal(info!.data.length, QR_BATCH_HEADER_LEN + MAX_QR_CODES_PER_APPEND * QR_CODE_LEN);
+    console.log("✅ Maximal batch fits");
+  });
+
+  it("Rejects over-long batch info", async () => {
+    try {
+      await program.methods
+        .generateQrCodes(1, null, "B".repeat(MAX_BATCH_INFO_LEN + 1))
+        .accounts({
+          usvState: usvStatePDA,
+          qrBatch: batchPDA(1),
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Expected BatchInfoTooLong");
+    } catch (err: any) {
+      assert.equal(err.error?.errorCode?.code, "BatchInfoTooLong");
+    }
+  });
+
+  it("Rejects batches above the maximum count", async () => {
+    try {
+      await program.methods
+        .generateQrCodes(MAX_QR_CODES_PER_APPEND + 1, null, "")
+        .accounts({
+          usvState: usvStatePDA,
+          qrBatch: batchPDA(1),
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Expected InvalidQRCodeCount");
+    } catch (err: any) {
+      assert.equal(err.error?.errorCode?.code, "InvalidQRCodeCount");
+    }
+  });
+
+  it("Fits a claim with a maximal email", async () => {
+    const qrBatch = batchPDA(0);
+    const qrCode = await readQrCode(qrBatch, 0);
+    const claimer = Keypair.generate();
+    const [qrClaim] = PublicKey.findProgramAddressSync([Buffer.from("qr_claim"), Buffer.from(qrCode)], programId);
+
+    await program.methods
+      .claimTokens(qrCode, 0, "e".repeat(MAX_USER_EMAIL_LEN), null)
+      .accounts({
+        usvState: usvStatePDA,
+        qrBatch,
+        batchBreaker: null, // The per-batch circuit breaker is off
+        qrClaim,
+        authorityTokenAccount,
+        claimerTokenAccount: getAssociatedTokenAddressSync(mintPDA, claimer.publicKey),
+        referral: PublicKey.findProgramAddressSync([Buffer.from("referral"), claimer.publicKey.toBuffer()], programId)[0],
+        userProfile: PublicKey.findProgramAddressSync([Buffer.from("user_profile"), claimer.publicKey.toBuffer()], programId)[0],
+        loyaltyTiers: loyaltyTiersPDA,
+        referrerReferral: null,
+        referrerTokenAccount: null,
+        mint: mintPDA,
+        authority: authority.publicKey,
+        claimer: claimer.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const claim = await program.account.qrClaim.fetch(qrClaim);
+    assert.equal(claim.userEmail.length, MAX_USER_EMAIL_LEN);
+    console.log("✅ Maximal claim fits");
+  });
+
+  it("Grows a batch across appends and freezes it when sealed", async () => {
+    const qrBatch = batchPDA(1);
+    const budget = anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 });
+
+    await program.methods
+      .createBatch("P".repeat(MAX_PARTNER_ID_LEN), "production run")
+      .accounts({
+        usvState: usvStatePDA,
+        qrBatch,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    for (let i = 0; i < 2; i++) {
+      await program.methods
+        .appendQrCodes(MAX_QR_CODES_PER_APPEND)
+        .accounts({ usvState: usvStatePDA, qrBatch, authority: authority.publicKey, systemProgram: SystemProgram.programId })
+        .preInstructions([budget])
+        .rpc();
+    }
+
+    await program.methods
+      .sealBatch()
+      .accounts({ usvState: usvStatePDA, qrBatch, authority: authority.publicKey })
+      .rpc();
+
+    const batch = await program.account.qrBatch.fetch(qrBatch);
+    assert.equal(batch.count, 2 * MAX_QR_CODES_PER_APPEND);
+    assert.equal(batch.isSealed, 1);
+
+    try {
+      await program.methods
+        .appendQrCodes(1)
+        .accounts({ usvState: usvStatePDA, qrBatch, authority: authority.publicKey, systemProgram: SystemProgram.programId })
+        .rpc();
+      assert.fail("Expected BatchSealed");
+    } catch (err: any) {
+      assert.equal(err.error?.errorCode?.code, "BatchSealed");
+    }
+    console.log("✅ Batch grown to", batch.count, "codes and sealed");
+  });
+});